@@ -0,0 +1,100 @@
+use chrono::{DateTime, Datelike, Local};
+use eframe::egui::{self, Color32, Context};
+
+use crate::timezone::BoardZone;
+use crate::{generate_title, AppState, Todo};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Group {
+    Overdue,
+    Today,
+    ThisWeek,
+    Later,
+    Undated,
+}
+
+impl Group {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Overdue => "Overdue",
+            Self::Today => "Today",
+            Self::ThisWeek => "This week",
+            Self::Later => "Later",
+            Self::Undated => "No due date",
+        }
+    }
+
+    /// Groups by the board zone's calendar; the week ends on Sunday.
+    fn of(todo: &Todo, now: DateTime<Local>, zone: BoardZone) -> Self {
+        let Some(due) = todo.due else {
+            return Self::Undated;
+        };
+        if due < now {
+            return Self::Overdue;
+        }
+        let today = zone.wall_clock(now).date();
+        let day = zone.wall_clock(due).date();
+        let days_left_in_week = 6 - today.weekday().num_days_from_monday() as i64;
+        if day == today {
+            Self::Today
+        } else if (day - today).num_days() <= days_left_in_week {
+            Self::ThisWeek
+        } else {
+            Self::Later
+        }
+    }
+}
+
+/// Side panel listing open todos by due date, grouped into Overdue / Today /
+/// This week / Later. Returns the id of the todo the user clicked, if any.
+pub fn agenda_panel(ctx: &Context, state: &AppState, now: DateTime<Local>) -> Option<usize> {
+    let zone = state.zone();
+    let mut todos: Vec<(Group, &Todo)> = state
+        .todos
+        .iter()
+        .filter(|t| !t.is_done)
+        .map(|t| (Group::of(t, now, zone), t))
+        .collect();
+    todos.sort_by_key(|(group, t)| (*group, t.due, t.id));
+
+    let mut clicked = None;
+    egui::SidePanel::right("agenda")
+        .resizable(true)
+        .default_width(220.0)
+        .show(ctx, |ui| {
+            ui.heading("Agenda");
+            if todos.is_empty() {
+                ui.weak("Nothing to do.");
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let mut current = None;
+                for (group, todo) in &todos {
+                    if current != Some(*group) {
+                        current = Some(*group);
+                        let count = todos.iter().filter(|(g, _)| g == group).count();
+                        ui.add_space(4.0);
+                        let title = format!("{} ({})", group.title(), count);
+                        if *group == Group::Overdue {
+                            ui.colored_label(Color32::LIGHT_RED, title);
+                        } else {
+                            ui.strong(title);
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if let Some(due) = todo.due {
+                            let format = if *group == Group::Today {
+                                "%H:%M"
+                            } else {
+                                "%a %d %b %H:%M"
+                            };
+                            ui.weak(zone.wall_clock(due).format(format).to_string());
+                        }
+                        if ui.link(generate_title(&todo.text)).clicked() {
+                            clicked = Some(todo.id);
+                        }
+                    });
+                }
+            });
+        });
+    clicked
+}
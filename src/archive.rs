@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, Context};
+
+use crate::{generate_title, AppState};
+
+impl AppState {
+    /// Moves done todo `id` off the canvas into the archive. Open todos stay
+    /// put, since the archive only holds finished work.
+    pub fn archive_todo(&mut self, id: usize) -> bool {
+        let Some(i) = self.todos.iter().position(|t| t.id == id && t.is_done) else {
+            return false;
+        };
+        let todo = self.todos.remove(i);
+        self.archived.push(todo);
+        self.selected.remove(&id);
+        if self.focus == Some(id) {
+            self.focus = None;
+        }
+        true
+    }
+
+    /// Puts archived todo `id` back on the canvas where it was, links and all.
+    pub fn restore_archived(&mut self, id: usize) {
+        if let Some(i) = self.archived.iter().position(|t| t.id == id) {
+            let todo = self.archived.remove(i);
+            self.todos.push(todo);
+        }
+    }
+
+    /// Deletes archived todos for good, with their links.
+    fn purge_archived(&mut self, ids: &HashSet<usize>) {
+        self.archived.retain(|t| !ids.contains(&t.id));
+        self.prune_dangling_refs();
+    }
+}
+
+/// Window listing archived todos, most recently completed first, each with
+/// Restore and Delete buttons. Returns the id of a todo that was restored,
+/// so the canvas can move to it.
+pub fn archive_window(ctx: &Context, state: &mut AppState, read_only: bool) -> Option<usize> {
+    let mut open = state.archive_open;
+    let mut restored = None;
+    let mut purge = HashSet::new();
+    let zone = state.zone();
+    egui::Window::new(format!("Archive ({})", state.archived.len()))
+        .id(egui::Id::new("archive_window"))
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            if state.archived.is_empty() {
+                ui.weak("Nothing archived yet. Done todos can be archived from their menu.");
+                return;
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut state.archive_search)
+                    .hint_text("Search the archive"),
+            );
+            let needle = state.archive_search.to_lowercase();
+            let mut todos: Vec<_> = state
+                .archived
+                .iter()
+                .filter(|t| needle.is_empty() || t.text.to_lowercase().contains(&needle))
+                .collect();
+            todos.sort_by_key(|t| std::cmp::Reverse(t.done_at));
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("archive_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for todo in todos {
+                                ui.label(generate_title(&todo.text));
+                                match todo.done_at {
+                                    Some(at) => {
+                                        ui.weak(zone.wall_clock(at).format("%Y-%m-%d").to_string())
+                                    }
+                                    None => ui.weak("-"),
+                                };
+                                ui.add_enabled_ui(!read_only, |ui| {
+                                    if ui.button("Restore").clicked() {
+                                        restored = Some(todo.id);
+                                    }
+                                    if ui
+                                        .button("Delete")
+                                        .on_hover_text("Delete for good")
+                                        .clicked()
+                                    {
+                                        purge.insert(todo.id);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            if !read_only && ui.button("Delete all archived").clicked() {
+                purge = state.archived.iter().map(|t| t.id).collect();
+            }
+        });
+    state.archive_open = open;
+    if let Some(id) = restored {
+        state.restore_archived(id);
+    }
+    if !purge.is_empty() {
+        state.purge_archived(&purge);
+    }
+    restored
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Context};
+
+/// Extra boards live next to the main state file as `<stem>.<name>.json`, so
+/// "Move data to..." (which copies every file sharing the stem) takes them
+/// along.
+pub fn board_file(main: &Path, name: Option<&str>) -> PathBuf {
+    let Some(name) = name else {
+        return main.to_path_buf();
+    };
+    let stem = main.file_stem().unwrap_or_default().to_string_lossy();
+    main.with_file_name(format!("{}.{}.json", stem, name))
+}
+
+/// Names of the extra boards saved next to `main`, sorted.
+pub fn list_boards(main: &Path) -> Vec<String> {
+    let (Some(dir), Some(stem)) = (main.parent(), main.file_stem()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", stem.to_string_lossy());
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter_map(|file| {
+            let name = file.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            valid_board_name(name).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every board saved next to `main` with its file, the main board first.
+pub fn board_files(main: &Path) -> Vec<(Option<String>, PathBuf)> {
+    std::iter::once(None)
+        .chain(list_boards(main).into_iter().map(Some))
+        .map(|name| {
+            let path = board_file(main, name.as_deref());
+            (name, path)
+        })
+        .collect()
+}
+
+/// Board names become part of a file name, so they are kept to letters,
+/// digits, spaces, `-` and `_`.
+pub fn valid_board_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+/// A tab the user picked in the board bar.
+pub enum BoardChoice {
+    /// Switch to an existing board; `None` is the main board.
+    Open(Option<String>),
+    /// Start a new, empty board under this name.
+    Create(String),
+}
+
+/// Tab bar along the top listing the main board and every extra board.
+pub fn board_tabs(
+    ctx: &Context,
+    boards: &[String],
+    current: Option<&str>,
+    new_name: &mut String,
+) -> Option<BoardChoice> {
+    let mut choice = None;
+    egui::TopBottomPanel::top("board_tabs").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.selectable_label(current.is_none(), "Main").clicked() && current.is_some() {
+                choice = Some(BoardChoice::Open(None));
+            }
+            for name in boards {
+                let open = current == Some(name.as_str());
+                if ui.selectable_label(open, name).clicked() && !open {
+                    choice = Some(BoardChoice::Open(Some(name.clone())));
+                }
+            }
+            ui.separator();
+            ui.add(
+                egui::TextEdit::singleline(new_name)
+                    .hint_text("New board")
+                    .desired_width(100.0),
+            );
+            let name = new_name.trim();
+            let taken = name.eq_ignore_ascii_case("main") || boards.iter().any(|b| b == name);
+            if ui
+                .add_enabled(valid_board_name(name) && !taken, egui::Button::new("+"))
+                .on_hover_text("Create board")
+                .clicked()
+            {
+                choice = Some(BoardChoice::Create(name.to_string()));
+                new_name.clear();
+            }
+        });
+    });
+    choice
+}
@@ -0,0 +1,187 @@
+use eframe::egui::{self, Context, Key, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::hotkeys::hotkeys_active;
+use crate::{generate_title, rounded, AppState};
+
+/// The first nine bookmarks are one key press away, in list order.
+const NUMBER_KEYS: [Key; 9] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/// A named view of the board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ViewBookmark {
+    pub name: String,
+    #[serde(serialize_with = "rounded::vec2")]
+    pub offset: Vec2,
+    pub zoom: f32,
+    /// When set, jumping centers on this item instead of the saved offset,
+    /// so the bookmark follows the item around.
+    #[serde(default)]
+    pub anchor: Option<usize>,
+}
+
+/// Typed into the bookmarks window; never saved.
+#[derive(Default)]
+pub struct BookmarkInput {
+    name: String,
+    anchored: bool,
+}
+
+/// Offset and zoom the bookmark shows on `screen`.
+pub fn bookmark_view(state: &AppState, bookmark: &ViewBookmark, screen: Rect) -> (Vec2, f32) {
+    let zoom = bookmark.zoom;
+    match bookmark.anchor.and_then(|id| state.get_item_pos(id)) {
+        Some(pos) => (screen.center().to_vec2() - pos.to_vec2() * zoom, zoom),
+        None => (bookmark.offset, zoom),
+    }
+}
+
+/// The number key that jumps to bookmark `i`, if it has one.
+pub fn key_hint(i: usize) -> String {
+    if i < NUMBER_KEYS.len() {
+        (i + 1).to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Index of the bookmark whose number key was pressed on its own this
+/// frame, if there is one.
+pub fn bookmark_key_pressed(ctx: &Context, count: usize) -> Option<usize> {
+    if !hotkeys_active(ctx) || !ctx.input(|i| i.modifiers.is_none()) {
+        return None;
+    }
+    NUMBER_KEYS
+        .iter()
+        .take(count)
+        .position(|&key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key)))
+}
+
+/// "Go to" dropdown of the bookmarks, for the view bar. Returns the index
+/// of the one picked.
+pub fn bookmark_picker(ui: &mut egui::Ui, bookmarks: &[ViewBookmark]) -> Option<usize> {
+    let mut picked = None;
+    egui::ComboBox::from_id_source("bookmark_picker")
+        .selected_text("Go to")
+        .show_ui(ui, |ui| {
+            for (i, bookmark) in bookmarks.iter().enumerate() {
+                let button = egui::Button::new(&bookmark.name)
+                    .shortcut_text(key_hint(i))
+                    .frame(false);
+                if ui.add(button).clicked() {
+                    picked = Some(i);
+                }
+            }
+        });
+    picked
+}
+
+/// Small window listing the bookmarks. Returns the index of the one to jump
+/// to, if any. A `read_only` board can still jump to them, but not add,
+/// reorder or delete any.
+pub fn bookmarks_window(ctx: &Context, state: &mut AppState, read_only: bool) -> Option<usize> {
+    let mut jump = None;
+    let mut remove = None;
+    let mut raise = None;
+    let single_selected = (state.selected.len() == 1)
+        .then(|| state.selected.iter().next().copied())
+        .flatten();
+    let anchor_title = single_selected.and_then(|id| {
+        state
+            .notes
+            .iter()
+            .find(|n| n.id == id)
+            .map(|n| generate_title(&n.text))
+            .or_else(|| {
+                state
+                    .todos
+                    .iter()
+                    .find(|t| t.id == id)
+                    .map(|t| generate_title(&t.text))
+            })
+    });
+    let AppState {
+        bookmarks,
+        bookmarks_open,
+        bookmark_input: input,
+        offset,
+        zoom,
+        ..
+    } = state;
+    egui::Window::new("Bookmarks")
+        .open(bookmarks_open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for (i, bookmark) in bookmarks.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.weak(key_hint(i));
+                    if ui.link(&bookmark.name).clicked() {
+                        jump = Some(i);
+                    }
+                    if bookmark.anchor.is_some() {
+                        ui.weak("(follows item)");
+                    }
+                    ui.add_enabled_ui(!read_only, |ui| {
+                        if i > 0
+                            && ui
+                                .small_button("\u{25B2}")
+                                .on_hover_text("Move up")
+                                .clicked()
+                        {
+                            raise = Some(i);
+                        }
+                        if ui.small_button("\u{2715}").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                });
+            }
+            if bookmarks.is_empty() {
+                ui.weak("No bookmarks yet");
+            }
+            if read_only {
+                return;
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut input.name)
+                        .hint_text("Name")
+                        .desired_width(120.0),
+                );
+                let ready = !input.name.trim().is_empty();
+                if ui
+                    .add_enabled(ready, egui::Button::new("Save current view"))
+                    .clicked()
+                {
+                    bookmarks.push(ViewBookmark {
+                        name: input.name.trim().to_string(),
+                        offset: *offset,
+                        zoom: *zoom,
+                        anchor: single_selected.filter(|_| input.anchored),
+                    });
+                    input.name.clear();
+                }
+            });
+            if let Some(title) = anchor_title {
+                ui.checkbox(&mut input.anchored, format!("Follow \"{}\"", title));
+            }
+        });
+    if let Some(i) = raise {
+        bookmarks.swap(i - 1, i);
+    }
+    if let Some(i) = remove {
+        bookmarks.remove(i);
+    }
+    jump
+}
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::persist::PersistedState;
+use crate::AppState;
+
+const BUNDLE_STATE: &str = "app_state.json";
+const BUNDLE_IMAGES: &str = "images";
+/// Present only in partial exports, saying what was left out.
+const BUNDLE_SUBSET_NOTE: &str = "FILTERED.txt";
+
+/// Plain-text account of which items a filtered export kept.
+fn subset_note(state: &AppState, kept: usize) -> String {
+    let mut criteria = Vec::new();
+    if state.filter.only_starred {
+        criteria.push("starred items only".to_string());
+    }
+    if !state.filter.tags.is_empty() {
+        let tags: Vec<String> = state
+            .filter
+            .tags
+            .iter()
+            .map(|t| format!("#{}", t))
+            .collect();
+        criteria.push(format!("tagged {}", tags.join(" or ")));
+    }
+    let query = state.search.query.trim();
+    if !query.is_empty() {
+        criteria.push(format!("matching the search \"{}\"", query));
+    }
+    format!(
+        "Filtered export: {} of {} items ({}). Archived todos and links to items \
+         outside the export were left out.\n",
+        kept,
+        state.notes.len() + state.todos.len(),
+        criteria.join(", ")
+    )
+}
+
+/// Writes a zip holding the board and every image it references, with image
+/// paths rewritten relative to the archive. With `only`, just those items go
+/// in and the bundle says so. Missing images are skipped and reported in the
+/// returned warnings.
+pub fn export_bundle(
+    state: &AppState,
+    dest: &Path,
+    only: Option<&HashSet<usize>>,
+) -> io::Result<Vec<String>> {
+    let mut bundled = state.to_persisted();
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let options = SimpleFileOptions::default();
+    let mut warnings = Vec::new();
+
+    if let Some(ids) = only {
+        bundled.keep_only(ids);
+        let kept = bundled.notes.len() + bundled.todos.len();
+        zip.start_file(BUNDLE_SUBSET_NOTE, options)?;
+        zip.write_all(subset_note(state, kept).as_bytes())?;
+    }
+
+    for (i, path) in bundled.image_paths_mut().into_iter().enumerate() {
+        let source = PathBuf::from(&*path);
+        let Ok(bytes) = std::fs::read(&source) else {
+            warnings.push(format!("Skipped missing image {}", source.display()));
+            continue;
+        };
+        let name = source
+            .file_name()
+            .map_or("image".into(), |n| n.to_string_lossy());
+        let entry = format!("{}/{}_{}", BUNDLE_IMAGES, i, name);
+        zip.start_file(entry.as_str(), options)?;
+        zip.write_all(&bytes)?;
+        *path = entry;
+    }
+
+    zip.start_file(BUNDLE_STATE, options)?;
+    zip.write_all(serde_json::to_string_pretty(&bundled)?.as_bytes())?;
+    zip.finish()?;
+    Ok(warnings)
+}
+
+/// A folder of its own for one import's images, named after the bundle and
+/// the time, so a later bundle with same-named images can't overwrite the
+/// ones earlier boards (and their `.bak` backups) point at.
+fn import_dir(src: &Path, data_dir: &Path) -> PathBuf {
+    let images = data_dir.join(BUNDLE_IMAGES);
+    let stem = src.file_stem().map_or("bundle".into(), |s| s.to_string_lossy());
+    let name = format!("{}-{}", stem, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let mut dir = images.join(&name);
+    for n in 2.. {
+        if !dir.exists() {
+            break;
+        }
+        dir = images.join(format!("{}-{}", name, n));
+    }
+    dir
+}
+
+/// Unpacks a bundle's images into a new folder under `data_dir` and returns
+/// its board with image paths pointing at the extracted files.
+pub fn import_bundle(src: &Path, data_dir: &Path) -> io::Result<AppState> {
+    let mut zip = ZipArchive::new(File::open(src)?)?;
+    let mut json = String::new();
+    zip.by_name(BUNDLE_STATE)?.read_to_string(&mut json)?;
+    let mut state: PersistedState = serde_json::from_str(&json)?;
+
+    let dir = import_dir(src, data_dir);
+    for path in state.image_paths_mut() {
+        // `enclosed_name` refuses entries that would escape the target dir.
+        let Some(relative) = zip.by_name(path).ok().and_then(|f| f.enclosed_name()) else {
+            continue;
+        };
+        let Some(file_name) = relative.file_name() else {
+            continue;
+        };
+        let target = dir.join(file_name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut zip.by_name(path)?, &mut File::create(&target)?)?;
+        *path = target.display().to_string();
+    }
+    Ok(AppState::from_persisted(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::scratch_dir;
+
+    /// A bundle at `dir/board.zip` whose background is `pic.png` holding
+    /// `bytes`.
+    fn bundle_with_image(dir: &Path, bytes: &[u8]) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let image = dir.join("pic.png");
+        std::fs::write(&image, bytes).unwrap();
+        let state = AppState {
+            background_image_path: Some(image.display().to_string()),
+            ..AppState::default()
+        };
+        let dest = dir.join("board.zip");
+        assert!(export_bundle(&state, &dest, None).unwrap().is_empty());
+        dest
+    }
+
+    #[test]
+    fn imports_never_overwrite_earlier_images() {
+        let dir = scratch_dir("bundle-images");
+        let first = bundle_with_image(&dir.join("a"), b"first");
+        let second = bundle_with_image(&dir.join("b"), b"second");
+        let data_dir = dir.join("data");
+        let mut imported = Vec::new();
+        for bundle in [&first, &second, &first] {
+            let board = import_bundle(bundle, &data_dir).unwrap();
+            imported.push(PathBuf::from(board.background_image_path.unwrap()));
+        }
+        assert_eq!(std::fs::read(&imported[0]).unwrap(), b"first");
+        assert_eq!(std::fs::read(&imported[1]).unwrap(), b"second");
+        assert_eq!(std::fs::read(&imported[2]).unwrap(), b"first");
+        assert_ne!(imported[0], imported[2]);
+        assert!(imported.iter().all(|p| p.starts_with(data_dir.join(BUNDLE_IMAGES))));
+    }
+}
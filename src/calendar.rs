@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate};
+use eframe::egui::{self, Color32, Id, RichText, Sense, Ui, Vec2};
+
+use crate::timezone::BoardZone;
+use crate::{anchored_day, anchored_wall, generate_title, get_next_due, AppState, LoopFrequency, Todo};
+
+/// Chips shown per day before the rest collapse into "+n more".
+const MAX_CHIPS: usize = 4;
+
+pub enum CalendarAction {
+    /// Occurrence of todo `id` was dragged this many days later (or earlier).
+    Reschedule(usize, i64),
+    /// A chip was clicked: show the todo on the canvas.
+    Show(usize),
+}
+
+/// A todo placed on one day: its id and the day the occurrence falls on.
+#[derive(Clone, Copy)]
+struct Dragged(usize, NaiveDate);
+
+/// Every due date of `todo` from `start` to `end` (inclusive, board zone
+/// calendar), following its repeat.
+fn occurrences(todo: &Todo, zone: BoardZone, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let Some(mut due) = todo.due else {
+        return Vec::new();
+    };
+    let day = |t| zone.wall_clock(t).date();
+    if todo.loop_freq == LoopFrequency::Once {
+        return Some(day(due))
+            .filter(|d| (start..=end).contains(d))
+            .into_iter()
+            .collect();
+    }
+    let anchor = Some(anchored_day(todo.repeat_day, day(due)));
+    let time = Some(anchored_wall(todo.repeat_time, due, zone).time());
+    // A copy, so counting towards a custom rule's end leaves the todo alone.
+    let mut rule = todo.recurrence.clone();
+    let mut next_after = |due, now| match todo.loop_freq {
+        LoopFrequency::Custom => rule.advance(due, zone, now, anchor, time),
+        freq => get_next_due(&due, freq, zone, now, anchor, time),
+    };
+    let before_start = zone.instant_at(start.and_hms_opt(0, 0, 0).unwrap_or_default());
+    if due < before_start {
+        // Skip ahead in one go rather than stepping through the years.
+        match next_after(due, before_start) {
+            Some(next) => due = next,
+            None => return Vec::new(),
+        }
+    }
+    let mut days = Vec::new();
+    while day(due) <= end {
+        days.push(day(due));
+        match next_after(due, due) {
+            Some(next) => due = next,
+            None => break,
+        }
+    }
+    days
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// A month grid with todos on their due dates, repeats included. Chips can
+/// be dragged to another day to reschedule. Items the filter hides are left
+/// out. The month shown lives in egui memory, starting at the current one.
+pub fn calendar_view(
+    ui: &mut Ui,
+    state: &AppState,
+    now: DateTime<Local>,
+    read_only: bool,
+) -> Option<CalendarAction> {
+    let zone = state.zone();
+    let today = zone.wall_clock(now).date();
+    let month_id = Id::new("calendar_month");
+    let mut month = ui
+        .data(|d| d.get_temp::<NaiveDate>(month_id))
+        .unwrap_or(month_start(today));
+    ui.horizontal(|ui| {
+        if ui
+            .button("\u{25C0}")
+            .on_hover_text("Previous month")
+            .clicked()
+        {
+            month = month - Months::new(1);
+        }
+        if ui.button("Today").clicked() {
+            month = month_start(today);
+        }
+        if ui.button("\u{25B6}").on_hover_text("Next month").clicked() {
+            month = month + Months::new(1);
+        }
+        ui.heading(month.format("%B %Y").to_string());
+    });
+    ui.data_mut(|d| d.insert_temp(month_id, month));
+
+    // Six weeks from the Monday on or before the 1st cover any month.
+    let first = month - Duration::days(month.weekday().num_days_from_monday() as i64);
+    let last = first + Duration::days(41);
+    let mut by_day: BTreeMap<NaiveDate, Vec<&Todo>> = BTreeMap::new();
+    for todo in state.todos.iter().filter(|t| state.filter.matches_todo(t)) {
+        for day in occurrences(todo, zone, first, last) {
+            by_day.entry(day).or_default().push(todo);
+        }
+    }
+    for todos in by_day.values_mut() {
+        todos.sort_by_key(|t| (t.due.map(|d| zone.wall_clock(d).time()), t.id));
+    }
+
+    let spacing = ui.spacing().item_spacing;
+    ui.horizontal(|ui| {
+        for day in 0..7 {
+            let date = first + Duration::days(day);
+            let width = (ui.available_width() - spacing.x * (6 - day) as f32) / (7 - day) as f32;
+            ui.add_sized(
+                [width, 0.0],
+                egui::Label::new(RichText::new(date.format("%a").to_string()).weak()),
+            );
+        }
+    });
+    let cell = Vec2::new(
+        (ui.available_width() - spacing.x * 6.0) / 7.0,
+        ((ui.available_height() - spacing.y * 5.0) / 6.0).max(60.0),
+    );
+    let mut action = None;
+    for week in 0..6 {
+        ui.horizontal(|ui| {
+            for weekday in 0..7 {
+                let date = first + Duration::days(week * 7 + weekday);
+                let todos = by_day.get(&date).map_or(&[][..], Vec::as_slice);
+                if let Some(a) = day_cell(ui, cell, date, month, today, todos, read_only) {
+                    action = Some(a);
+                }
+            }
+        });
+    }
+    action
+}
+
+fn day_cell(
+    ui: &mut Ui,
+    size: Vec2,
+    date: NaiveDate,
+    month: NaiveDate,
+    today: NaiveDate,
+    todos: &[&Todo],
+    read_only: bool,
+) -> Option<CalendarAction> {
+    let mut action = None;
+    let mut frame = egui::Frame::group(ui.style()).inner_margin(2.0);
+    if date == today {
+        frame = frame.stroke(ui.visuals().selection.stroke);
+    }
+    let margin = frame.inner_margin.sum() + Vec2::splat(2.0 * frame.stroke.width);
+    ui.allocate_ui(size, |ui| {
+        let (_, dropped) = ui.dnd_drop_zone::<Dragged, _>(frame, |ui| {
+            ui.set_min_size(size - margin);
+            ui.set_max_size(size - margin);
+            let number = RichText::new(date.day().to_string());
+            ui.label(if date.month() == month.month() {
+                number
+            } else {
+                number.weak()
+            });
+            for todo in todos.iter().take(MAX_CHIPS) {
+                let mut text = RichText::new(generate_title(&todo.text)).small();
+                if todo.is_done {
+                    text = text.strikethrough().weak();
+                } else if todo.due.is_some() && date < today {
+                    text = text.color(Color32::LIGHT_RED);
+                }
+                let chip = |ui: &mut Ui| ui.add(egui::Label::new(text).truncate(true));
+                let response = if read_only {
+                    chip(ui)
+                } else {
+                    let id = Id::new("calendar_chip").with(todo.id).with(date);
+                    ui.dnd_drag_source(id, Dragged(todo.id, date), chip)
+                        .response
+                };
+                if response.interact(Sense::click()).clicked() {
+                    action = Some(CalendarAction::Show(todo.id));
+                }
+            }
+            if todos.len() > MAX_CHIPS {
+                ui.weak(format!("+{} more", todos.len() - MAX_CHIPS));
+            }
+        });
+        if let Some(dragged) = dropped.filter(|_| !read_only) {
+            let Dragged(id, from) = *dragged;
+            if from != date {
+                action = Some(CalendarAction::Reschedule(id, (date - from).num_days()));
+            }
+        }
+    });
+    action
+}
+
+impl AppState {
+    /// Moves todo `id`'s due date `days` days on the board zone's calendar,
+    /// keeping its time of day. For repeats this shifts the whole series.
+    pub fn reschedule(&mut self, id: usize, days: i64) {
+        let zone = self.zone();
+        if let Some(todo) = self.todos.iter_mut().find(|t| t.id == id) {
+            if let Some(due) = todo.due {
+                todo.due = Some(zone.instant_at(zone.wall_clock(due) + Duration::days(days)));
+                todo.rearm_reminders();
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use eframe::egui::{self, Ui};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChecklistProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ChecklistProgress {
+    pub fn fraction(self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+/// Counts Markdown task lines (`- [ ]`, `* [x]`, ...) in a note.
+pub fn parse_checklist(text: &str) -> ChecklistProgress {
+    let mut progress = ChecklistProgress::default();
+    for line in text.lines() {
+        let line = line.trim_start();
+        let Some(rest) = line
+            .strip_prefix("- [")
+            .or_else(|| line.strip_prefix("* ["))
+        else {
+            continue;
+        };
+        match rest.get(..2) {
+            Some(" ]") => progress.total += 1,
+            Some("x]") | Some("X]") => {
+                progress.total += 1;
+                progress.done += 1;
+            }
+            _ => {}
+        }
+    }
+    progress
+}
+
+/// Caches the parse result keyed by a hash of the text, so it is only
+/// recomputed on frames where the note actually changed.
+#[derive(Clone, Debug, Default)]
+pub struct ChecklistCache {
+    hash: u64,
+    progress: Option<ChecklistProgress>,
+}
+
+impl ChecklistCache {
+    pub fn get(&mut self, text: &str) -> ChecklistProgress {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        match self.progress {
+            Some(progress) if self.hash == hash => progress,
+            _ => {
+                let progress = parse_checklist(text);
+                self.hash = hash;
+                self.progress = Some(progress);
+                progress
+            }
+        }
+    }
+}
+
+/// One line of a todo's checklist.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SubTask {
+    pub text: String,
+    pub done: bool,
+}
+
+pub fn subtask_progress(subtasks: &[SubTask]) -> ChecklistProgress {
+    ChecklistProgress {
+        done: subtasks.iter().filter(|s| s.done).count(),
+        total: subtasks.len(),
+    }
+}
+
+/// Checkbox, text and remove button per subtask, plus an add button. Returns
+/// true when a box was ticked or unticked or a subtask removed, i.e. when
+/// the parent's completion may need updating.
+pub fn subtask_list(ui: &mut Ui, subtasks: &mut Vec<SubTask>) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+    for (i, subtask) in subtasks.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut subtask.done, "").changed();
+            ui.add(
+                egui::TextEdit::singleline(&mut subtask.text)
+                    .hint_text("Subtask")
+                    .frame(false),
+            );
+            if ui
+                .small_button("\u{00D7}")
+                .on_hover_text("Remove")
+                .clicked()
+            {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        subtasks.remove(i);
+        changed = true;
+    }
+    if ui.small_button("+ Subtask").clicked() {
+        subtasks.push(SubTask::default());
+    }
+    changed
+}
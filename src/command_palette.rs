@@ -0,0 +1,203 @@
+use eframe::egui::{self, Align2, Context, Id, Key, RichText};
+
+use crate::bookmarks::key_hint;
+use crate::fonts::{ICON_BOX, ICON_BOX_CHECKED, ICON_NOTE};
+use crate::hotkeys::{binding, HotkeyAction};
+use crate::AppState;
+
+/// Rows listed under the query.
+const MAX_RESULTS: usize = 12;
+
+/// What picking a palette row asks the app to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Anything with a keyboard shortcut, done as if the shortcut was pressed.
+    Hotkey(HotkeyAction),
+    ChangeBackground,
+    ExportBundle,
+    ExportImage,
+    /// Switch to this board; `None` is the main one.
+    OpenBoard(Option<String>),
+    /// Glide to the item and flash it.
+    JumpTo(usize),
+    /// Glide to the bookmark at this index.
+    Bookmark(usize),
+}
+
+struct Entry {
+    label: String,
+    /// Shown dimmed on the right: the shortcut, or what kind of item it is.
+    hint: String,
+    command: Command,
+}
+
+/// How well `query` matches `text` as a subsequence, ignoring case; `None`
+/// if some query character is missing. Characters in a row and at the start
+/// of words score higher, so "ad" ranks "Add todo" above "Load data".
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut at = 0;
+    let mut last: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = at + text[at..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last.is_some_and(|l| l + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - at).min(10) as i32 / 2;
+        last = Some(found);
+        at = found + 1;
+    }
+    Some(score)
+}
+
+fn entries(ctx: &Context, state: &AppState, boards: &[String], read_only: bool) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = HotkeyAction::ALL
+        .into_iter()
+        .filter(|&a| a != HotkeyAction::CommandPalette)
+        .map(|a| Entry {
+            label: a.label().to_string(),
+            hint: ctx.format_shortcut(&binding(&state.settings.keybindings, a)),
+            command: Command::Hotkey(a),
+        })
+        .collect();
+    if !read_only {
+        entries.push(Entry {
+            label: "Change background...".into(),
+            hint: String::new(),
+            command: Command::ChangeBackground,
+        });
+    }
+    entries.push(Entry {
+        label: "Export bundle...".into(),
+        hint: String::new(),
+        command: Command::ExportBundle,
+    });
+    entries.push(Entry {
+        label: "Export image...".into(),
+        hint: String::new(),
+        command: Command::ExportImage,
+    });
+    if !boards.is_empty() {
+        let names = std::iter::once(None).chain(boards.iter().cloned().map(Some));
+        entries.extend(names.map(|name| Entry {
+            label: format!("Open board: {}", name.as_deref().unwrap_or("Main")),
+            hint: "board".into(),
+            command: Command::OpenBoard(name),
+        }));
+    }
+    entries.extend(state.bookmarks.iter().enumerate().map(|(i, b)| Entry {
+        label: format!("Go to bookmark: {}", b.name),
+        hint: key_hint(i),
+        command: Command::Bookmark(i),
+    }));
+    let first_line = |text: &str| text.lines().next().unwrap_or("").chars().take(60).collect();
+    entries.extend(state.notes.iter().map(|n| Entry {
+        label: first_line(&n.text),
+        hint: ICON_NOTE.into(),
+        command: Command::JumpTo(n.id),
+    }));
+    entries.extend(state.todos.iter().map(|t| {
+        let icon = if t.is_done {
+            ICON_BOX_CHECKED
+        } else {
+            ICON_BOX
+        };
+        Entry {
+            label: first_line(&t.text),
+            hint: icon.into(),
+            command: Command::JumpTo(t.id),
+        }
+    }));
+    entries.retain(|e| !e.label.trim().is_empty());
+    entries
+}
+
+/// The command palette, while `state.palette` holds its query: a search box
+/// over the actions, the boards and every item's first line, best match
+/// first. Arrow keys move the highlight, Enter or a click picks it and
+/// Escape closes.
+pub fn command_palette(
+    ctx: &Context,
+    state: &mut AppState,
+    boards: &[String],
+    read_only: bool,
+) -> Option<Command> {
+    let mut query = state.palette.take()?;
+    let mut ranked: Vec<(i32, Entry)> = entries(ctx, state, boards, read_only)
+        .into_iter()
+        .filter_map(|e| Some((fuzzy_score(&query, &e.label)?, e)))
+        .collect();
+    // Stable, so ties keep actions above items.
+    ranked.sort_by_key(|(score, _)| -score);
+    ranked.truncate(MAX_RESULTS);
+
+    let highlight_id = Id::new("command_palette_highlight");
+    let mut highlight = ctx.data(|d| d.get_temp::<usize>(highlight_id)).unwrap_or(0);
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        )
+    });
+    if down {
+        highlight += 1;
+    }
+    if up {
+        highlight = highlight.saturating_sub(1);
+    }
+    highlight = highlight.min(ranked.len().saturating_sub(1));
+
+    let mut picked = None;
+    egui::Window::new("Command palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .fixed_size([420.0, 0.0])
+        .show(ctx, |ui| {
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut query)
+                    .hint_text("Type a command or an item")
+                    .desired_width(f32::INFINITY),
+            );
+            field.request_focus();
+            if field.changed() {
+                highlight = 0;
+            }
+            ui.separator();
+            if ranked.is_empty() {
+                ui.weak("Nothing matches");
+            }
+            for (i, (_, entry)) in ranked.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let row = ui.selectable_label(i == highlight, &entry.label);
+                    if row.clicked() {
+                        picked = Some(entry.command.clone());
+                    }
+                    if row.hovered() && ui.input(|i| i.pointer.delta() != egui::Vec2::ZERO) {
+                        highlight = i;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(RichText::new(&entry.hint).weak());
+                    });
+                });
+            }
+        });
+    if enter {
+        picked = picked.or_else(|| ranked.get(highlight).map(|(_, e)| e.command.clone()));
+    }
+    if picked.is_none() && !escape {
+        state.palette = Some(query);
+        ctx.data_mut(|d| d.insert_temp(highlight_id, highlight));
+    } else {
+        ctx.data_mut(|d| d.remove::<usize>(highlight_id));
+    }
+    picked
+}
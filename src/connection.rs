@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+
+use eframe::egui::{
+    self, Color32, CursorIcon, FontId, Id, Painter, Pos2, Rect, Sense, Shape, Stroke, Vec2,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnchorSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+impl AnchorSide {
+    const ALL: [Self; 4] = [Self::Top, Self::Bottom, Self::Left, Self::Right];
+
+    fn point_on(self, rect: Rect) -> Pos2 {
+        match self {
+            AnchorSide::Top => rect.center_top(),
+            AnchorSide::Bottom => rect.center_bottom(),
+            AnchorSide::Left => rect.left_center(),
+            AnchorSide::Right => rect.right_center(),
+        }
+    }
+
+    /// Unit vector pointing out of the item on this side.
+    fn normal(self) -> Vec2 {
+        match self {
+            AnchorSide::Top => -Vec2::Y,
+            AnchorSide::Bottom => Vec2::Y,
+            AnchorSide::Left => -Vec2::X,
+            AnchorSide::Right => Vec2::X,
+        }
+    }
+}
+
+/// The side a connection attaches to. `None` picks the side nearest to the
+/// other end of the connection.
+pub fn resolve_side(rect: Rect, side: Option<AnchorSide>, toward: Pos2) -> AnchorSide {
+    side.unwrap_or_else(|| {
+        AnchorSide::ALL
+            .into_iter()
+            .min_by(|a, b| {
+                let da = a.point_on(rect).distance_sq(toward);
+                let db = b.point_on(rect).distance_sq(toward);
+                da.total_cmp(&db)
+            })
+            .unwrap_or(AnchorSide::Right)
+    })
+}
+
+/// A resolved connection end: where it touches the item and on which side.
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor {
+    pub pos: Pos2,
+    pub side: AnchorSide,
+}
+impl Anchor {
+    pub fn on(rect: Rect, side: Option<AnchorSide>, toward: Pos2) -> Self {
+        let side = resolve_side(rect, side, toward);
+        Self {
+            pos: side.point_on(rect),
+            side,
+        }
+    }
+}
+
+/// Decoration drawn where a connection meets an item.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Marker {
+    #[default]
+    None,
+    Dot,
+    Arrow,
+}
+impl Marker {
+    const ALL: [Self; 3] = [Self::None, Self::Dot, Self::Arrow];
+}
+
+fn default_end_marker() -> Marker {
+    Marker::Arrow
+}
+
+fn side_label(side: Option<AnchorSide>) -> String {
+    side.map_or("Auto".to_string(), |s| format!("{:?}", s))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Connection {
+    pub from: usize,
+    pub to: usize,
+    #[serde(default)]
+    pub from_side: Option<AnchorSide>,
+    #[serde(default)]
+    pub to_side: Option<AnchorSide>,
+    /// Overrides the global curve tension for this connection.
+    #[serde(default)]
+    pub tension: Option<f32>,
+    #[serde(default)]
+    pub start_marker: Marker,
+    #[serde(default = "default_end_marker")]
+    pub end_marker: Marker,
+    /// Drawn at the middle of the line; empty for none.
+    #[serde(default)]
+    pub label: String,
+    /// `to` can't be started until `from` is done. Only links between two
+    /// todos block anything.
+    #[serde(default)]
+    pub dependency: bool,
+}
+impl Connection {
+    pub fn new(from: usize, to: usize) -> Self {
+        Self {
+            from,
+            to,
+            from_side: None,
+            to_side: None,
+            tension: None,
+            start_marker: Marker::None,
+            end_marker: default_end_marker(),
+            label: String::new(),
+            dependency: false,
+        }
+    }
+
+    /// Points the connection the other way. Each item keeps the side it is
+    /// attached on; the markers stay at the start and end, so the arrow
+    /// flips over.
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.from, &mut self.to);
+        std::mem::swap(&mut self.from_side, &mut self.to_side);
+    }
+}
+
+/// One end of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionEnd {
+    From,
+    To,
+}
+
+/// A connection end being dragged onto another item.
+#[derive(Clone, Copy, Debug)]
+pub struct Rewire {
+    pub index: usize,
+    pub end: ConnectionEnd,
+}
+
+/// Connections used to be stored as plain `(from, to)` pairs.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConnectionRepr {
+    Pair(usize, usize),
+    Full(Connection),
+}
+
+pub fn deserialize_connections<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Connection>, D::Error> {
+    let reprs = Vec::<ConnectionRepr>::deserialize(deserializer)?;
+    Ok(reprs
+        .into_iter()
+        .map(|repr| match repr {
+            ConnectionRepr::Pair(from, to) => Connection::new(from, to),
+            ConnectionRepr::Full(conn) => conn,
+        })
+        .collect())
+}
+
+/// Bezier control points for a curved connection: they leave each end along
+/// its side's normal, `tension` times the distance between the ends.
+fn control_points(start: Anchor, end: Anchor, tension: Option<f32>) -> Option<[Pos2; 4]> {
+    let tension = tension.filter(|&t| t > 0.0)?;
+    let reach = start.pos.distance(end.pos) * tension;
+    Some([
+        start.pos,
+        start.pos + start.side.normal() * reach,
+        end.pos + end.side.normal() * reach,
+        end.pos,
+    ])
+}
+
+fn marker_shape(
+    marker: Marker,
+    tip: Pos2,
+    toward_tip: Vec2,
+    size: f32,
+    color: Color32,
+) -> Option<Shape> {
+    let dir = toward_tip.normalized();
+    if !dir.is_finite() {
+        return None;
+    }
+    match marker {
+        Marker::None => None,
+        Marker::Dot => Some(Shape::circle_filled(tip, size * 0.4, color)),
+        Marker::Arrow => {
+            let back = tip - dir * size;
+            let side = dir.rot90() * size * 0.5;
+            Some(Shape::convex_polygon(
+                vec![tip, back + side, back - side],
+                color,
+                Stroke::NONE,
+            ))
+        }
+    }
+}
+
+/// Screen-space shapes for a connection: the line (a cubic bezier when a
+/// `tension` is given) plus its end markers, sized by `scale` (the zoom).
+pub fn connection_shapes(
+    conn: &Connection,
+    start: Anchor,
+    end: Anchor,
+    tension: Option<f32>,
+    stroke: Stroke,
+    scale: f32,
+) -> Vec<Shape> {
+    let mut shapes = Vec::with_capacity(3);
+    let (toward_start, toward_end) = match control_points(start, end, tension) {
+        Some(points) => {
+            shapes.push(Shape::CubicBezier(
+                egui::epaint::CubicBezierShape::from_points_stroke(
+                    points,
+                    false,
+                    Color32::TRANSPARENT,
+                    stroke,
+                ),
+            ));
+            (points[0] - points[1], points[3] - points[2])
+        }
+        None => {
+            shapes.push(Shape::line_segment([start.pos, end.pos], stroke));
+            (start.pos - end.pos, end.pos - start.pos)
+        }
+    };
+    let size = (12.0 * scale).clamp(4.0, 40.0);
+    shapes.extend(marker_shape(
+        conn.start_marker,
+        start.pos,
+        toward_start,
+        size,
+        stroke.color,
+    ));
+    shapes.extend(marker_shape(
+        conn.end_marker,
+        end.pos,
+        toward_end,
+        size,
+        stroke.color,
+    ));
+    shapes
+}
+
+fn bezier_point([a, b, c, d]: [Pos2; 4], t: f32) -> Pos2 {
+    let u = 1.0 - t;
+    Pos2::ZERO
+        + a.to_vec2() * (u * u * u)
+        + b.to_vec2() * (3.0 * u * u * t)
+        + c.to_vec2() * (3.0 * u * t * t)
+        + d.to_vec2() * (t * t * t)
+}
+
+/// Halfway along the drawn line, where the label goes.
+pub fn connection_midpoint(start: Anchor, end: Anchor, tension: Option<f32>) -> Pos2 {
+    match control_points(start, end, tension) {
+        Some(points) => bezier_point(points, 0.5),
+        None => start.pos.lerp(end.pos, 0.5),
+    }
+}
+
+fn segment_distance(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0);
+    if t.is_finite() {
+        p.distance(a + ab * t)
+    } else {
+        p.distance(a)
+    }
+}
+
+/// How far `p` is from the drawn line, for hit-testing. Curves are measured
+/// against a polyline approximation, which is plenty at pointer precision.
+pub fn distance_to_connection(start: Anchor, end: Anchor, tension: Option<f32>, p: Pos2) -> f32 {
+    const SAMPLES: usize = 24;
+    let points: Vec<Pos2> = match control_points(start, end, tension) {
+        Some(cp) => (0..=SAMPLES)
+            .map(|k| bezier_point(cp, k as f32 / SAMPLES as f32))
+            .collect(),
+        None => vec![start.pos, end.pos],
+    };
+    points
+        .windows(2)
+        .map(|w| segment_distance(w[0], w[1], p))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// `label` centred on `pos` over a filled pill, so it reads on top of the
+/// line and whatever is behind it.
+pub fn draw_connection_label(
+    painter: &Painter,
+    pos: Pos2,
+    label: &str,
+    size: f32,
+    text: Color32,
+    fill: Color32,
+    outline: Stroke,
+) {
+    let galley = painter.layout_no_wrap(label.to_string(), FontId::proportional(size), text);
+    let rect = Rect::from_center_size(pos, galley.size() + Vec2::new(size, size * 0.4));
+    painter.rect(rect, rect.height() / 2.0, fill, outline);
+    painter.galley(rect.center() - galley.size() / 2.0, galley, text);
+}
+
+/// Grab handle on a connection end at `pos`, shown while hovered or dragged.
+/// While dragged it follows the pointer.
+pub fn endpoint_handle(ui: &egui::Ui, id: Id, pos: Pos2, radius: f32) -> egui::Response {
+    let rect = Rect::from_center_size(pos, Vec2::splat(radius * 2.0));
+    let response = ui.interact(rect, id, Sense::drag());
+    if response.hovered() || response.dragged() {
+        let at = if response.dragged() {
+            response.interact_pointer_pos().unwrap_or(pos)
+        } else {
+            pos
+        };
+        let visuals = ui.visuals();
+        ui.painter().circle(
+            at,
+            radius,
+            visuals.selection.bg_fill,
+            visuals.widgets.active.fg_stroke,
+        );
+        ui.ctx().set_cursor_icon(CursorIcon::Grab);
+    }
+    response
+}
+
+/// Lists the connections touching item `id` and lets the user pick, for this
+/// item's end, the side it attaches to and its marker, plus the curve
+/// tension, the label and which way the connection points.
+pub fn connection_sides_menu(
+    ui: &mut egui::Ui,
+    id: usize,
+    connections: &mut [Connection],
+    titles: &HashMap<usize, String>,
+) {
+    let mut any = false;
+    let mut reverse = None;
+    for (i, conn) in connections.iter_mut().enumerate() {
+        let Connection {
+            from,
+            to,
+            from_side,
+            to_side,
+            tension,
+            start_marker,
+            end_marker,
+            label,
+            dependency,
+        } = conn;
+        let (arrow, other, side, marker) = if *from == id {
+            ("→", *to, from_side, start_marker)
+        } else if *to == id {
+            ("←", *from, to_side, end_marker)
+        } else {
+            continue;
+        };
+        any = true;
+        ui.horizontal(|ui| {
+            let title = titles.get(&other).map_or("?", String::as_str);
+            ui.label(format!("{} {}", arrow, title));
+            egui::ComboBox::from_id_source(Id::new("conn_side").with(i).with(id))
+                .selected_text(side_label(*side))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(side, None, side_label(None));
+                    for s in AnchorSide::ALL {
+                        ui.selectable_value(side, Some(s), side_label(Some(s)));
+                    }
+                });
+            egui::ComboBox::from_id_source(Id::new("conn_marker").with(i).with(id))
+                .selected_text(format!("{:?}", marker))
+                .show_ui(ui, |ui| {
+                    for m in Marker::ALL {
+                        ui.selectable_value(marker, m, format!("{:?}", m));
+                    }
+                });
+            let mut custom = tension.is_some();
+            if ui.checkbox(&mut custom, "Own tension").changed() {
+                *tension = custom.then_some(0.5);
+            }
+            if let Some(t) = tension {
+                ui.add(egui::Slider::new(t, 0.0..=1.5));
+            }
+            ui.add(
+                egui::TextEdit::singleline(label)
+                    .hint_text("Label")
+                    .desired_width(80.0),
+            );
+            ui.checkbox(dependency, "Dependency")
+                .on_hover_text("The item at the end waits for the one at the start");
+            if ui
+                .small_button("\u{21C4}")
+                .on_hover_text("Reverse direction")
+                .clicked()
+            {
+                reverse = Some(i);
+            }
+        });
+    }
+    if let Some(i) = reverse {
+        connections[i].reverse();
+    }
+    if !any {
+        ui.label("No connections");
+    }
+}
+
+/// Window editing the connection with the ends in `editing`: label, markers,
+/// sides and tension, plus reversing and deleting it. Closes when the
+/// connection goes away.
+pub fn connection_editor(
+    ctx: &egui::Context,
+    connections: &mut Vec<Connection>,
+    editing: &mut Option<(usize, usize)>,
+    titles: &HashMap<usize, String>,
+) {
+    let Some(ends) = *editing else {
+        return;
+    };
+    let Some(i) = connections.iter().position(|c| (c.from, c.to) == ends) else {
+        *editing = None;
+        return;
+    };
+    let conn = &mut connections[i];
+    let mut open = true;
+    let mut delete = false;
+    egui::Window::new("Connection")
+        .id(Id::new("connection_editor"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let title = |id| titles.get(&id).map_or("?", String::as_str);
+            ui.label(format!("{} \u{2192} {}", title(conn.from), title(conn.to)));
+            ui.add(egui::TextEdit::singleline(&mut conn.label).hint_text("Label"));
+            ui.checkbox(&mut conn.dependency, "Dependency")
+                .on_hover_text(format!(
+                    "\"{}\" waits until \"{}\" is done",
+                    title(conn.to),
+                    title(conn.from)
+                ));
+            egui::Grid::new("connection_ends").show(ui, |ui| {
+                ui.label("");
+                ui.label("Side");
+                ui.label("Marker");
+                ui.end_row();
+                let ends = [
+                    ("Start", &mut conn.from_side, &mut conn.start_marker),
+                    ("End", &mut conn.to_side, &mut conn.end_marker),
+                ];
+                for (name, side, marker) in ends {
+                    ui.label(name);
+                    egui::ComboBox::from_id_source(Id::new("edit_side").with(name))
+                        .selected_text(side_label(*side))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(side, None, side_label(None));
+                            for s in AnchorSide::ALL {
+                                ui.selectable_value(side, Some(s), side_label(Some(s)));
+                            }
+                        });
+                    egui::ComboBox::from_id_source(Id::new("edit_marker").with(name))
+                        .selected_text(format!("{:?}", marker))
+                        .show_ui(ui, |ui| {
+                            for m in Marker::ALL {
+                                ui.selectable_value(marker, m, format!("{:?}", m));
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut custom = conn.tension.is_some();
+                if ui.checkbox(&mut custom, "Own tension").changed() {
+                    conn.tension = custom.then_some(0.5);
+                }
+                if let Some(t) = &mut conn.tension {
+                    ui.add(egui::Slider::new(t, 0.0..=1.5));
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Reverse").clicked() {
+                    conn.reverse();
+                    *editing = Some((conn.from, conn.to));
+                }
+                if ui.button("Delete connection").clicked() {
+                    delete = true;
+                }
+            });
+        });
+    if delete {
+        connections.remove(i);
+    }
+    if delete || !open {
+        *editing = None;
+    }
+}
@@ -0,0 +1,34 @@
+use eframe::egui::{Color32, ColorImage};
+
+/// Perceived brightness of a color in `0.0..=1.0` (Rec. 709 weights on the
+/// gamma-encoded channels, which is close enough for picking an accent).
+pub fn luminance(color: Color32) -> f32 {
+    let [r, g, b, _] = color.to_array();
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+/// Average luminance of an image, from a sparse grid of samples.
+pub fn image_luminance(image: &ColorImage) -> f32 {
+    const SAMPLES: usize = 4096;
+    let step = (image.pixels.len() / SAMPLES).max(1);
+    let (sum, count) = image
+        .pixels
+        .iter()
+        .step_by(step)
+        .fold((0.0, 0usize), |(sum, n), &p| (sum + luminance(p), n + 1));
+    if count == 0 {
+        0.5
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Gray for lines and labels drawn straight on a background of the given
+/// luminance; dark on light backgrounds, light on dark ones.
+pub fn accent_for(background_luminance: f32) -> Color32 {
+    if background_luminance > 0.5 {
+        Color32::from_gray(60)
+    } else {
+        Color32::from_gray(200)
+    }
+}
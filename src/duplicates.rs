@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Context};
+
+use crate::{generate_title, AppState};
+
+/// Items whose text matches once normalized, and the one that stays when the
+/// group is merged.
+pub struct DuplicateGroup {
+    pub ids: Vec<usize>,
+    pub survivor: usize,
+}
+
+/// Case, punctuation and spacing differences don't make an item unique.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn group_by_text<'a>(items: impl Iterator<Item = (usize, &'a str)>) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut by_text: HashMap<String, usize> = HashMap::new();
+    for (id, text) in items {
+        let key = normalize(text);
+        if key.is_empty() {
+            continue;
+        }
+        match by_text.get(&key) {
+            Some(&i) => groups[i].push(id),
+            None => {
+                by_text.insert(key, groups.len());
+                groups.push(vec![id]);
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|ids| ids.len() > 1)
+        .map(|ids| DuplicateGroup {
+            survivor: ids[0],
+            ids,
+        })
+        .collect()
+}
+
+/// Groups notes with matching text, and todos with matching text. Notes and
+/// todos are never grouped together.
+pub fn find_duplicates(state: &AppState) -> Vec<DuplicateGroup> {
+    let mut groups = group_by_text(state.notes.iter().map(|n| (n.id, n.text.as_str())));
+    groups.extend(group_by_text(
+        state.todos.iter().map(|t| (t.id, t.text.as_str())),
+    ));
+    groups
+}
+
+/// Review window for the groups found by "Find duplicates". Each group can be
+/// merged into the item picked as its survivor, or dismissed.
+pub fn duplicates_window(ctx: &Context, state: &mut AppState) {
+    let Some(groups) = state.duplicates.as_mut() else {
+        return;
+    };
+    let titles: HashMap<usize, String> = state
+        .notes
+        .iter()
+        .map(|n| (n.id, generate_title(&n.text)))
+        .chain(state.todos.iter().map(|t| (t.id, generate_title(&t.text))))
+        .collect();
+    let mut open = true;
+    let mut merge = None;
+    let mut dismiss = None;
+    let mut pan_to = None;
+    egui::Window::new("Duplicates")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if groups.is_empty() {
+                ui.label("No duplicates found");
+                return;
+            }
+            ui.label("Pick the item to keep in each group.");
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    for (i, group) in groups.iter_mut().enumerate() {
+                        ui.separator();
+                        for &id in &group.ids {
+                            ui.horizontal(|ui| {
+                                let title = titles.get(&id).map_or("?", String::as_str);
+                                ui.radio_value(&mut group.survivor, id, title);
+                                if ui.small_button("Show").clicked() {
+                                    pan_to = Some(id);
+                                }
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Merge").clicked() {
+                                merge = Some(i);
+                            }
+                            if ui.button("Not duplicates").clicked() {
+                                dismiss = Some(i);
+                            }
+                        });
+                    }
+                });
+        });
+    if let Some(i) = merge {
+        let group = groups.remove(i);
+        let others = group
+            .ids
+            .into_iter()
+            .filter(|&id| id != group.survivor)
+            .collect();
+        state.merge_into(group.survivor, &others);
+    } else if let Some(i) = dismiss {
+        groups.remove(i);
+    }
+    if let Some(id) = pan_to {
+        state.center_on(id, ctx.available_rect());
+    }
+    if !open {
+        state.duplicates = None;
+    }
+}
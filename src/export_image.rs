@@ -0,0 +1,843 @@
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{Font, FontArc, OutlineCurve, PxScale, ScaleFont};
+use base64::Engine as _;
+use eframe::egui::{
+    self, Color32, Context, FontDefinitions, FontFamily, Pos2, Rect, Shape, Stroke,
+};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, PixmapPaint, Transform};
+
+use crate::connection::{connection_midpoint, connection_shapes, Anchor};
+use crate::palette::ItemColor;
+use crate::{generate_title, AppState, ItemMeta};
+
+/// Longest side of an exported PNG in pixels; past this the file stops being
+/// something most viewers will open. SVGs scale freely.
+pub const MAX_EXPORT_SIDE: u32 = 16384;
+/// Canvas units of empty space around the outermost items.
+const MARGIN: f32 = 40.0;
+/// Height of an item's title bar, in canvas units.
+const HEADER: f32 = 24.0;
+const PADDING: f32 = 8.0;
+const FONT_SIZE: f32 = 14.0;
+const LINE_HEIGHT: f32 = FONT_SIZE * 1.3;
+const ROUNDING: f32 = 6.0;
+const LABEL_SIZE: f32 = 13.0;
+
+/// The pill behind a connection label, matching the one drawn on the canvas.
+fn label_rect(center: Pos2, text_width: f32, size: f32) -> Rect {
+    Rect::from_center_size(center, egui::vec2(text_width + size, size * 1.6))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// Settings of the "Export image" window while it is open.
+#[derive(Clone, Debug)]
+pub struct ImageExport {
+    /// Pixels per canvas unit.
+    pub scale: f32,
+}
+
+impl Default for ImageExport {
+    fn default() -> Self {
+        Self { scale: 2.0 }
+    }
+}
+
+/// Colours taken from the live UI, so the image looks like the app does.
+#[derive(Clone, Copy, Debug)]
+pub struct ExportStyle {
+    pub canvas: Color32,
+    pub fill: Color32,
+    pub stroke: Stroke,
+    pub text: Color32,
+    pub link: Color32,
+}
+
+impl ExportStyle {
+    pub fn from_ctx(ctx: &Context, link: Color32) -> Self {
+        let visuals = &ctx.style().visuals;
+        Self {
+            canvas: visuals.panel_fill,
+            fill: visuals.window_fill,
+            stroke: visuals.window_stroke,
+            text: visuals.text_color(),
+            link,
+        }
+    }
+}
+
+struct TextLine {
+    /// Left end of the baseline, in pixels.
+    pos: Pos2,
+    text: String,
+    strong: bool,
+}
+
+struct ItemBox {
+    rect: Rect,
+    opacity: f32,
+    color: Option<ItemColor>,
+    lines: Vec<TextLine>,
+    picture: Option<(Rect, PathBuf)>,
+}
+
+impl ItemBox {
+    fn fill(&self, style: ExportStyle) -> Color32 {
+        self.color
+            .map_or(style.fill, |c| c.tint(style.fill))
+            .gamma_multiply(self.opacity)
+    }
+}
+
+/// The board laid out in image pixels, independent of the current view.
+struct Scene {
+    width: u32,
+    height: u32,
+    scale: f32,
+    links: Vec<Shape>,
+    /// Connection labels, centred on the point given.
+    labels: Vec<(Pos2, String)>,
+    items: Vec<ItemBox>,
+    background: Option<PathBuf>,
+    brightness: f32,
+    overlay: Option<Color32>,
+}
+
+/// The canvas area the export covers: every item plus a margin.
+fn board_bounds(state: &AppState) -> Rect {
+    let rects = state
+        .notes
+        .iter()
+        .map(|n| Rect::from_min_size(n.position, n.size))
+        .chain(
+            state
+                .todos
+                .iter()
+                .map(|t| Rect::from_min_size(t.position, t.size)),
+        );
+    let bounds = rects.reduce(|a, b| a.union(b));
+    bounds
+        .unwrap_or(Rect::from_center_size(Pos2::ZERO, egui::vec2(400.0, 300.0)))
+        .expand(MARGIN)
+}
+
+/// Width and height in pixels of the board exported at `scale`.
+pub fn export_size(state: &AppState, scale: f32) -> [u32; 2] {
+    let size = board_bounds(state).size() * scale;
+    [size.x.ceil() as u32, size.y.ceil() as u32]
+}
+
+/// egui's own proportional font, so the export needs nothing installed.
+fn export_font() -> io::Result<FontArc> {
+    let fonts = FontDefinitions::default();
+    fonts
+        .families
+        .get(&FontFamily::Proportional)
+        .and_then(|names| names.first())
+        .and_then(|name| fonts.font_data.get(name))
+        .and_then(|data| FontArc::try_from_vec(data.font.to_vec()).ok())
+        .ok_or_else(|| io::Error::other("no font available"))
+}
+
+fn text_width(font: &FontArc, size: f32, text: &str) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum()
+}
+
+/// Greedy word wrap. Words wider than `max_width` get a line to themselves.
+fn wrap(font: &FontArc, size: f32, text: &str, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && text_width(font, size, &candidate) > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn build_scene(state: &AppState, style: ExportStyle, scale: f32, font: &FontArc) -> Scene {
+    let bounds = board_bounds(state);
+    let [width, height] = export_size(state, scale);
+    let to_px = |p: Pos2| Pos2::ZERO + (p - bounds.min) * scale;
+    let zone = state.zone();
+
+    let item_box = |position: Pos2,
+                    size: egui::Vec2,
+                    meta: &ItemMeta,
+                    title: String,
+                    body: String,
+                    picture: Option<&String>| {
+        let size = if meta.collapsed {
+            egui::vec2(size.x, HEADER)
+        } else {
+            size
+        };
+        let rect = Rect::from_min_size(to_px(position), size * scale);
+        let font_px = FONT_SIZE * scale;
+        let left = rect.min.x + PADDING * scale;
+        let max_width = rect.width() - 2.0 * PADDING * scale;
+        let mut lines = vec![TextLine {
+            pos: Pos2::new(left, rect.min.y + (HEADER + FONT_SIZE) * 0.5 * scale),
+            text: title,
+            strong: true,
+        }];
+        let mut y = rect.min.y + (HEADER + PADDING) * scale;
+        let mut shown_picture = None;
+        if !meta.collapsed {
+            if let Some(path) = picture {
+                if let Ok((w, h)) = image::image_dimensions(path) {
+                    let pic_height = (max_width * h as f32 / w.max(1) as f32)
+                        .min(rect.max.y - y - PADDING * scale);
+                    if pic_height > 0.0 {
+                        let pic = Rect::from_min_size(
+                            Pos2::new(left, y),
+                            egui::vec2(max_width, pic_height),
+                        );
+                        shown_picture = Some((pic, PathBuf::from(path)));
+                        y += pic_height + PADDING * scale;
+                    }
+                }
+            }
+            for text in wrap(font, font_px, &body, max_width) {
+                y += LINE_HEIGHT * scale;
+                // Clip at the bottom like the window would.
+                if y > rect.max.y - PADDING * scale {
+                    break;
+                }
+                lines.push(TextLine {
+                    pos: Pos2::new(left, y),
+                    text,
+                    strong: false,
+                });
+            }
+        }
+        ItemBox {
+            rect,
+            opacity: meta.opacity,
+            color: meta.color,
+            lines,
+            picture: shown_picture,
+        }
+    };
+
+    let items = state
+        .notes
+        .iter()
+        .map(|n| {
+            item_box(
+                n.position,
+                n.size,
+                &n.meta,
+                generate_title(&n.text),
+                n.text.clone(),
+                n.image.as_ref(),
+            )
+        })
+        .chain(state.todos.iter().map(|t| {
+            let mut body = t.text.clone();
+            if t.is_done {
+                body.push_str("\nDone");
+            } else if let Some(due) = t.due {
+                let _ = write!(
+                    body,
+                    "\nDue: {}",
+                    zone.wall_clock(due).format("%Y-%m-%d %H:%M")
+                );
+            }
+            item_box(
+                t.position,
+                t.size,
+                &t.meta,
+                generate_title(&t.text),
+                body,
+                None,
+            )
+        }))
+        .collect();
+
+    let link_stroke = Stroke::new(2.0 * scale, style.link);
+    let mut links = Vec::new();
+    let mut labels = Vec::new();
+    for conn in &state.connections {
+        let Some((start, end)) = state.connection_anchors(conn) else {
+            continue;
+        };
+        let start = Anchor {
+            pos: to_px(start.pos),
+            ..start
+        };
+        let end = Anchor {
+            pos: to_px(end.pos),
+            ..end
+        };
+        let tension = state
+            .settings
+            .curved_connections
+            .then(|| conn.tension.unwrap_or(state.settings.curve_tension));
+        links.extend(connection_shapes(
+            conn,
+            start,
+            end,
+            tension,
+            link_stroke,
+            scale,
+        ));
+        if !conn.label.is_empty() {
+            labels.push((connection_midpoint(start, end, tension), conn.label.clone()));
+        }
+    }
+
+    Scene {
+        width,
+        height,
+        scale,
+        links,
+        labels,
+        items,
+        background: state.background_image_path.as_ref().map(PathBuf::from),
+        brightness: state.settings.background_brightness.clamp(0.0, 1.0),
+        overlay: state.settings.background_overlay,
+    }
+}
+
+/// Renders the whole board, whatever the current zoom and offset, to `dest`
+/// as a PNG or SVG with `scale` pixels per canvas unit.
+pub fn export_image(
+    state: &AppState,
+    style: ExportStyle,
+    scale: f32,
+    format: ImageFormat,
+    dest: &Path,
+) -> io::Result<()> {
+    let [width, height] = export_size(state, scale);
+    if format == ImageFormat::Png && (width > MAX_EXPORT_SIDE || height > MAX_EXPORT_SIDE) {
+        return Err(io::Error::other(format!(
+            "{}x{} pixels is too large; pick a lower scale",
+            width, height
+        )));
+    }
+    let font = export_font()?;
+    let scene = build_scene(state, style, scale, &font);
+    match format {
+        ImageFormat::Png => render_png(&scene, style, &font)?
+            .save_png(dest)
+            .map_err(io::Error::other),
+        ImageFormat::Svg => std::fs::write(dest, render_svg(&scene, style, &font)?),
+    }
+}
+
+fn skia_color(color: Color32) -> tiny_skia::Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    tiny_skia::Color::from_rgba8(r, g, b, a)
+}
+
+fn paint(color: Color32) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(skia_color(color));
+    paint.anti_alias = true;
+    paint
+}
+
+/// Decodes `path` scaled to exactly `width` x `height`, or to cover that
+/// area and crop when `cover` is set.
+fn load_pixmap(path: &Path, width: u32, height: u32, cover: bool) -> Option<Pixmap> {
+    let image = image::open(path).ok()?;
+    let filter = image::imageops::FilterType::Triangle;
+    let image = if cover {
+        image.resize_to_fill(width, height, filter)
+    } else {
+        image.resize_exact(width, height, filter)
+    };
+    let mut pixmap = Pixmap::new(image.width(), image.height())?;
+    for (dst, src) in pixmap
+        .pixels_mut()
+        .iter_mut()
+        .zip(image.to_rgba8().pixels())
+    {
+        let [r, g, b, a] = src.0;
+        *dst = tiny_skia::ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+    Some(pixmap)
+}
+
+fn stroke_path(pixmap: &mut Pixmap, path: &tiny_skia::Path, stroke: Stroke) {
+    let line = tiny_skia::Stroke {
+        width: stroke.width,
+        line_cap: tiny_skia::LineCap::Round,
+        ..Default::default()
+    };
+    pixmap.stroke_path(
+        path,
+        &paint(stroke.color),
+        &line,
+        Transform::identity(),
+        None,
+    );
+}
+
+fn fill_path(pixmap: &mut Pixmap, path: &tiny_skia::Path, color: Color32) {
+    pixmap.fill_path(
+        path,
+        &paint(color),
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+}
+
+/// The connection shapes this module produces: lines, curves and markers.
+fn draw_shape(pixmap: &mut Pixmap, shape: &Shape) {
+    let mut pb = PathBuilder::new();
+    match shape {
+        Shape::LineSegment { points, stroke } => {
+            pb.move_to(points[0].x, points[0].y);
+            pb.line_to(points[1].x, points[1].y);
+            if let Some(path) = pb.finish() {
+                stroke_path(pixmap, &path, *stroke);
+            }
+        }
+        Shape::CubicBezier(bezier) => {
+            let [a, b, c, d] = bezier.points;
+            pb.move_to(a.x, a.y);
+            pb.cubic_to(b.x, b.y, c.x, c.y, d.x, d.y);
+            if let Some(path) = pb.finish() {
+                stroke_path(pixmap, &path, bezier.stroke);
+            }
+        }
+        Shape::Path(poly) => {
+            for (i, p) in poly.points.iter().enumerate() {
+                if i == 0 {
+                    pb.move_to(p.x, p.y);
+                } else {
+                    pb.line_to(p.x, p.y);
+                }
+            }
+            pb.close();
+            if let Some(path) = pb.finish() {
+                fill_path(pixmap, &path, poly.fill);
+            }
+        }
+        Shape::Circle(circle) => {
+            if let Some(path) =
+                PathBuilder::from_circle(circle.center.x, circle.center.y, circle.radius)
+            {
+                fill_path(pixmap, &path, circle.fill);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rounded_rect_path(rect: Rect, radius: f32) -> Option<tiny_skia::Path> {
+    let r = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    let (x0, y0, x1, y1) = (rect.min.x, rect.min.y, rect.max.x, rect.max.y);
+    let mut pb = PathBuilder::new();
+    pb.move_to(x0 + r, y0);
+    pb.line_to(x1 - r, y0);
+    pb.quad_to(x1, y0, x1, y0 + r);
+    pb.line_to(x1, y1 - r);
+    pb.quad_to(x1, y1, x1 - r, y1);
+    pb.line_to(x0 + r, y1);
+    pb.quad_to(x0, y1, x0, y1 - r);
+    pb.line_to(x0, y0 + r);
+    pb.quad_to(x0, y0, x0 + r, y0);
+    pb.close();
+    pb.finish()
+}
+
+/// Glyph outlines of `text` as one path, so tiny-skia does the antialiasing.
+fn text_path(font: &FontArc, size: f32, origin: Pos2, text: &str) -> Option<tiny_skia::Path> {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let (sx, sy) = (scaled.h_scale_factor(), scaled.v_scale_factor());
+    let mut pb = PathBuilder::new();
+    let mut x = origin.x;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(outline) = font.outline(id) {
+            let map = |p: ab_glyph::Point| (x + p.x * sx, origin.y - p.y * sy);
+            let mut last = None;
+            for curve in &outline.curves {
+                let (start, end) = match *curve {
+                    OutlineCurve::Line(a, b) => (a, b),
+                    OutlineCurve::Quad(a, _, b) => (a, b),
+                    OutlineCurve::Cubic(a, _, _, b) => (a, b),
+                };
+                if last != Some(start) {
+                    if last.is_some() {
+                        pb.close();
+                    }
+                    let (px, py) = map(start);
+                    pb.move_to(px, py);
+                }
+                match *curve {
+                    OutlineCurve::Line(_, b) => {
+                        let (bx, by) = map(b);
+                        pb.line_to(bx, by);
+                    }
+                    OutlineCurve::Quad(_, c, b) => {
+                        let ((cx, cy), (bx, by)) = (map(c), map(b));
+                        pb.quad_to(cx, cy, bx, by);
+                    }
+                    OutlineCurve::Cubic(_, c1, c2, b) => {
+                        let ((ax, ay), (cx, cy), (bx, by)) = (map(c1), map(c2), map(b));
+                        pb.cubic_to(ax, ay, cx, cy, bx, by);
+                    }
+                }
+                last = Some(end);
+            }
+            if last.is_some() {
+                pb.close();
+            }
+        }
+        x += scaled.h_advance(id);
+    }
+    pb.finish()
+}
+
+fn render_png(scene: &Scene, style: ExportStyle, font: &FontArc) -> io::Result<Pixmap> {
+    let mut pixmap = Pixmap::new(scene.width.max(1), scene.height.max(1))
+        .ok_or_else(|| io::Error::other("could not allocate the image"))?;
+    pixmap.fill(skia_color(style.canvas));
+    let full = tiny_skia::Rect::from_xywh(0.0, 0.0, pixmap.width() as f32, pixmap.height() as f32);
+
+    if let Some(path) = &scene.background {
+        if let Some(image) = load_pixmap(path, pixmap.width(), pixmap.height(), true) {
+            pixmap.draw_pixmap(
+                0,
+                0,
+                image.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+            let dim = Color32::from_black_alpha(((1.0 - scene.brightness) * 255.0).round() as u8);
+            for color in std::iter::once(dim).chain(scene.overlay) {
+                if let Some(rect) = full {
+                    pixmap.fill_rect(rect, &paint(color), Transform::identity(), None);
+                }
+            }
+        }
+    }
+
+    for shape in &scene.links {
+        draw_shape(&mut pixmap, shape);
+    }
+    let label_px = LABEL_SIZE * scene.scale;
+    for (center, label) in &scene.labels {
+        let rect = label_rect(*center, text_width(font, label_px, label), label_px);
+        if let Some(pill) = rounded_rect_path(rect, rect.height() / 2.0) {
+            fill_path(&mut pixmap, &pill, style.fill);
+            stroke_path(&mut pixmap, &pill, Stroke::new(scene.scale, style.link));
+        }
+        let baseline = Pos2::new(rect.min.x + label_px * 0.5, center.y + label_px * 0.35);
+        if let Some(path) = text_path(font, label_px, baseline, label) {
+            fill_path(&mut pixmap, &path, style.text);
+        }
+    }
+
+    let font_px = FONT_SIZE * scene.scale;
+    for item in &scene.items {
+        let Some(outline) = rounded_rect_path(item.rect, ROUNDING * scene.scale) else {
+            continue;
+        };
+        fill_path(&mut pixmap, &outline, item.fill(style));
+        let border = Stroke::new(
+            style.stroke.width.max(1.0) * scene.scale,
+            style.stroke.color,
+        );
+        stroke_path(&mut pixmap, &outline, border);
+        if let Some((rect, path)) = &item.picture {
+            let (w, h) = (rect.width().round() as u32, rect.height().round() as u32);
+            if let Some(image) = load_pixmap(path, w.max(1), h.max(1), false) {
+                pixmap.draw_pixmap(
+                    rect.min.x.round() as i32,
+                    rect.min.y.round() as i32,
+                    image.as_ref(),
+                    &PixmapPaint::default(),
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+        for line in &item.lines {
+            let Some(path) = text_path(font, font_px, line.pos, &line.text) else {
+                continue;
+            };
+            fill_path(&mut pixmap, &path, style.text);
+            if line.strong {
+                stroke_path(
+                    &mut pixmap,
+                    &path,
+                    Stroke::new(0.4 * scene.scale, style.text),
+                );
+            }
+        }
+    }
+    Ok(pixmap)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `name="#rrggbb" name-opacity="a"` for an SVG paint attribute.
+fn svg_paint(name: &str, color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format!(
+        "{name}=\"#{r:02x}{g:02x}{b:02x}\" {name}-opacity=\"{:.3}\"",
+        a as f32 / 255.0
+    )
+}
+
+/// An `<image>` element with the file embedded, so the SVG stands alone.
+fn svg_image(path: &Path, rect: Rect, cover: bool) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let mime = if ext == "png" {
+        "image/png"
+    } else {
+        "image/jpeg"
+    };
+    Some(format!(
+        "<image x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+         preserveAspectRatio=\"{}\" href=\"data:{};base64,{}\"/>\n",
+        rect.min.x,
+        rect.min.y,
+        rect.width(),
+        rect.height(),
+        if cover { "xMidYMid slice" } else { "none" },
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn svg_shape(out: &mut String, shape: &Shape) -> std::fmt::Result {
+    match shape {
+        Shape::LineSegment { points, stroke } => writeln!(
+            out,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke-width=\"{:.1}\" \
+             stroke-linecap=\"round\" {}/>",
+            points[0].x,
+            points[0].y,
+            points[1].x,
+            points[1].y,
+            stroke.width,
+            svg_paint("stroke", stroke.color)
+        ),
+        Shape::CubicBezier(bezier) => {
+            let [a, b, c, d] = bezier.points;
+            writeln!(
+                out,
+                "<path d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\" \
+                 stroke-width=\"{:.1}\" stroke-linecap=\"round\" {}/>",
+                a.x,
+                a.y,
+                b.x,
+                b.y,
+                c.x,
+                c.y,
+                d.x,
+                d.y,
+                bezier.stroke.width,
+                svg_paint("stroke", bezier.stroke.color)
+            )
+        }
+        Shape::Path(poly) => {
+            let points: Vec<String> = poly
+                .points
+                .iter()
+                .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+                .collect();
+            writeln!(
+                out,
+                "<polygon points=\"{}\" {}/>",
+                points.join(" "),
+                svg_paint("fill", poly.fill)
+            )
+        }
+        Shape::Circle(circle) => writeln!(
+            out,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" {}/>",
+            circle.center.x,
+            circle.center.y,
+            circle.radius,
+            svg_paint("fill", circle.fill)
+        ),
+        _ => Ok(()),
+    }
+}
+
+fn render_svg(scene: &Scene, style: ExportStyle, font: &FontArc) -> io::Result<String> {
+    let mut out = String::new();
+    let full = Rect::from_min_size(
+        Pos2::ZERO,
+        egui::vec2(scene.width as f32, scene.height as f32),
+    );
+    let result = (|| -> std::fmt::Result {
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\">",
+            w = scene.width,
+            h = scene.height
+        )?;
+        writeln!(
+            out,
+            "<rect width=\"100%\" height=\"100%\" {}/>",
+            svg_paint("fill", style.canvas)
+        )?;
+        if let Some(image) = scene
+            .background
+            .as_deref()
+            .and_then(|p| svg_image(p, full, true))
+        {
+            out.push_str(&image);
+            let dim = Color32::from_black_alpha(((1.0 - scene.brightness) * 255.0).round() as u8);
+            for color in std::iter::once(dim).chain(scene.overlay) {
+                writeln!(
+                    out,
+                    "<rect width=\"100%\" height=\"100%\" {}/>",
+                    svg_paint("fill", color)
+                )?;
+            }
+        }
+        for shape in &scene.links {
+            svg_shape(&mut out, shape)?;
+        }
+        let label_px = LABEL_SIZE * scene.scale;
+        for (center, label) in &scene.labels {
+            let rect = label_rect(*center, text_width(font, label_px, label), label_px);
+            writeln!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" \
+                 {} stroke-width=\"{:.1}\" {}/>",
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                rect.height(),
+                rect.height() / 2.0,
+                svg_paint("fill", style.fill),
+                scene.scale,
+                svg_paint("stroke", style.link)
+            )?;
+            writeln!(
+                out,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" \
+                 font-family=\"Ubuntu, sans-serif\" font-size=\"{:.1}\" {}>{}</text>",
+                center.x,
+                center.y,
+                label_px,
+                svg_paint("fill", style.text),
+                escape_xml(label)
+            )?;
+        }
+        let font_px = FONT_SIZE * scene.scale;
+        for item in &scene.items {
+            writeln!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" \
+                 {} stroke-width=\"{:.1}\" {}/>",
+                item.rect.min.x,
+                item.rect.min.y,
+                item.rect.width(),
+                item.rect.height(),
+                ROUNDING * scene.scale,
+                svg_paint("fill", item.fill(style)),
+                style.stroke.width.max(1.0) * scene.scale,
+                svg_paint("stroke", style.stroke.color)
+            )?;
+            if let Some(image) = item
+                .picture
+                .as_ref()
+                .and_then(|(rect, path)| svg_image(path, *rect, false))
+            {
+                out.push_str(&image);
+            }
+            for line in &item.lines {
+                writeln!(
+                    out,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"Ubuntu, sans-serif\" \
+                     font-size=\"{:.1}\" font-weight=\"{}\" xml:space=\"preserve\" {}>{}</text>",
+                    line.pos.x,
+                    line.pos.y,
+                    font_px,
+                    if line.strong { "bold" } else { "normal" },
+                    svg_paint("fill", style.text),
+                    escape_xml(&line.text)
+                )?;
+            }
+        }
+        writeln!(out, "</svg>")
+    })();
+    result.map_err(io::Error::other)?;
+    Ok(out)
+}
+
+/// Scale picker with the resulting pixel size. Returns the chosen format and
+/// scale once a format button is pressed, closing the window.
+pub fn image_export_window(ctx: &Context, state: &mut AppState) -> Option<(ImageFormat, f32)> {
+    let scale = state.image_export.as_ref()?.scale;
+    let [width, height] = export_size(state, scale);
+    let fits = width <= MAX_EXPORT_SIDE && height <= MAX_EXPORT_SIDE;
+    let mut open = true;
+    let mut chosen = None;
+    let export = state.image_export.as_mut()?;
+    egui::Window::new("Export image")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add(egui::Slider::new(&mut export.scale, 0.25..=8.0).text("Pixels per unit"));
+            ui.label(format!("{} x {} pixels", width, height));
+            if !fits {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("Larger than {} pixels; lower the scale", MAX_EXPORT_SIDE),
+                );
+            }
+            ui.label("The whole board is exported, whatever the current view.");
+            ui.horizontal(|ui| {
+                if ui.add_enabled(fits, egui::Button::new("PNG...")).clicked() {
+                    chosen = Some(ImageFormat::Png);
+                }
+                if ui.button("SVG...").clicked() {
+                    chosen = Some(ImageFormat::Svg);
+                }
+            });
+        });
+    let scale = export.scale;
+    if !open || chosen.is_some() {
+        state.image_export = None;
+    }
+    chosen.map(|format| (format, scale))
+}
@@ -0,0 +1,57 @@
+use crate::{AppState, ItemMeta, Note, Todo};
+use std::collections::{BTreeSet, HashSet};
+
+/// Narrows down which items are shown on the canvas. View state only, it is
+/// not saved with the board.
+#[derive(Default, Clone, Debug)]
+pub struct ItemFilter {
+    pub only_starred: bool,
+    /// Show only items carrying at least one of these. Empty shows all.
+    pub tags: BTreeSet<String>,
+}
+
+impl ItemFilter {
+    pub fn is_active(&self) -> bool {
+        self.only_starred || !self.tags.is_empty()
+    }
+
+    fn matches_meta(&self, meta: &ItemMeta) -> bool {
+        (!self.only_starred || meta.starred)
+            && (self.tags.is_empty() || meta.tags.iter().any(|t| self.tags.contains(t)))
+    }
+
+    pub fn matches_note(&self, note: &Note) -> bool {
+        self.matches_meta(&note.meta)
+    }
+
+    pub fn matches_todo(&self, todo: &Todo) -> bool {
+        self.matches_meta(&todo.meta)
+    }
+
+    /// Ids of the items the filter hides.
+    pub fn hidden_ids(&self, state: &AppState) -> HashSet<usize> {
+        state
+            .notes
+            .iter()
+            .filter(|n| !self.matches_note(n))
+            .map(|n| n.id)
+            .chain(
+                state
+                    .todos
+                    .iter()
+                    .filter(|t| !self.matches_todo(t))
+                    .map(|t| t.id),
+            )
+            .collect()
+    }
+}
+
+/// Items that pass the filter and, while a search is active, match it.
+pub fn visible_ids(state: &AppState, matches: Option<&HashSet<usize>>) -> HashSet<usize> {
+    let hidden = state.filter.hidden_ids(state);
+    state
+        .item_ids()
+        .into_iter()
+        .filter(|id| !hidden.contains(id) && matches.is_none_or(|m| m.contains(id)))
+        .collect()
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, Context, FontId, Id, Key, LayerId, Order, Rect};
+
+use crate::{generate_title, truncate_chars, AppState};
+
+/// How much of their opacity items outside the focus keep.
+pub const FOCUS_DIM: f32 = 0.15;
+
+/// Items left at full strength in focus mode: the focused one and, if the
+/// setting is on, everything directly connected to it. `None` outside focus
+/// mode.
+pub fn focus_visible(state: &AppState) -> Option<HashSet<usize>> {
+    let id = state.focus?;
+    let mut visible = HashSet::from([id]);
+    if state.settings.focus_neighbors {
+        for conn in &state.connections {
+            if conn.from == id {
+                visible.insert(conn.to);
+            } else if conn.to == id {
+                visible.insert(conn.from);
+            }
+        }
+    }
+    Some(visible)
+}
+
+/// The focused item's text, enlarged in the middle of the screen. Escape or
+/// the close button leaves focus mode. Returns whether a note was cut to the
+/// hard length limit.
+pub fn focus_window(ctx: &Context, state: &mut AppState, read_only: bool) -> bool {
+    let Some(id) = state.focus else {
+        return false;
+    };
+    let limit = state.settings.note_hard_limit;
+    let (text, cache, is_note) = if let Some(note) = state.notes.iter_mut().find(|n| n.id == id) {
+        (&mut note.text, &mut note.search_cache, true)
+    } else if let Some(todo) = state.todos.iter_mut().find(|t| t.id == id) {
+        (&mut todo.text, &mut todo.search_cache, false)
+    } else {
+        // The item went away (deleted, undone, moved to another board).
+        state.focus = None;
+        return false;
+    };
+    let window_id = Id::new("focus_window");
+    let screen = ctx.screen_rect();
+    let rect = Rect::from_center_size(screen.center(), screen.size() * egui::vec2(0.6, 0.75));
+    let mut open = !ctx.input(|i| i.key_pressed(Key::Escape));
+    let mut cut = false;
+    egui::Window::new(generate_title(text))
+        .id(window_id)
+        .open(&mut open)
+        .collapsible(false)
+        .fixed_rect(rect)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut state.settings.focus_neighbors, "Show linked items");
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let edit = if is_note {
+                    egui::TextEdit::multiline(text).desired_rows(20)
+                } else {
+                    egui::TextEdit::singleline(text)
+                };
+                let edit = ui.add(
+                    edit.font(FontId::proportional(20.0))
+                        .frame(false)
+                        .interactive(!read_only)
+                        .desired_width(f32::INFINITY),
+                );
+                if state.focus_item == Some(id) {
+                    edit.request_focus();
+                    state.focus_item = None;
+                }
+                if edit.changed() {
+                    cache.invalidate();
+                }
+                cut = is_note && edit.changed() && truncate_chars(text, limit);
+            });
+        });
+    ctx.move_to_top(LayerId::new(Order::Middle, window_id));
+    if !open {
+        state.focus = None;
+    }
+    cut
+}
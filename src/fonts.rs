@@ -0,0 +1,46 @@
+use eframe::egui::{Context, FontData, FontDefinitions, FontFamily};
+
+/// Icons used in the UI, spelled as escapes so no editor or encoding round
+/// trip can turn them into mojibake.
+pub const ICON_LINK: &str = "\u{1F517}";
+pub const ICON_NOTE: &str = "\u{1F4DD}";
+pub const ICON_PIN: &str = "\u{1F4CC}";
+pub const ICON_STAR: &str = "\u{2605}";
+pub const ICON_STAR_OUTLINE: &str = "\u{2606}";
+pub const ICON_BOX: &str = "\u{2610}";
+pub const ICON_BOX_CHECKED: &str = "\u{2611}";
+pub const ICON_EDIT: &str = "\u{270F}";
+
+/// System fonts with wide symbol/emoji coverage. egui can only draw outline
+/// glyphs, so color-bitmap emoji fonts (Apple Color Emoji, Noto Color Emoji)
+/// are deliberately not listed.
+const FALLBACK_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "/System/Library/Fonts/Apple Symbols.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+];
+
+/// Keeps egui's bundled fonts (which already include an emoji font) and adds
+/// whichever system symbol fonts exist as further fallbacks, so emoji typed
+/// into titles render instead of showing boxes.
+pub fn install_fonts(ctx: &Context) {
+    let mut fonts = FontDefinitions::default();
+    for path in FALLBACK_FONTS {
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        let name = path.to_string();
+        fonts
+            .font_data
+            .insert(name.clone(), FontData::from_owned(bytes));
+        for family in [FontFamily::Proportional, FontFamily::Monospace] {
+            fonts.families.entry(family).or_default().push(name.clone());
+        }
+    }
+    ctx.set_fonts(fonts);
+}
@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+
+use eframe::egui::{Context, KeyboardShortcut};
+
+/// A shortcut grabbed for the whole desktop, so it works while another
+/// program has focus or the window is hidden. Presses arrive on a channel
+/// and wake the UI, like tray commands. Dropping it releases the grab.
+pub struct GlobalHotkey {
+    presses: Receiver<()>,
+    stop: Arc<AtomicBool>,
+}
+
+impl GlobalHotkey {
+    /// Grabs `shortcut`. Fails where the desktop offers no way to (Wayland
+    /// without X11, other systems) or another program holds it already.
+    pub fn register(ctx: &Context, shortcut: KeyboardShortcut) -> Result<Self, String> {
+        let (send, presses) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        x11::listen(shortcut, ctx.clone(), send, Arc::clone(&stop))?;
+        Ok(Self { presses, stop })
+    }
+
+    /// Whether the shortcut was pressed since the last call.
+    pub fn pressed(&self) -> bool {
+        self.presses.try_iter().count() > 0
+    }
+}
+
+impl Drop for GlobalHotkey {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod x11 {
+    use std::sync::mpsc::Sender;
+
+    use super::*;
+
+    pub fn listen(
+        _: KeyboardShortcut,
+        _: Context,
+        _: Sender<()>,
+        _: Arc<AtomicBool>,
+    ) -> Result<(), String> {
+        Err("system-wide shortcuts are not supported here".into())
+    }
+}
+
+/// A passive key grab on the X11 root window, which XWayland also honors
+/// for X11 apps.
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::sync::mpsc::Sender;
+    use std::time::Duration;
+
+    use eframe::egui::Key;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask};
+    use x11rb::protocol::Event;
+
+    use super::*;
+
+    /// How often the listener checks whether it should stop.
+    const POLL: Duration = Duration::from_millis(50);
+
+    fn keysym(key: Key) -> Option<u32> {
+        let name = key.name();
+        match name.as_bytes() {
+            [c] if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase() as u32),
+            _ if key == Key::Space => Some(0x20),
+            _ if key == Key::Enter => Some(0xff0d),
+            [b'F', ..] => name[1..].parse::<u32>().ok().map(|n| 0xffbe + n - 1),
+            _ => None,
+        }
+    }
+
+    pub fn listen(
+        shortcut: KeyboardShortcut,
+        ctx: Context,
+        send: Sender<()>,
+        stop: Arc<AtomicBool>,
+    ) -> Result<(), String> {
+        let wanted = keysym(shortcut.logical_key)
+            .ok_or_else(|| format!("{} can't be grabbed", shortcut.logical_key.name()))?;
+        let (conn, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let root = conn.setup().roots[screen].root;
+        let (min, max) = (conn.setup().min_keycode, conn.setup().max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        let per = mapping.keysyms_per_keycode.max(1) as usize;
+        let keycode = mapping
+            .keysyms
+            .chunks(per)
+            .position(|syms| syms.first() == Some(&wanted))
+            .map(|i| min + i as u8)
+            .ok_or("the key is not on this keyboard")?;
+
+        let none = ModMask::from(0u16);
+        let m = shortcut.modifiers;
+        let mut mask = none;
+        for (held, bit) in [
+            (m.ctrl || m.command, ModMask::CONTROL),
+            (m.shift, ModMask::SHIFT),
+            (m.alt, ModMask::M1),
+        ] {
+            if held {
+                mask |= bit;
+            }
+        }
+        // Caps Lock and Num Lock count as modifiers to X, so grab with them
+        // on as well.
+        for lock in [
+            none,
+            ModMask::LOCK,
+            ModMask::M2,
+            ModMask::LOCK | ModMask::M2,
+        ] {
+            conn.grab_key(
+                false,
+                root,
+                mask | lock,
+                keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )
+            .map_err(|e| e.to_string())?
+            .check()
+            .map_err(|_| "another program already uses this shortcut".to_string())?;
+        }
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match conn.poll_for_event() {
+                    Ok(Some(Event::KeyPress(_))) => {
+                        _ = send.send(());
+                        ctx.request_repaint();
+                    }
+                    Ok(Some(_)) => {}
+                    Ok(None) => std::thread::sleep(POLL),
+                    Err(_) => break,
+                }
+            }
+            // Closing the connection releases the grabs.
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use eframe::egui::{Context, Event};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::connection::Connection;
+use crate::lanes::Lane;
+use crate::trash::Trashed;
+use crate::{AppState, Note, Todo};
+
+/// Undo steps kept; older ones are dropped.
+const HISTORY_LIMIT: usize = 100;
+
+/// Reminder bookkeeping on todos. The reminder thread sets it without any
+/// user action, so it is left out of snapshots and kept as it is on undo;
+/// otherwise undo would re-arm reminders that already went out.
+const REMINDER_FIELDS: [&str; 3] = ["notified", "early_notified", "snoozed_until"];
+
+/// The board content undo restores. The view, settings and session state
+/// are left out, so undo never scrolls or reconfigures anything.
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    notes: &'a [Note],
+    todos: &'a [Todo],
+    connections: &'a [Connection],
+    archived: &'a [Todo],
+    trash: &'a [Trashed],
+    lanes: &'a [Lane],
+    next_id: usize,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    notes: Vec<Note>,
+    todos: Vec<Todo>,
+    connections: Vec<Connection>,
+    archived: Vec<Todo>,
+    trash: Vec<Trashed>,
+    lanes: Vec<Lane>,
+    next_id: usize,
+}
+
+/// Board snapshots, stored as JSON since the items carry caches that make
+/// them awkward to compare directly. Written through [`Value`], so a patched
+/// snapshot compares equal to a fresh one of the same board.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<String>,
+    redo: Vec<String>,
+    /// The board as of the last checkpoint.
+    current: Option<String>,
+}
+
+/// Whether this frame could have finished an edit: a click or drag ended, or
+/// a key was pressed outside a text field. Text edits are recorded once the
+/// field loses focus, not per keystroke.
+pub fn edit_may_have_ended(ctx: &Context) -> bool {
+    !ctx.wants_keyboard_input()
+        && ctx.input(|i| {
+            !i.pointer.any_down()
+                && (i.pointer.any_released()
+                    || i.events
+                        .iter()
+                        .any(|e| matches!(e, Event::Key { pressed: true, .. })))
+        })
+}
+
+impl AppState {
+    fn snapshot(&self) -> String {
+        let Ok(mut snapshot) = serde_json::to_value(SnapshotRef {
+            notes: &self.notes,
+            todos: &self.todos,
+            connections: &self.connections,
+            archived: &self.archived,
+            trash: &self.trash,
+            lanes: &self.lanes,
+            next_id: self.next_id,
+        }) else {
+            return String::new();
+        };
+        for todo in snapshot_todos(&mut snapshot) {
+            for field in REMINDER_FIELDS {
+                todo.remove(field);
+            }
+        }
+        snapshot.to_string()
+    }
+
+    fn restore(&mut self, json: &str) {
+        let Ok(snapshot) = serde_json::from_str::<Snapshot>(json) else {
+            return;
+        };
+        self.notes = snapshot.notes;
+        let live: HashMap<usize, Todo> = std::mem::take(&mut self.todos)
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+        self.todos = snapshot.todos;
+        for todo in &mut self.todos {
+            if let Some(live) = live.get(&todo.id) {
+                todo.notified = live.notified;
+                todo.early_notified = live.early_notified;
+                todo.snoozed_until = live.snoozed_until;
+            }
+        }
+        self.connections = snapshot.connections;
+        self.archived = snapshot.archived;
+        self.trash = snapshot.trash;
+        self.lanes = snapshot.lanes;
+        self.next_id = snapshot.next_id;
+        let ids = self.item_ids();
+        self.selected.retain(|id| ids.contains(id));
+        if self.connecting_from_id.is_some_and(|id| !ids.contains(&id)) {
+            self.connecting_from_id = None;
+        }
+        self.rewiring = None;
+    }
+
+    /// Records the board as an undo step if it changed since the last
+    /// checkpoint. A change clears the redo stack.
+    pub fn checkpoint(&mut self) {
+        let snapshot = self.snapshot();
+        let history = &mut self.history;
+        match history.current.replace(snapshot) {
+            Some(previous) if Some(&previous) != history.current.as_ref() => {
+                history.undo.push(previous);
+                if history.undo.len() > HISTORY_LIMIT {
+                    history.undo.remove(0);
+                }
+                history.redo.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs a reminder pass over the todos and writes what it changed, such
+    /// as a repeat moving on to its next due time, into every undo and redo
+    /// step. Undo then steps over it rather than taking a todo back to a due
+    /// time that has already reminded.
+    pub fn reminder_pass<R>(&mut self, pass: impl FnOnce(&mut Self) -> R) -> R {
+        let before: HashMap<usize, Value> = self
+            .todos
+            .iter()
+            .filter_map(|t| Some((t.id, serde_json::to_value(t).ok()?)))
+            .collect();
+        let result = pass(self);
+        let mut changes: HashMap<usize, Vec<(String, Value, Value)>> = HashMap::new();
+        for todo in &self.todos {
+            let (Some(Value::Object(old)), Ok(Value::Object(new))) =
+                (before.get(&todo.id), serde_json::to_value(todo))
+            else {
+                continue;
+            };
+            for (field, value) in new {
+                if let Some(old) = old.get(&field).filter(|o| **o != value) {
+                    let change = (field, old.clone(), value);
+                    changes.entry(todo.id).or_default().push(change);
+                }
+            }
+        }
+        if changes.is_empty() {
+            return result;
+        }
+        let history = &mut self.history;
+        let steps = history.undo.iter_mut().chain(&mut history.redo);
+        for json in steps.chain(history.current.as_mut()) {
+            let Ok(mut snapshot) = serde_json::from_str::<Value>(json) else {
+                continue;
+            };
+            for todo in snapshot_todos(&mut snapshot) {
+                let id = todo.get("id").and_then(Value::as_u64);
+                let Some(changes) = id.and_then(|id| changes.get(&(id as usize))) else {
+                    continue;
+                };
+                // Only where the step still has what the pass started from,
+                // so earlier edits by the user stay as they were.
+                for (field, old, new) in changes {
+                    if todo.get(field) == Some(old) {
+                        todo.insert(field.clone(), new.clone());
+                    }
+                }
+            }
+            *json = snapshot.to_string();
+        }
+        result
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Goes back one step. Returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        self.checkpoint();
+        let Some(previous) = self.history.undo.pop() else {
+            return false;
+        };
+        self.restore(&previous);
+        if let Some(current) = self.history.current.replace(previous) {
+            self.history.redo.push(current);
+        }
+        true
+    }
+
+    /// Reapplies the last undone step. Returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.history.redo.pop() else {
+            return false;
+        };
+        self.restore(&next);
+        if let Some(current) = self.history.current.replace(next) {
+            self.history.undo.push(current);
+        }
+        true
+    }
+}
+
+/// The todos of a snapshot, as JSON objects.
+fn snapshot_todos(snapshot: &mut Value) -> impl Iterator<Item = &mut Map<String, Value>> {
+    snapshot
+        .get_mut("todos")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+    use eframe::egui::{Pos2, Vec2};
+
+    use super::*;
+    use crate::{fire_due_reminders, LoopFrequency};
+
+    fn texts(state: &AppState) -> Vec<&str> {
+        state.notes.iter().map(|n| n.text.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_steps() {
+        let mut state = AppState::default();
+        state.checkpoint();
+        let id = state.add_note(Pos2::ZERO);
+        state.checkpoint();
+        state.notes[0].text = "edited".into();
+        state.checkpoint();
+        state.notes[0].position += Vec2::new(50.0, 0.0);
+        let other = state.add_note(Pos2::new(0.0, 200.0));
+        state.add_connection(id, other);
+
+        assert!(state.undo());
+        assert_eq!(state.notes.len(), 1);
+        assert_eq!(state.notes[0].position, Pos2::ZERO);
+        assert!(state.connections.is_empty());
+        assert!(state.undo());
+        assert_eq!(texts(&state), ["New note"]);
+        assert!(state.undo());
+        assert!(state.notes.is_empty());
+        assert!(!state.undo());
+
+        assert!(state.redo());
+        assert!(state.redo());
+        assert_eq!(texts(&state), ["edited"]);
+        assert!(state.redo());
+        assert_eq!(state.notes[0].position, Pos2::new(50.0, 0.0));
+        assert_eq!(state.connections.len(), 1);
+        assert!(!state.redo());
+    }
+
+    #[test]
+    fn a_new_change_clears_redo() {
+        let mut state = AppState::default();
+        state.checkpoint();
+        state.add_note(Pos2::ZERO);
+        state.checkpoint();
+        assert!(state.undo());
+        assert!(state.can_redo());
+        state.add_note(Pos2::new(10.0, 10.0));
+        state.checkpoint();
+        assert!(!state.can_redo());
+    }
+
+    #[test]
+    fn unchanged_checkpoints_add_no_steps() {
+        let mut state = AppState::default();
+        state.checkpoint();
+        state.checkpoint();
+        assert!(!state.can_undo());
+        // Selection is session state, not an undo step.
+        state.selected.insert(3);
+        state.checkpoint();
+        assert!(!state.can_undo());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut state = AppState::default();
+        state.checkpoint();
+        for i in 0..HISTORY_LIMIT + 20 {
+            state.add_note(Pos2::new(i as f32, 0.0));
+            state.checkpoint();
+        }
+        let mut steps = 0;
+        while state.undo() {
+            steps += 1;
+        }
+        assert_eq!(steps, HISTORY_LIMIT);
+        assert_eq!(state.notes.len(), 20);
+    }
+
+    #[test]
+    fn undo_drops_selection_of_removed_items() {
+        let mut state = AppState::default();
+        state.checkpoint();
+        let id = state.add_note(Pos2::ZERO);
+        state.selected.insert(id);
+        state.connecting_from_id = Some(id);
+        assert!(state.undo());
+        assert!(state.selected.is_empty());
+        assert_eq!(state.connecting_from_id, None);
+    }
+
+    fn fire(state: &mut AppState, now: chrono::DateTime<Local>) -> usize {
+        let zone = state.zone();
+        state.reminder_pass(|state| {
+            fire_due_reminders(&mut state.todos, &state.connections, now, zone, 0, false).len()
+        })
+    }
+
+    #[test]
+    fn undo_steps_over_reminders() {
+        let now = Local::now();
+        let mut state = AppState::default();
+        for _ in 0..2 {
+            state.add_todo(Pos2::ZERO, now);
+        }
+        state.todos[0].due = Some(now - Duration::hours(1));
+        state.todos[1].due = Some(now - Duration::hours(1));
+        state.todos[1].loop_freq = LoopFrequency::Daily;
+        state.checkpoint();
+        state.todos[0].text = "edited".into();
+        state.checkpoint();
+
+        assert_eq!(fire(&mut state, now), 2);
+        let moved = state.todos[1].due;
+        assert!(moved.is_some_and(|d| d > now));
+        // The pass is not a step of its own.
+        state.checkpoint();
+        assert!(state.undo());
+        assert_eq!(state.todos[0].text, "New todo");
+        assert!(state.todos[0].notified);
+        assert_eq!(state.todos[1].due, moved);
+        assert_eq!(fire(&mut state, now), 0);
+
+        assert!(state.redo());
+        assert_eq!(state.todos[0].text, "edited");
+        assert!(state.todos[0].notified);
+        assert_eq!(state.todos[1].due, moved);
+        assert!(!state.redo());
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, HashMap};
+
+use eframe::egui::{self, Context, Event, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Everything that can be bound to a keyboard shortcut.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HotkeyAction {
+    AddNote,
+    AddTodo,
+    UndoConnection,
+    Undo,
+    Redo,
+    DeleteSelection,
+    ToggleItemList,
+    Search,
+    OpenSettings,
+    ToggleFocus,
+    Duplicate,
+    ResetZoom,
+    FitAll,
+    FitSelection,
+    Save,
+    NextBoard,
+    CommandPalette,
+    QuickCapture,
+    Bookmarks,
+}
+
+impl HotkeyAction {
+    pub const ALL: [Self; 19] = [
+        Self::AddNote,
+        Self::AddTodo,
+        Self::UndoConnection,
+        Self::Undo,
+        Self::Redo,
+        Self::DeleteSelection,
+        Self::ToggleItemList,
+        Self::Search,
+        Self::OpenSettings,
+        Self::ToggleFocus,
+        Self::Duplicate,
+        Self::ResetZoom,
+        Self::FitAll,
+        Self::FitSelection,
+        Self::Save,
+        Self::NextBoard,
+        Self::CommandPalette,
+        Self::QuickCapture,
+        Self::Bookmarks,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::AddNote => "Add note",
+            Self::AddTodo => "Add todo",
+            Self::UndoConnection => "Undo last connection",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::DeleteSelection => "Delete selected items",
+            Self::ToggleItemList => "Toggle item list",
+            Self::Search => "Search items",
+            Self::OpenSettings => "Open settings",
+            Self::ToggleFocus => "Focus selected item",
+            Self::Duplicate => "Duplicate selected items",
+            Self::ResetZoom => "Reset zoom to 100%",
+            Self::FitAll => "Fit all items",
+            Self::FitSelection => "Fit selection",
+            Self::Save => "Save now",
+            Self::NextBoard => "Switch to next board",
+            Self::CommandPalette => "Command palette",
+            Self::QuickCapture => "Quick capture todo",
+            Self::Bookmarks => "Manage bookmarks",
+        }
+    }
+
+    pub fn default_shortcut(self) -> KeyboardShortcut {
+        match self {
+            Self::AddNote => KeyboardShortcut::new(Modifiers::NONE, Key::N),
+            Self::AddTodo => KeyboardShortcut::new(Modifiers::NONE, Key::T),
+            Self::UndoConnection => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Backspace)
+            }
+            Self::Undo => KeyboardShortcut::new(Modifiers::COMMAND, Key::Z),
+            Self::Redo => KeyboardShortcut::new(Modifiers::COMMAND, Key::Y),
+            Self::DeleteSelection => KeyboardShortcut::new(Modifiers::NONE, Key::Delete),
+            Self::ToggleItemList => KeyboardShortcut::new(Modifiers::NONE, Key::L),
+            Self::Search => KeyboardShortcut::new(Modifiers::COMMAND, Key::F),
+            Self::OpenSettings => KeyboardShortcut::new(Modifiers::COMMAND, Key::Comma),
+            Self::ToggleFocus => KeyboardShortcut::new(Modifiers::NONE, Key::F),
+            Self::Duplicate => KeyboardShortcut::new(Modifiers::COMMAND, Key::D),
+            Self::ResetZoom => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num0),
+            Self::FitAll => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num1),
+            Self::FitSelection => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num2),
+            Self::Save => KeyboardShortcut::new(Modifiers::COMMAND, Key::S),
+            Self::NextBoard => KeyboardShortcut::new(Modifiers::COMMAND, Key::Tab),
+            Self::CommandPalette => KeyboardShortcut::new(Modifiers::COMMAND, Key::P),
+            Self::Bookmarks => KeyboardShortcut::new(Modifiers::COMMAND, Key::B),
+            Self::QuickCapture => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::ALT), Key::Space)
+            }
+        }
+    }
+}
+
+/// User overrides of the default shortcuts. Actions not in the map use
+/// [`HotkeyAction::default_shortcut`].
+pub type Keybindings = HashMap<HotkeyAction, KeyboardShortcut>;
+
+/// Writes the overrides sorted by action, since `HashMap` order changes from
+/// run to run.
+pub fn sorted_bindings<S: Serializer>(bindings: &Keybindings, s: S) -> Result<S::Ok, S::Error> {
+    bindings.iter().collect::<BTreeMap<_, _>>().serialize(s)
+}
+
+pub fn binding(bindings: &Keybindings, action: HotkeyAction) -> KeyboardShortcut {
+    bindings
+        .get(&action)
+        .copied()
+        .unwrap_or_else(|| action.default_shortcut())
+}
+
+/// Canvas hotkeys stay quiet while a text field has focus, so typing into a
+/// note never triggers them. Every hotkey goes through here.
+pub fn hotkeys_active(ctx: &Context) -> bool {
+    !ctx.wants_keyboard_input() && ctx.memory(|m| m.focused().is_none())
+}
+
+/// Consumes `shortcut` if it was pressed this frame and hotkeys are active.
+pub fn hotkey_pressed(ctx: &Context, shortcut: &KeyboardShortcut) -> bool {
+    hotkeys_active(ctx) && ctx.input_mut(|i| i.consume_shortcut(shortcut))
+}
+
+/// Whether the shortcut bound to `action` was pressed this frame.
+pub fn action_pressed(ctx: &Context, bindings: &Keybindings, action: HotkeyAction) -> bool {
+    hotkey_pressed(ctx, &binding(bindings, action))
+}
+
+/// Settings section listing every action with its shortcut. Clicking a
+/// shortcut waits for the next key press (Escape cancels).
+pub fn keybindings_ui(
+    ui: &mut egui::Ui,
+    bindings: &mut Keybindings,
+    rebinding: &mut Option<HotkeyAction>,
+) {
+    if let Some(action) = *rebinding {
+        let pressed = ui.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                _ => None,
+            })
+        });
+        if let Some(shortcut) = pressed {
+            if shortcut.logical_key != Key::Escape {
+                bindings.insert(action, shortcut);
+            }
+            *rebinding = None;
+        }
+    }
+
+    let ctx = ui.ctx().clone();
+    let texts: Vec<(HotkeyAction, String)> = HotkeyAction::ALL
+        .into_iter()
+        .map(|a| (a, ctx.format_shortcut(&binding(bindings, a))))
+        .collect();
+    egui::Grid::new("keybindings").show(ui, |ui| {
+        for (action, text) in &texts {
+            ui.label(action.label());
+            let waiting = *rebinding == Some(*action);
+            let label = if waiting { "Press a key..." } else { text };
+            if ui.selectable_label(waiting, label).clicked() {
+                *rebinding = Some(*action);
+            }
+            if let Some((other, _)) = texts.iter().find(|(a, t)| a != action && t == text) {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("Also bound to {}", other.label()),
+                );
+            }
+            ui.end_row();
+        }
+    });
+    if ui.button("Reset to defaults").clicked() {
+        bindings.clear();
+        *rebinding = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::{CentralPanel, RawInput, TextEdit};
+
+    use super::*;
+
+    fn key_press(key: Key, modifiers: Modifiers) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    /// Runs two frames with a text field, focused or not, pressing `event`
+    /// on the second; returns which actions fired on it.
+    fn fired(typing: bool, event: Event) -> Vec<HotkeyAction> {
+        let ctx = Context::default();
+        let bindings = Keybindings::new();
+        let mut text = String::new();
+        let mut fired = Vec::new();
+        for frame in 0..2 {
+            let input = RawInput {
+                modifiers: match &event {
+                    Event::Key { modifiers, .. } if frame == 1 => *modifiers,
+                    _ => Modifiers::NONE,
+                },
+                events: if frame == 1 { vec![event.clone()] } else { Vec::new() },
+                ..RawInput::default()
+            };
+            _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let field = ui.add(TextEdit::singleline(&mut text));
+                    if typing && frame == 0 {
+                        field.request_focus();
+                    }
+                });
+                fired.extend(
+                    HotkeyAction::ALL
+                        .into_iter()
+                        .filter(|&a| action_pressed(ctx, &bindings, a)),
+                );
+            });
+        }
+        fired
+    }
+
+    #[test]
+    fn hotkeys_fire_on_the_canvas() {
+        assert_eq!(
+            fired(false, key_press(Key::N, Modifiers::NONE)),
+            [HotkeyAction::AddNote]
+        );
+        assert_eq!(
+            fired(false, key_press(Key::T, Modifiers::NONE)),
+            [HotkeyAction::AddTodo]
+        );
+    }
+
+    #[test]
+    fn typing_in_a_note_does_not_trigger_hotkeys() {
+        for (key, modifiers) in [
+            (Key::N, Modifiers::NONE),
+            (Key::T, Modifiers::NONE),
+            (Key::F, Modifiers::NONE),
+            (Key::L, Modifiers::NONE),
+            (Key::Delete, Modifiers::NONE),
+            (Key::Backspace, Modifiers::COMMAND.plus(Modifiers::SHIFT)),
+            (Key::Z, Modifiers::COMMAND),
+        ] {
+            assert_eq!(fired(true, key_press(key, modifiers)), [], "{key:?}");
+        }
+    }
+
+    /// egui-winit names keys by the character they type, so with Shift held
+    /// a digit or punctuation key arrives as its shifted character ("!" for
+    /// 1 on most layouts), which egui has no key for. Only letters and named
+    /// keys make it through with Shift.
+    #[test]
+    fn default_shortcuts_reach_egui() {
+        for action in HotkeyAction::ALL {
+            let shortcut = action.default_shortcut();
+            let key = shortcut.logical_key;
+            assert_eq!(Key::from_name(key.name()), Some(key), "{action:?}");
+            let symbol = key.symbol_or_name();
+            let shifted_char = symbol.len() == 1 && !symbol.chars().all(|c| c.is_ascii_alphabetic());
+            assert!(!(shortcut.modifiers.shift && shifted_char), "{action:?}");
+        }
+    }
+
+    #[test]
+    fn overrides_replace_the_default() {
+        let mut bindings = Keybindings::new();
+        let shortcut = KeyboardShortcut::new(Modifiers::ALT, Key::A);
+        bindings.insert(HotkeyAction::AddNote, shortcut);
+        assert_eq!(binding(&bindings, HotkeyAction::AddNote), shortcut);
+        assert_eq!(
+            binding(&bindings, HotkeyAction::AddTodo),
+            HotkeyAction::AddTodo.default_shortcut()
+        );
+    }
+}
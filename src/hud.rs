@@ -0,0 +1,48 @@
+use eframe::egui::{self, Align2, Context, Pos2, RichText, Vec2};
+
+/// Formats a number for the HUD, switching to k/M/G suffixes so huge values
+/// keep a bounded width. Non-finite values render as a dash.
+pub fn format_compact(value: f32, decimals: usize) -> String {
+    if !value.is_finite() {
+        return "—".to_string();
+    }
+    let abs = value.abs();
+    let (scaled, suffix) = if abs >= 1e9 {
+        (value / 1e9, "G")
+    } else if abs >= 1e6 {
+        (value / 1e6, "M")
+    } else if abs >= 1e4 {
+        (value / 1e3, "k")
+    } else {
+        (value, "")
+    };
+    format!("{:.*}{}", decimals, scaled, suffix)
+}
+
+pub fn format_zoom(zoom: f32, decimals: usize) -> String {
+    format!("{}%", format_compact(zoom * 100.0, decimals))
+}
+
+/// World coordinates are shown as integers once zoomed out, where fractions
+/// of a unit are below a pixel anyway.
+pub fn format_world_pos(pos: Pos2, zoom: f32, decimals: usize) -> String {
+    let decimals = if zoom < 1.0 { 0 } else { decimals };
+    format!(
+        "{}, {}",
+        format_compact(pos.x, decimals),
+        format_compact(pos.y, decimals)
+    )
+}
+
+pub fn show_hud(ctx: &Context, zoom: f32, pointer_world: Option<Pos2>, decimals: usize) {
+    egui::Area::new(egui::Id::new("hud"))
+        .anchor(Align2::LEFT_BOTTOM, Vec2::new(8.0, -8.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            let mut text = format!("Zoom {}", format_zoom(zoom, decimals));
+            if let Some(pos) = pointer_world {
+                text += &format!("  ·  {}", format_world_pos(pos, zoom, decimals));
+            }
+            ui.label(RichText::new(text).monospace().weak());
+        });
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Datelike, Local, Utc};
+
+use crate::timezone::BoardZone;
+use crate::{anchored_day, AppState, LoopFrequency};
+
+/// How long each exported event lasts; todos only have a due instant.
+const EVENT_MINUTES: u32 = 15;
+
+/// The RRULE for a repeat on `day` of the month, or `None` for one-off
+/// todos. Plain monthly rules skip months too short for the day, where the
+/// app clamps to the month's last day instead; picking the last of 28..=day
+/// does the same.
+fn rrule(freq: LoopFrequency, day: u32, month: u32) -> Option<String> {
+    let last_of_28_to_day = || {
+        let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+        format!("BYMONTHDAY={};BYSETPOS=-1", days.join(","))
+    };
+    let rule = match freq {
+        LoopFrequency::Once | LoopFrequency::Custom => return None,
+        LoopFrequency::Monthly if day > 28 => {
+            return Some(format!("FREQ=MONTHLY;{}", last_of_28_to_day()));
+        }
+        LoopFrequency::Yearly if month == 2 && day == 29 => {
+            return Some(format!("FREQ=YEARLY;BYMONTH=2;{}", last_of_28_to_day()));
+        }
+        LoopFrequency::Daily => "FREQ=DAILY",
+        LoopFrequency::Weekly => "FREQ=WEEKLY",
+        LoopFrequency::Monthly => "FREQ=MONTHLY",
+        LoopFrequency::Yearly => "FREQ=YEARLY",
+        LoopFrequency::Sunday => "FREQ=WEEKLY;BYDAY=SU",
+        LoopFrequency::Monday => "FREQ=WEEKLY;BYDAY=MO",
+        LoopFrequency::Tuesday => "FREQ=WEEKLY;BYDAY=TU",
+        LoopFrequency::Wednesday => "FREQ=WEEKLY;BYDAY=WE",
+        LoopFrequency::Thursday => "FREQ=WEEKLY;BYDAY=TH",
+        LoopFrequency::Friday => "FREQ=WEEKLY;BYDAY=FR",
+        LoopFrequency::Saturday => "FREQ=WEEKLY;BYDAY=SA",
+    };
+    Some(rule.to_string())
+}
+
+/// Escapes a TEXT value (RFC 5545 3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+/// Appends a content line, folded so no line exceeds 75 octets.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// `DTSTART` in the board zone, so repeats keep their wall-clock time across
+/// DST changes. Boards on the system zone use floating time, which calendar
+/// clients read in the viewer's own zone.
+fn dtstart(zone: BoardZone, due: DateTime<Local>) -> String {
+    let wall = zone.wall_clock(due).format("%Y%m%dT%H%M%S");
+    match zone.name() {
+        Some(tz) => format!("DTSTART;TZID={}:{}", tz, wall),
+        None => format!("DTSTART:{}", wall),
+    }
+}
+
+/// Every todo with a due date as a VEVENT, with repeats as RRULEs and the
+/// details note, if any, as the description.
+pub fn todos_to_ics(state: &AppState, now: DateTime<Local>) -> String {
+    let zone = state.zone();
+    let stamp = now.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ");
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//Endless Canvas//Todos//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    for todo in state.todos.iter() {
+        let Some(due) = todo.due else {
+            continue;
+        };
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:todo-{}@endless-canvas", todo.id));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut out, &dtstart(zone, due));
+        push_line(&mut out, &format!("DURATION:PT{}M", EVENT_MINUTES));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(todo.text.trim())));
+        let details = todo
+            .details_note
+            .and_then(|id| state.notes.iter().find(|n| n.id == id));
+        if let Some(note) = details {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(&note.text)));
+        }
+        let date = zone.wall_clock(due).date();
+        let day = anchored_day(todo.repeat_day, date);
+        let rule = match todo.loop_freq {
+            LoopFrequency::Custom => Some(todo.recurrence.to_rrule()),
+            freq => rrule(freq, day, date.month()),
+        };
+        if let Some(rule) = rule {
+            push_line(&mut out, &format!("RRULE:{}", rule));
+        }
+        if !todo.meta.tags.is_empty() {
+            let tags: Vec<String> = todo.meta.tags.iter().map(|t| escape(t)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", tags.join(",")));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// How many todos [`todos_to_ics`] would write.
+pub fn exportable_count(state: &AppState) -> usize {
+    state.todos.iter().filter(|t| t.due.is_some()).count()
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use eframe::egui::{self, ColorImage, Context, Pos2, TextureHandle, Vec2};
+
+use crate::{load_image_from_path, AppState};
+
+/// Width image notes get on import; the height follows the aspect ratio.
+const IMAGE_NOTE_WIDTH: f32 = 200.0;
+const GRID_GAP: f32 = 20.0;
+
+enum Slot {
+    Loading,
+    Ready(TextureHandle),
+    Failed,
+}
+
+/// Textures for image notes, decoded on background threads the first time a
+/// note asks for them.
+pub struct ImageCache {
+    slots: HashMap<PathBuf, Slot>,
+    sender: Sender<(PathBuf, Option<ColorImage>)>,
+    receiver: Receiver<(PathBuf, Option<ColorImage>)>,
+}
+impl Default for ImageCache {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            slots: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+}
+impl ImageCache {
+    /// Uploads images that finished decoding since the last frame.
+    pub fn poll(&mut self, ctx: &Context) {
+        while let Ok((path, image)) = self.receiver.try_recv() {
+            let slot = match image {
+                Some(image) => {
+                    let name = path.display().to_string();
+                    Slot::Ready(ctx.load_texture(name, image, Default::default()))
+                }
+                None => Slot::Failed,
+            };
+            self.slots.insert(path, slot);
+        }
+    }
+
+    /// Drops the cached texture so the file is read again next time.
+    pub fn forget(&mut self, path: &Path) {
+        self.slots.remove(path);
+    }
+
+    /// Shows the image at `path` scaled to the available width, starting the
+    /// load if needed.
+    pub fn show(&mut self, ui: &mut egui::Ui, path: &str) {
+        let path = PathBuf::from(path);
+        let slot = self.slots.entry(path.clone()).or_insert_with(|| {
+            let sender = self.sender.clone();
+            let ctx = ui.ctx().clone();
+            thread::spawn(move || {
+                let image = load_image_from_path(&path).ok();
+                _ = sender.send((path, image));
+                ctx.request_repaint();
+            });
+            Slot::Loading
+        });
+        match slot {
+            Slot::Loading => {
+                ui.spinner();
+            }
+            Slot::Ready(texture) => {
+                let size = texture.size_vec2();
+                let width = ui.available_width().max(1.0);
+                ui.image((texture.id(), size * (width / size.x)));
+            }
+            Slot::Failed => {
+                ui.weak("Image missing");
+            }
+        }
+    }
+}
+
+/// Adds an image note per file, laid out in a grid whose top-left is `at`.
+/// Notes keep a common width and each image's aspect ratio; the pixels load
+/// later, when the notes are drawn. Returns the number of notes added and the
+/// files that couldn't be read as images.
+pub fn import_images(state: &mut AppState, files: &[PathBuf], at: Pos2) -> (usize, Vec<PathBuf>) {
+    let mut failed = Vec::new();
+    let sized: Vec<(&PathBuf, f32)> = files
+        .iter()
+        .filter_map(|path| match image::image_dimensions(path) {
+            Ok((w, h)) if w > 0 => Some((path, IMAGE_NOTE_WIDTH * h as f32 / w as f32)),
+            _ => {
+                failed.push(path.clone());
+                None
+            }
+        })
+        .collect();
+    let columns = (sized.len() as f32).sqrt().ceil().max(1.0) as usize;
+    let mut y = at.y;
+    for row in sized.chunks(columns) {
+        let mut row_height: f32 = 0.0;
+        for (i, &(path, height)) in row.iter().enumerate() {
+            let x = at.x + i as f32 * (IMAGE_NOTE_WIDTH + GRID_GAP);
+            let id = state.add_note(egui::pos2(x, y));
+            if let Some(note) = state.notes.iter_mut().find(|n| n.id == id) {
+                note.text = path
+                    .file_name()
+                    .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+                note.image = Some(path.display().to_string());
+                // Room for the title bar and the caption under the image.
+                note.size = Vec2::new(IMAGE_NOTE_WIDTH, height + 60.0);
+                row_height = row_height.max(note.size.y);
+            }
+        }
+        y += row_height + GRID_GAP;
+    }
+    (sized.len(), failed)
+}
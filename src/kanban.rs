@@ -0,0 +1,236 @@
+use chrono::{DateTime, Local, NaiveTime};
+use eframe::egui::{self, Context, Id, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::bookmarks::{bookmark_picker, ViewBookmark};
+use crate::fonts::ICON_STAR;
+use crate::hotkeys::{binding, HotkeyAction};
+use crate::settings::Settings;
+use crate::timezone::BoardZone;
+use crate::{AppState, Todo};
+
+/// What the central area shows. The canvas layout is untouched by the other
+/// views, so switching back finds every item where it was.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Canvas,
+    Kanban,
+    Calendar,
+    Timeline,
+}
+
+impl ViewMode {
+    const ALL: [Self; 4] = [Self::Canvas, Self::Kanban, Self::Calendar, Self::Timeline];
+}
+
+/// What was picked in the view bar.
+pub enum ViewBarAction {
+    Hotkey(HotkeyAction),
+    /// Jump to the bookmark at this index.
+    Bookmark(usize),
+}
+
+/// Top bar switching between the views, plus the agenda toggle. On the
+/// canvas it also has the view fitting buttons and a bookmark dropdown.
+/// `has_selection` enables "Fit selection".
+pub fn view_mode_bar(
+    ctx: &Context,
+    settings: &mut Settings,
+    has_selection: bool,
+    bookmarks: &[ViewBookmark],
+) -> Option<ViewBarAction> {
+    let mut clicked = None;
+    egui::TopBottomPanel::top("view_mode").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("View:");
+            for m in ViewMode::ALL {
+                ui.selectable_value(&mut settings.view_mode, m, format!("{:?}", m));
+            }
+            ui.separator();
+            ui.toggle_value(&mut settings.show_agenda, "Agenda");
+            ui.toggle_value(&mut settings.show_time_report, "Time report");
+            if settings.view_mode != ViewMode::Canvas {
+                return;
+            }
+            ui.separator();
+            for (action, label, enabled) in [
+                (HotkeyAction::FitAll, "Fit all", true),
+                (HotkeyAction::FitSelection, "Fit selection", has_selection),
+                (HotkeyAction::ResetZoom, "100%", true),
+            ] {
+                let shortcut = ctx.format_shortcut(&binding(&settings.keybindings, action));
+                if ui
+                    .add_enabled(enabled, egui::Button::new(label))
+                    .on_hover_text(format!("{} ({})", action.label(), shortcut))
+                    .clicked()
+                {
+                    clicked = Some(ViewBarAction::Hotkey(action));
+                }
+            }
+            if !bookmarks.is_empty() {
+                if let Some(i) = bookmark_picker(ui, bookmarks) {
+                    clicked = Some(ViewBarAction::Bookmark(i));
+                }
+            }
+        });
+    });
+    clicked
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Backlog,
+    DueToday,
+    Overdue,
+    Done,
+}
+
+impl Column {
+    const ALL: [Self; 4] = [Self::Backlog, Self::DueToday, Self::Overdue, Self::Done];
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Backlog => "Backlog",
+            Self::DueToday => "Due today",
+            Self::Overdue => "Overdue",
+            Self::Done => "Done",
+        }
+    }
+
+    /// Where `todo` sits at `now`, with "today" on the board zone's clock.
+    fn of(todo: &Todo, now: DateTime<Local>, zone: BoardZone) -> Self {
+        if todo.is_done {
+            return Self::Done;
+        }
+        match todo.due {
+            Some(due) if due < now => Self::Overdue,
+            Some(due) if zone.wall_clock(due).date() == zone.wall_clock(now).date() => {
+                Self::DueToday
+            }
+            _ => Self::Backlog,
+        }
+    }
+
+    /// Overdue is where time puts todos, not somewhere to drop them.
+    fn accepts_drops(self) -> bool {
+        self != Self::Overdue
+    }
+}
+
+pub enum KanbanAction {
+    /// A card was dropped on another column.
+    Move(usize, Column),
+    /// A card was double-clicked: show it on the canvas.
+    Show(usize),
+}
+
+/// Todos in Backlog / Due today / Overdue / Done columns, with cards that
+/// can be dragged between the columns. Items the filter hides are left out.
+pub fn kanban_view(
+    ui: &mut Ui,
+    state: &AppState,
+    now: DateTime<Local>,
+    read_only: bool,
+) -> Option<KanbanAction> {
+    let zone = state.zone();
+    let mut action = None;
+    ui.columns(Column::ALL.len(), |columns| {
+        for (ui, column) in columns.iter_mut().zip(Column::ALL) {
+            let mut todos: Vec<&Todo> = state
+                .todos
+                .iter()
+                .filter(|t| state.filter.matches_todo(t) && Column::of(t, now, zone) == column)
+                .collect();
+            todos.sort_by_key(|t| (t.due.is_none(), t.due, t.id));
+            ui.heading(format!("{} ({})", column.title(), todos.len()));
+            let frame = egui::Frame::group(ui.style()).inner_margin(4.0);
+            let (_, dropped) = ui.dnd_drop_zone::<usize, _>(frame, |ui| {
+                ui.set_min_size(ui.available_size());
+                egui::ScrollArea::vertical()
+                    .id_source(Id::new("kanban_column").with(column.title()))
+                    .show(ui, |ui| {
+                        for todo in todos {
+                            if let Some(a) = card(ui, todo, zone, read_only) {
+                                action = Some(a);
+                            }
+                        }
+                    });
+            });
+            if let Some(id) = dropped.filter(|_| column.accepts_drops() && !read_only) {
+                action = Some(KanbanAction::Move(*id, column));
+            }
+        }
+    });
+    action
+}
+
+fn card(ui: &mut Ui, todo: &Todo, zone: BoardZone, read_only: bool) -> Option<KanbanAction> {
+    let contents = |ui: &mut Ui| {
+        egui::Frame::window(ui.style())
+            .inner_margin(6.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                let mut title = todo.text.clone();
+                if todo.meta.starred {
+                    title = format!("{} {}", ICON_STAR, title);
+                }
+                ui.add(egui::Label::new(title).wrap(true));
+                if let Some(due) = todo.due {
+                    ui.weak(zone.wall_clock(due).format("%Y-%m-%d %H:%M").to_string());
+                }
+            })
+            .response
+    };
+    let id = Id::new("kanban_card").with(todo.id);
+    let response = if read_only {
+        contents(ui)
+    } else {
+        ui.dnd_drag_source(id, todo.id, contents).inner
+    };
+    let double_clicked = ui.input(|i| {
+        i.pointer
+            .button_double_clicked(egui::PointerButton::Primary)
+            .then(|| i.pointer.interact_pos())
+            .flatten()
+            .is_some_and(|p| response.rect.contains(p))
+    });
+    double_clicked.then_some(KanbanAction::Show(todo.id))
+}
+
+impl AppState {
+    /// Moves todo `id` into `column` by changing what the column is based on:
+    /// reopening it, and clearing or setting the due date. Done is left to
+    /// the caller, which handles completion cues and blocking. Returns
+    /// whether anything changed.
+    pub fn move_to_column(&mut self, id: usize, column: Column, now: DateTime<Local>) -> bool {
+        let zone = self.zone();
+        let Some(todo) = self.todos.iter_mut().find(|t| t.id == id) else {
+            return false;
+        };
+        if Column::of(todo, now, zone) == column {
+            return false;
+        }
+        if column != Column::Done && todo.is_done {
+            todo.set_done(false, now);
+        }
+        let today = zone.wall_clock(now).date();
+        let due_by_today = todo.due.is_some_and(|d| zone.wall_clock(d).date() <= today);
+        match column {
+            Column::Backlog if due_by_today => todo.due = None,
+            Column::DueToday if !todo.due.is_some_and(|d| d >= now && due_by_today) => {
+                // Keep the usual time of day when it is still ahead, else
+                // make it due by the end of the day.
+                let time = todo
+                    .due
+                    .map(|d| zone.wall_clock(d).time())
+                    .filter(|&t| today.and_time(t) > zone.wall_clock(now))
+                    .unwrap_or(NaiveTime::from_hms_opt(23, 59, 0).unwrap_or_default());
+                todo.due = Some(zone.instant_at(today.and_time(time)));
+                todo.rearm_reminders();
+            }
+            _ => {}
+        }
+        true
+    }
+}
@@ -0,0 +1,131 @@
+use eframe::egui::{self, Align2, Color32, FontId, Painter, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::Todo;
+
+/// A kanban column: a vertical band of the canvas between two world x
+/// coordinates. Todos dropped inside it take its name as their status.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Lane {
+    pub name: String,
+    pub x_min: f32,
+    pub x_max: f32,
+    /// Todos in this lane count as done, and completed todos move here.
+    #[serde(default)]
+    pub done: bool,
+}
+impl Lane {
+    fn new(name: &str, x_min: f32, width: f32, done: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            x_min,
+            x_max: x_min + width,
+            done,
+        }
+    }
+
+    fn contains(&self, x: f32) -> bool {
+        (self.x_min..self.x_max).contains(&x)
+    }
+}
+
+const DEFAULT_LANE_WIDTH: f32 = 400.0;
+
+/// The lane under world x coordinate `x`.
+pub fn lane_at(lanes: &[Lane], x: f32) -> Option<&Lane> {
+    lanes.iter().find(|l| l.contains(x))
+}
+
+/// Centers `todo` horizontally in `lane` and gives it the lane's status.
+pub fn snap_into_lane(todo: &mut Todo, lane: &Lane) {
+    let center = (lane.x_min + lane.x_max) * 0.5;
+    todo.position.x = center - todo.size.x * 0.5;
+    todo.status = Some(lane.name.clone());
+}
+
+/// Updates a todo that was just dropped at its current position: it snaps
+/// into the lane under its center, or loses its status outside every lane.
+/// Returns `Some(done)` when landing in or leaving a done lane should change
+/// whether the todo is done.
+pub fn drop_into_lanes(todo: &mut Todo, lanes: &[Lane]) -> Option<bool> {
+    let Some(lane) = lane_at(lanes, todo.position.x + todo.size.x * 0.5) else {
+        todo.status = None;
+        return None;
+    };
+    snap_into_lane(todo, lane);
+    (lane.done != todo.is_done).then_some(lane.done)
+}
+
+/// Moves a todo that was just completed into the first done lane, if there is
+/// one.
+pub fn move_to_done_lane(todo: &mut Todo, lanes: &[Lane]) {
+    if let Some(lane) = lanes.iter().find(|l| l.done) {
+        snap_into_lane(todo, lane);
+    }
+}
+
+/// Shades each lane across the canvas, with its name along the top edge.
+/// `to_screen_x` maps a world x coordinate to the screen.
+pub fn draw_lanes(
+    painter: &Painter,
+    canvas: Rect,
+    lanes: &[Lane],
+    text_color: Color32,
+    to_screen_x: impl Fn(f32) -> f32,
+) {
+    for (i, lane) in lanes.iter().enumerate() {
+        let rect = Rect::from_x_y_ranges(
+            to_screen_x(lane.x_min)..=to_screen_x(lane.x_max),
+            canvas.y_range(),
+        );
+        if !rect.intersects(canvas) {
+            continue;
+        }
+        let shade = if i % 2 == 0 { 12 } else { 24 };
+        painter.rect_filled(rect, 0.0, Color32::from_white_alpha(shade));
+        painter.text(
+            egui::pos2(rect.center().x, canvas.top() + 8.0),
+            Align2::CENTER_TOP,
+            &lane.name,
+            FontId::proportional(16.0),
+            text_color,
+        );
+    }
+}
+
+/// Editor for the board's lanes.
+pub fn lanes_ui(ui: &mut egui::Ui, lanes: &mut Vec<Lane>) {
+    let mut remove = None;
+    for (i, lane) in lanes.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut lane.name).desired_width(80.0));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut lane.x_min).speed(5.0));
+            ui.label("to");
+            ui.add(
+                egui::DragValue::new(&mut lane.x_max)
+                    .speed(5.0)
+                    .clamp_range(lane.x_min..=f32::MAX),
+            );
+            ui.checkbox(&mut lane.done, "Done");
+            if ui.small_button("\u{2715}").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        lanes.remove(i);
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Add lane").clicked() {
+            let x = lanes.last().map_or(0.0, |l| l.x_max);
+            lanes.push(Lane::new("Lane", x, DEFAULT_LANE_WIDTH, false));
+        }
+        if lanes.is_empty() && ui.button("Todo / Doing / Done").clicked() {
+            for (i, name) in ["Todo", "Doing", "Done"].into_iter().enumerate() {
+                let x = i as f32 * DEFAULT_LANE_WIDTH;
+                lanes.push(Lane::new(name, x, DEFAULT_LANE_WIDTH, name == "Done"));
+            }
+        }
+    });
+}
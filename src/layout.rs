@@ -0,0 +1,41 @@
+use eframe::egui::{Rect, Vec2};
+
+/// Pushes overlapping rects apart in a few relaxation passes until none
+/// overlap (keeping `gap` between them) or `max_passes` is reached. Each pair
+/// separates along the axis where they overlap least, so the overall
+/// arrangement is preserved. Returns which rects moved.
+pub fn spread_overlaps(rects: &mut [Rect], gap: f32, max_passes: usize) -> Vec<bool> {
+    let mut moved = vec![false; rects.len()];
+    for _ in 0..max_passes {
+        let mut any = false;
+        for i in 0..rects.len() {
+            for j in i + 1..rects.len() {
+                let a = rects[i].expand(gap / 2.0);
+                let b = rects[j].expand(gap / 2.0);
+                if !a.intersects(b) {
+                    continue;
+                }
+                let overlap_x = a.max.x.min(b.max.x) - a.min.x.max(b.min.x);
+                let overlap_y = a.max.y.min(b.max.y) - a.min.y.max(b.min.y);
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+                let delta = b.center() - a.center();
+                let push = if overlap_x < overlap_y {
+                    Vec2::new(overlap_x * if delta.x < 0.0 { -1.0 } else { 1.0 }, 0.0)
+                } else {
+                    Vec2::new(0.0, overlap_y * if delta.y < 0.0 { -1.0 } else { 1.0 })
+                };
+                rects[i] = rects[i].translate(-push / 2.0);
+                rects[j] = rects[j].translate(push / 2.0);
+                moved[i] = true;
+                moved[j] = true;
+                any = true;
+            }
+        }
+        if !any {
+            break;
+        }
+    }
+    moved
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, Context};
+
+use crate::fonts::{ICON_BOX, ICON_BOX_CHECKED, ICON_NOTE, ICON_PIN};
+use crate::priority::Priority;
+use crate::{generate_title, AppState};
+
+struct Row {
+    id: usize,
+    label: String,
+    pinned: bool,
+    priority: Priority,
+}
+
+/// Side panel listing every note and todo. Pinned rows stay above the rest.
+/// While a search is active only `matches` are listed. Returns the id of the
+/// item the user clicked (or picked with Enter in the search box), if any.
+pub fn list_panel(
+    ctx: &Context,
+    state: &mut AppState,
+    matches: Option<&HashSet<usize>>,
+) -> Option<usize> {
+    let mut rows: Vec<Row> = state
+        .notes
+        .iter()
+        .map(|n| Row {
+            id: n.id,
+            label: format!("{} {}", ICON_NOTE, generate_title(&n.text)),
+            pinned: n.meta.list_pinned,
+            priority: Priority::Normal,
+        })
+        .chain(state.todos.iter().map(|t| Row {
+            id: t.id,
+            label: format!(
+                "{} {}",
+                if t.is_done {
+                    ICON_BOX_CHECKED
+                } else {
+                    ICON_BOX
+                },
+                generate_title(&t.text)
+            ),
+            pinned: t.meta.list_pinned,
+            priority: t.priority,
+        }))
+        .filter(|r| matches.is_none_or(|m| m.contains(&r.id)))
+        .collect();
+    // Stable, so the original order is kept within each group.
+    if state.settings.sort_by_priority {
+        rows.sort_by_key(|r| (!r.pinned, std::cmp::Reverse(r.priority)));
+    } else {
+        rows.sort_by_key(|r| !r.pinned);
+    }
+
+    let mut clicked = None;
+    let mut toggle_pin = None;
+    egui::SidePanel::left("item_list")
+        .resizable(true)
+        .default_width(220.0)
+        .show(ctx, |ui| {
+            ui.heading("Items");
+            let search = &mut state.search;
+            let field = ui.add(egui::TextEdit::singleline(&mut search.query).hint_text("Search"));
+            if std::mem::take(&mut search.focus) {
+                field.request_focus();
+            }
+            // Enter jumps to the first result.
+            if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                clicked = rows.first().map(|r| r.id);
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut search.scopes.text, "Text");
+                ui.checkbox(&mut search.scopes.details, "Details notes");
+                ui.checkbox(&mut search.scopes.tags, "Tags");
+            });
+            ui.checkbox(&mut state.settings.sort_by_priority, "Sort by priority");
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let pinned_count = rows.iter().filter(|r| r.pinned).count();
+                for (i, row) in rows.iter().enumerate() {
+                    if i == pinned_count && pinned_count > 0 {
+                        ui.separator();
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(row.pinned, ICON_PIN)
+                            .on_hover_text("Pin to top of list")
+                            .clicked()
+                        {
+                            toggle_pin = Some(row.id);
+                        }
+                        if ui.link(&row.label).clicked() {
+                            clicked = Some(row.id);
+                        }
+                    });
+                }
+            });
+        });
+
+    if let Some(id) = toggle_pin {
+        if let Some(n) = state.notes.iter_mut().find(|n| n.id == id) {
+            n.meta.list_pinned = !n.meta.list_pinned;
+        } else if let Some(t) = state.todos.iter_mut().find(|t| t.id == id) {
+            t.meta.list_pinned = !t.meta.list_pinned;
+        }
+    }
+    clicked
+}
@@ -0,0 +1,140 @@
+//! Password lock that keeps a board read-only until unlocked. This is not
+//! encryption: the board file stays readable, only editing in the app is
+//! gated. Unlike a view mode, it can't be left without the password.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use eframe::egui::{self, Align2, Context};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::AppState;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoardLock {
+    salt: String,
+    hash: String,
+}
+impl BoardLock {
+    pub fn new(password: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let salt =
+            hex(&Sha256::digest(format!("{}-{}", nanos, std::process::id())))[..16].to_string();
+        let hash = hash_password(&salt, password);
+        Self { salt, hash }
+    }
+
+    pub fn verify(&self, password: &str) -> bool {
+        hash_password(&self.salt, password) == self.hash
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hash_password(salt: &str, password: &str) -> String {
+    hex(&Sha256::digest(format!("{}{}", salt, password)))
+}
+
+/// Text typed into the lock fields; never saved.
+#[derive(Default)]
+pub struct LockInput {
+    password: String,
+    confirm: String,
+    wrong: bool,
+}
+
+fn unlock_row(ui: &mut egui::Ui, lock: &BoardLock, unlocked: &mut bool, input: &mut LockInput) {
+    ui.horizontal(|ui| {
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut input.password)
+                .password(true)
+                .hint_text("Password")
+                .desired_width(140.0),
+        );
+        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button("Unlock").clicked() || submitted {
+            input.wrong = !lock.verify(&input.password);
+            *unlocked = !input.wrong;
+            input.password.clear();
+        }
+    });
+    if input.wrong {
+        ui.colored_label(ui.visuals().error_fg_color, "Wrong password");
+    }
+}
+
+/// Banner shown over the canvas while the board is locked.
+pub fn lock_banner(ctx: &Context, state: &mut AppState) {
+    let AppState {
+        lock: Some(lock),
+        unlocked,
+        lock_input,
+        ..
+    } = state
+    else {
+        return;
+    };
+    if *unlocked {
+        return;
+    }
+    egui::Area::new(egui::Id::new("lock_banner"))
+        .anchor(Align2::CENTER_TOP, [0.0, 8.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label("\u{1F512} Read-only: this board is locked with a password.");
+                unlock_row(ui, lock, unlocked, lock_input);
+            });
+        });
+}
+
+/// Lock controls for the settings window.
+pub fn lock_settings(
+    ui: &mut egui::Ui,
+    lock: &mut Option<BoardLock>,
+    unlocked: &mut bool,
+    input: &mut LockInput,
+) {
+    ui.label("Board lock");
+    match lock {
+        None => {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut input.password)
+                        .password(true)
+                        .hint_text("Password")
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut input.confirm)
+                        .password(true)
+                        .hint_text("Confirm")
+                        .desired_width(120.0),
+                );
+                let ready = !input.password.is_empty() && input.password == input.confirm;
+                if ui
+                    .add_enabled(ready, egui::Button::new("Lock board"))
+                    .clicked()
+                {
+                    *lock = Some(BoardLock::new(&input.password));
+                    *unlocked = false;
+                    *input = LockInput::default();
+                }
+            });
+        }
+        Some(l) if !*unlocked => unlock_row(ui, l, unlocked, input),
+        Some(_) => {
+            ui.horizontal(|ui| {
+                if ui.button("Lock again").clicked() {
+                    *unlocked = false;
+                }
+                if ui.button("Remove password").clicked() {
+                    *lock = None;
+                }
+            });
+        }
+    }
+}
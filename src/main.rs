@@ -1,19 +1,146 @@
-use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
 use eframe::{egui, App, Frame};
 use egui::{
-    Color32, ColorImage, Context, DragValue, Id, Pos2, Rect, Sense, Stroke, TextureHandle, Vec2,
+    Color32, ColorImage, Context, DragValue, Id, KeyboardShortcut, Pos2, Rect, Sense, Stroke,
+    TextureHandle, Vec2, ViewportCommand,
 };
 use egui_extras::DatePickerButton;
-use notify_rust::Notification;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+mod agenda;
+mod archive;
+mod boards;
+mod bookmarks;
+mod bundle;
+mod calendar;
+mod checklist;
+mod command_palette;
+mod connection;
+mod contrast;
+mod duplicates;
+mod export_image;
+mod filter;
+mod focus;
+mod fonts;
+mod global_hotkey;
+mod history;
+mod hotkeys;
+mod hud;
+mod ics;
+mod images;
+mod kanban;
+mod lanes;
+mod layout;
+mod list_panel;
+mod lock;
+mod markdown;
+mod menu;
+mod minimap;
+mod natural_date;
+mod notifier;
+mod palette;
+mod persist;
+mod pomodoro;
+mod priority;
+mod quick_capture;
+mod recurrence;
+mod rounded;
+mod search;
+mod settings;
+mod sim_time;
+mod snapping;
+mod snooze;
+mod streak;
+mod tags;
+#[cfg(test)]
+mod test_util;
+mod theme;
+mod time_tracking;
+mod timeline;
+mod timezone;
+mod toast;
+mod transform;
+mod trash;
+mod tray;
+mod watch;
+
+use agenda::agenda_panel;
+use archive::archive_window;
+use boards::{board_file, board_files, board_tabs, list_boards, BoardChoice};
+use bookmarks::{
+    bookmark_key_pressed, bookmark_view, bookmarks_window, key_hint, BookmarkInput, ViewBookmark,
+};
+use bundle::{export_bundle, import_bundle};
+use calendar::{calendar_view, CalendarAction};
+use checklist::{subtask_list, subtask_progress, ChecklistCache, SubTask};
+use command_palette::{command_palette, Command};
+use connection::{
+    connection_editor, connection_midpoint, connection_shapes, connection_sides_menu,
+    distance_to_connection, draw_connection_label, endpoint_handle, Anchor, Connection,
+    ConnectionEnd, Rewire,
+};
+use contrast::{accent_for, image_luminance, luminance};
+use duplicates::{duplicates_window, find_duplicates, DuplicateGroup};
+use export_image::{export_image, image_export_window, ExportStyle, ImageExport, ImageFormat};
+use filter::{visible_ids, ItemFilter};
+use focus::{focus_visible, focus_window, FOCUS_DIM};
+use fonts::{install_fonts, ICON_EDIT, ICON_LINK, ICON_STAR, ICON_STAR_OUTLINE};
+use global_hotkey::GlobalHotkey;
+use history::{edit_may_have_ended, History};
+use hotkeys::{action_pressed, HotkeyAction};
+use hud::show_hud;
+use ics::{exportable_count, todos_to_ics};
+use images::{import_images, ImageCache};
+use kanban::{kanban_view, view_mode_bar, Column, KanbanAction, ViewBarAction, ViewMode};
+use lanes::{draw_lanes, drop_into_lanes, move_to_done_lane, Lane};
+use layout::spread_overlaps;
+use list_panel::list_panel;
+use lock::{lock_banner, BoardLock, LockInput};
+use markdown::show_markdown;
+use menu::MenuAction;
+use minimap::minimap;
+use natural_date::due_text_field;
+use notifier::{
+    ActionHandler, Cue, Notifier, Reminder, ReminderAction, ReminderKind, SNOOZE_MINUTES,
+};
+use palette::{color_picker, ItemColor};
+use pomodoro::{Pomodoro, BREAK_MINUTES, WORK_MINUTES};
+use priority::{draw_title_tint, Priority};
+use quick_capture::{quick_capture_window, CaptureResult, QuickCapture};
+use recurrence::{recurrence_editor, MonthDay, Recurrence, Unit};
+use search::{matching_ids, Search, SearchCache};
+use settings::{
+    remind_before_picker, settings_window, BoardSettings, LinkClickMode, NewTodoDue, Settings,
+    Theme,
+};
+use sim_time::simulated_time_banner;
+use snapping::{draw_grid, draw_guides, Guides, Snapping};
+use snooze::snooze_menu;
+use streak::streak_strip;
+use tags::{tag_editor, tag_filter_bar};
+use theme::CustomTheme;
+use time_tracking::{time_report_panel, tracking_row, TimeSpan};
+use timeline::timeline_view;
+use timezone::BoardZone;
+use toast::Toasts;
+use transform::{Transformer, MAX_ZOOM, MIN_ZOOM};
+use trash::{trash_window, Trashed};
+use tray::{Tray, TrayCommand};
+use watch::FileWatch;
+
+const STATE_FILE: &str = "app_state.json";
+/// Sits in the default data dir and points at a relocated state file.
+const LOCATION_FILE: &str = "location";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 enum LoopFrequency {
+    #[default]
     Once,
     Daily,
     Weekly,
@@ -26,9 +153,11 @@ enum LoopFrequency {
     Thursday,
     Friday,
     Saturday,
+    /// Follows the todo's own [`Recurrence`] rule.
+    Custom,
 }
 impl LoopFrequency {
-    const ALL: [Self; 12] = [
+    const ALL: [Self; 13] = [
         Self::Once,
         Self::Daily,
         Self::Weekly,
@@ -41,45 +170,349 @@ impl LoopFrequency {
         Self::Thursday,
         Self::Friday,
         Self::Saturday,
+        Self::Custom,
     ];
 }
 #[derive(Serialize, Deserialize, Clone)]
 struct Note {
     id: usize,
+    #[serde(serialize_with = "rounded::pos2")]
     position: Pos2,
     text: String,
+    #[serde(serialize_with = "rounded::vec2")]
     size: Vec2,
+    /// Path of a picture shown above the text.
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(flatten)]
+    meta: ItemMeta,
+    #[serde(skip)]
+    checklist: ChecklistCache,
+    #[serde(skip)]
+    search_cache: SearchCache,
+    /// Showing the raw text editor instead of the rendered Markdown.
+    #[serde(skip)]
+    editing: bool,
 }
 #[derive(Serialize, Deserialize, Clone)]
 struct Todo {
     id: usize,
+    #[serde(serialize_with = "rounded::pos2")]
     position: Pos2,
     text: String,
     due: Option<DateTime<Local>>,
     is_done: bool,
+    #[serde(serialize_with = "rounded::vec2")]
     size: Vec2,
     #[serde(default)]
     loop_freq: LoopFrequency,
     #[serde(default)]
     notified: bool,
+    #[serde(default)]
+    done_at: Option<DateTime<Local>>,
+    /// A note holding the long-form description of this todo.
+    #[serde(default)]
+    details_note: Option<usize>,
+    /// Another todo that has to be done first.
+    #[serde(default)]
+    waiting_on: Option<usize>,
+    /// Name of the lane the todo was last dropped into.
+    #[serde(default)]
+    status: Option<String>,
+    /// Every time the todo was ticked off, oldest first.
+    #[serde(default)]
+    history: Vec<DateTime<Local>>,
+    /// Ticking the last one off completes the todo.
+    #[serde(default)]
+    subtasks: Vec<SubTask>,
+    #[serde(default)]
+    priority: Priority,
+    /// Minutes before `due` to send an early reminder. `None` uses the board
+    /// default and `Some(0)` turns it off.
+    #[serde(default)]
+    remind_before: Option<u32>,
+    /// The due time the early reminder went out for, so every occurrence of
+    /// a repeat gets its own.
+    #[serde(default)]
+    early_notified: Option<DateTime<Local>>,
+    /// When a snoozed reminder comes back. Overdue one-off todos are snoozed
+    /// by moving `due` instead; that would shift a repeat's whole series, or
+    /// pull an early reminder's due time in.
+    #[serde(default)]
+    snoozed_until: Option<DateTime<Local>>,
+    /// Day of the month Monthly/Yearly repeats aim for, remembered while a
+    /// short month has clamped `due` below it.
+    #[serde(default)]
+    repeat_day: Option<u32>,
+    /// Time of day repeats aim for, remembered while a spring-forward gap
+    /// has pushed `due` past it.
+    #[serde(default)]
+    repeat_time: Option<NaiveTime>,
+    /// The rule used when `loop_freq` is `Custom`.
+    #[serde(default)]
+    recurrence: Recurrence,
+    /// When each completed focus session on this todo ended.
+    #[serde(default)]
+    pomodoros: Vec<DateTime<Local>>,
+    /// Tracked work on the todo, oldest first.
+    #[serde(default)]
+    time_log: Vec<TimeSpan>,
+    #[serde(flatten)]
+    meta: ItemMeta,
+    #[serde(skip)]
+    search_cache: SearchCache,
+}
+/// Fields shared by notes and todos. Flattened, so they sit next to the
+/// item's own fields in the saved JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+struct ItemMeta {
+    list_pinned: bool,
+    starred: bool,
+    /// Window transparency, kept within `MIN_OPACITY..=1.0`.
+    opacity: f32,
+    /// Mirrors egui's collapse state for the item window, which egui itself
+    /// only keeps in memory.
+    collapsed: bool,
+    /// Lowercase, without the leading `#`; see [`tags::normalize_tag`].
+    tags: Vec<String>,
+    /// Tints the window, to tell clusters of related items apart.
+    color: Option<ItemColor>,
 }
-impl Default for LoopFrequency {
+impl Default for ItemMeta {
     fn default() -> Self {
-        LoopFrequency::Once
+        Self {
+            list_pinned: false,
+            starred: false,
+            opacity: 1.0,
+            collapsed: false,
+            tags: Vec::new(),
+            color: None,
+        }
+    }
+}
+
+fn note_window_id(id: usize) -> Id {
+    Id::new("note").with(id)
+}
+fn todo_window_id(id: usize) -> Id {
+    Id::new("todo").with(id)
+}
+
+/// Whether egui has the window collapsed. egui stores this in its memory
+/// under the window id plus `"collapsing"`; only this function and
+/// [`set_window_collapsed`] know that, so an egui upgrade that moves it only
+/// needs changing here.
+fn window_collapsed(ctx: &Context, window_id: Id) -> bool {
+    egui::collapsing_header::CollapsingState::load(ctx, window_id.with("collapsing"))
+        .is_some_and(|s| !s.is_open())
+}
+fn set_window_collapsed(ctx: &Context, window_id: Id, collapsed: bool) {
+    let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+        ctx,
+        window_id.with("collapsing"),
+        true,
+    );
+    state.set_open(!collapsed);
+    state.store(ctx);
+}
+
+/// Items never fade out entirely, so they can't get lost on the canvas.
+const MIN_OPACITY: f32 = 0.3;
+/// Screen pixels kept clear around items by "Fit all" and "Fit selection".
+const FIT_MARGIN: f32 = 40.0;
+/// How far copies made by "Duplicate" land from their originals.
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(24.0, 24.0);
+
+impl Note {
+    fn new(id: usize, position: Pos2) -> Self {
+        Self {
+            id,
+            position,
+            text: "New note".to_string(),
+            size: Vec2::new(200.0, 100.0),
+            image: None,
+            meta: ItemMeta::default(),
+            checklist: ChecklistCache::default(),
+            search_cache: SearchCache::default(),
+            editing: true,
+        }
+    }
+}
+impl Todo {
+    fn new(id: usize, position: Pos2) -> Self {
+        Self {
+            id,
+            position,
+            text: "New todo".to_string(),
+            due: None,
+            is_done: false,
+            size: Vec2::new(200.0, 150.0),
+            loop_freq: LoopFrequency::Once,
+            notified: false,
+            done_at: None,
+            details_note: None,
+            waiting_on: None,
+            status: None,
+            history: Vec::new(),
+            subtasks: Vec::new(),
+            priority: Priority::default(),
+            remind_before: None,
+            early_notified: None,
+            snoozed_until: None,
+            repeat_day: None,
+            repeat_time: None,
+            recurrence: Recurrence::default(),
+            pomodoros: Vec::new(),
+            time_log: Vec::new(),
+            meta: ItemMeta::default(),
+            search_cache: SearchCache::default(),
+        }
+    }
+    /// Whether the todo repeats by calendar month, so `repeat_day` applies.
+    fn by_month(&self) -> bool {
+        match self.loop_freq {
+            LoopFrequency::Monthly | LoopFrequency::Yearly => true,
+            LoopFrequency::Custom => {
+                self.recurrence.month_day == MonthDay::SameDay
+                    && matches!(self.recurrence.unit, Unit::Months | Unit::Years)
+            }
+            _ => false,
+        }
+    }
+    /// The first occurrence after `now`, counting from `from`, or `None` once
+    /// the repeat is over (or for one-off todos).
+    fn next_occurrence(
+        &mut self,
+        from: DateTime<Local>,
+        zone: BoardZone,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        self.repeat_day = self
+            .by_month()
+            .then(|| anchored_day(self.repeat_day, zone.wall_clock(from).date()));
+        self.repeat_time = Some(anchored_wall(self.repeat_time, from, zone).time());
+        let (day, time) = (self.repeat_day, self.repeat_time);
+        match self.loop_freq {
+            LoopFrequency::Custom => self.recurrence.advance(from, zone, now, day, time),
+            freq => get_next_due(&from, freq, zone, now, day, time),
+        }
+    }
+    /// Unticks a completed repeating todo and moves it to its next
+    /// occurrence, so that one gets its own reminders. The completion stays
+    /// in the history. Returns false, leaving the todo done, if there is no
+    /// next occurrence.
+    fn start_next_cycle(&mut self, zone: BoardZone, now: DateTime<Local>) -> bool {
+        let Some(due) = self.due.filter(|_| self.is_done) else {
+            return false;
+        };
+        let Some(next) = self.next_occurrence(due, zone, now) else {
+            return false;
+        };
+        self.due = Some(next);
+        self.is_done = false;
+        self.done_at = None;
+        self.rearm_reminders();
+        true
+    }
+    /// Forgets the reminders sent or snoozed for the old due time, after it
+    /// moved, so the new one gets its own.
+    fn rearm_reminders(&mut self) {
+        self.notified = false;
+        self.early_notified = None;
+        self.snoozed_until = None;
+    }
+    /// Marks the todo done or not done, recording when it was completed.
+    /// Unticking takes the completion back out of the history.
+    fn set_done(&mut self, done: bool, now: DateTime<Local>) {
+        if !done {
+            if let Some(at) = self.done_at.take() {
+                if let Some(i) = self.history.iter().rposition(|&h| h == at) {
+                    self.history.remove(i);
+                }
+            }
+        } else if !self.is_done {
+            self.done_at = Some(now);
+            self.history.push(now);
+            self.stop_tracking(now);
+        }
+        self.is_done = done;
     }
 }
 
-#[derive(Serialize, Deserialize, Default)]
+/// The whole board plus the session's view state. Only what
+/// [`AppState::to_persisted`] copies out is saved.
+#[derive(Default)]
 struct AppState {
     notes: Vec<Note>,
     todos: Vec<Todo>,
-    connections: Vec<(usize, usize)>,
+    connections: Vec<Connection>,
     offset: Vec2,
     zoom: f32,
     next_id: usize,
     background_image_path: Option<String>,
-    #[serde(skip)]
+    archived: Vec<Todo>,
+    /// Deleted items, oldest deletion first, until restored or purged.
+    trash: Vec<Trashed>,
+    /// IANA name of the zone due dates are shown and repeated in; `None`
+    /// follows the system zone.
+    timezone: Option<String>,
+    /// App-wide, so carried over when another board opens; saved to
+    /// [`settings::settings_file`] rather than the board.
+    settings: Settings,
+    board_settings: BoardSettings,
+    lock: Option<BoardLock>,
+    bookmarks: Vec<ViewBookmark>,
+    lanes: Vec<Lane>,
     connecting_from_id: Option<usize>,
+    selected: HashSet<usize>,
+    shift_custom_hours: i64,
+    settings_open: bool,
+    filter: ItemFilter,
+    search: Search,
+    /// Connections made this session, newest last, for "Undo last connection".
+    added_connections: Vec<Connection>,
+    /// Item whose text field should grab keyboard focus on the next frame.
+    focus_item: Option<usize>,
+    /// Item shown enlarged in focus mode. Pan and zoom are off meanwhile.
+    focus: Option<usize>,
+    /// The focus session running, if any; only one at a time.
+    pomodoro: Option<Pomodoro>,
+    rewiring: Option<Rewire>,
+    unlocked: bool,
+    lock_input: LockInput,
+    /// Action whose shortcut is being captured in the settings window.
+    rebinding: Option<HotkeyAction>,
+    /// The theme file is to be read again, as it may have been edited.
+    reload_theme: bool,
+    /// The command palette's query, while it is open.
+    palette: Option<String>,
+    bookmarks_open: bool,
+    archive_open: bool,
+    trash_open: bool,
+    archive_search: String,
+    bookmark_input: BookmarkInput,
+    /// Offset and zoom the view is gliding towards.
+    view_goal: Option<(Vec2, f32)>,
+    /// Stand-in for the current time in due highlights, for planning ahead.
+    /// Reminders and timestamps keep using the real clock.
+    simulated_now: Option<DateTime<Local>>,
+    history: History,
+    /// Item picked from the search results and when, for a short highlight.
+    flash: Option<(usize, f64)>,
+    /// Groups awaiting review in the duplicates window, open while `Some`.
+    duplicates: Option<Vec<DuplicateGroup>>,
+    /// The "Export image" window, while open.
+    image_export: Option<ImageExport>,
+    /// Ends of the connection under the pointer when the canvas menu was
+    /// opened; the menu then offers to edit or delete it instead of the
+    /// canvas actions. Held by ends rather than index, as undo or an unlink
+    /// can reorder the list while the menu is open.
+    connection_menu: Option<(usize, usize)>,
+    /// Ends of the connection shown in the editor window.
+    editing_connection: Option<(usize, usize)>,
+    /// Blocked todo the user ticked, awaiting confirmation.
+    confirm_early_done: Option<usize>,
 }
 
 impl AppState {
@@ -95,12 +528,566 @@ impl AppState {
                     .map(|t| t.position + t.size / 2.0)
             })
     }
+    /// Files on disk the board shows, for change watching.
+    fn linked_files(&self) -> HashSet<PathBuf> {
+        self.background_image_path
+            .iter()
+            .chain(self.notes.iter().filter_map(|n| n.image.as_ref()))
+            .map(PathBuf::from)
+            .collect()
+    }
+    /// A password-locked board refuses edits until unlocked this session.
+    fn read_only(&self) -> bool {
+        self.lock.is_some() && !self.unlocked
+    }
+    fn zone(&self) -> BoardZone {
+        BoardZone::parse(self.timezone.as_deref())
+    }
+    fn item_ids(&self) -> HashSet<usize> {
+        self.notes
+            .iter()
+            .map(|n| n.id)
+            .chain(self.todos.iter().map(|t| t.id))
+            .collect()
+    }
+    /// Connections per item, counting only links whose both ends are on the
+    /// canvas (archived todos keep their links for when they come back).
+    fn connection_counts(&self) -> HashMap<usize, usize> {
+        let ids = self.item_ids();
+        let mut counts = HashMap::new();
+        for conn in &self.connections {
+            if ids.contains(&conn.from) && ids.contains(&conn.to) {
+                *counts.entry(conn.from).or_insert(0) += 1;
+                *counts.entry(conn.to).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+    /// Moves todos that have been done for longer than `days` into the
+    /// archive. Returns how many were moved; `days == 0` disables this.
+    fn archive_done_todos(&mut self, now: DateTime<Local>, days: u32) -> usize {
+        if days == 0 {
+            return 0;
+        }
+        let cutoff = now - chrono::Duration::days(days as i64);
+        let (expired, kept): (Vec<Todo>, Vec<Todo>) = std::mem::take(&mut self.todos)
+            .into_iter()
+            .partition(|t| t.is_done && t.done_at.is_some_and(|at| at < cutoff));
+        self.todos = kept;
+        let moved = expired.len();
+        self.archived.extend(expired);
+        moved
+    }
+    /// Clears references to items that no longer exist and drops connections
+    /// with a missing end.
+    fn prune_dangling_refs(&mut self) {
+        let note_ids: HashSet<usize> = self.notes.iter().map(|n| n.id).collect();
+        // Archived todos are done, so waiting on one is still meaningful.
+        let todo_ids: HashSet<usize> = self
+            .todos
+            .iter()
+            .chain(&self.archived)
+            .map(|t| t.id)
+            .collect();
+        // Links to archived todos are kept, for when they come back.
+        self.connections.retain(|c| {
+            [c.from, c.to]
+                .iter()
+                .all(|id| note_ids.contains(id) || todo_ids.contains(id))
+        });
+        for todo in self.todos.iter_mut() {
+            if todo.details_note.is_some_and(|id| !note_ids.contains(&id)) {
+                todo.details_note = None;
+            }
+            if todo.waiting_on.is_some_and(|id| !todo_ids.contains(&id)) {
+                todo.waiting_on = None;
+            }
+        }
+    }
+    /// Connections with exactly one end in `ids`, which moving those items
+    /// elsewhere would drop.
+    fn links_leaving(&self, ids: &HashSet<usize>) -> usize {
+        self.connections
+            .iter()
+            .filter(|c| ids.contains(&c.from) != ids.contains(&c.to))
+            .count()
+    }
+    /// Removes the items in `ids` and every connection touching them. The
+    /// items and the connections among them are returned as a board.
+    fn take_items(&mut self, ids: &HashSet<usize>) -> AppState {
+        let (notes, kept) = std::mem::take(&mut self.notes)
+            .into_iter()
+            .partition(|n| ids.contains(&n.id));
+        self.notes = kept;
+        let (todos, kept) = std::mem::take(&mut self.todos)
+            .into_iter()
+            .partition(|t| ids.contains(&t.id));
+        self.todos = kept;
+        let (connections, kept) = std::mem::take(&mut self.connections)
+            .into_iter()
+            .filter(|c| ids.contains(&c.from) == ids.contains(&c.to))
+            .partition(|c| ids.contains(&c.from));
+        self.connections = kept;
+        self.selected.retain(|id| !ids.contains(id));
+        self.prune_dangling_refs();
+        AppState {
+            notes,
+            todos,
+            connections,
+            ..Default::default()
+        }
+    }
+    /// Renames item ids by `map` everywhere an id is stored: the items
+    /// themselves, connections (trashed ones too), todo references,
+    /// bookmarks, the pending link, the selection and the other session
+    /// state naming an item. Ids missing from `map` stay as they are.
+    fn remap_ids(&mut self, map: &HashMap<usize, usize>) {
+        let remap = |id: usize| map.get(&id).copied().unwrap_or(id);
+        for note in self.notes.iter_mut() {
+            note.id = remap(note.id);
+        }
+        for todo in self.todos.iter_mut().chain(self.archived.iter_mut()) {
+            todo.id = remap(todo.id);
+            todo.details_note = todo.details_note.map(remap);
+            todo.waiting_on = todo.waiting_on.map(remap);
+        }
+        for conn in self
+            .connections
+            .iter_mut()
+            .chain(self.added_connections.iter_mut())
+            .chain(self.trash.iter_mut().flat_map(|t| t.connections.iter_mut()))
+        {
+            conn.from = remap(conn.from);
+            conn.to = remap(conn.to);
+        }
+        for bookmark in self.bookmarks.iter_mut() {
+            bookmark.anchor = bookmark.anchor.map(remap);
+        }
+        self.connecting_from_id = self.connecting_from_id.map(remap);
+        self.focus_item = self.focus_item.map(remap);
+        self.focus = self.focus.map(remap);
+        self.confirm_early_done = self.confirm_early_done.map(remap);
+        let remap_ends = |(from, to)| (remap(from), remap(to));
+        self.connection_menu = self.connection_menu.map(remap_ends);
+        self.editing_connection = self.editing_connection.map(remap_ends);
+        if let Some((id, _)) = self.flash.as_mut() {
+            *id = remap(*id);
+        }
+        if let Some(session) = self.pomodoro.as_mut() {
+            session.todo = remap(session.todo);
+        }
+        self.selected = self.selected.iter().copied().map(remap).collect();
+    }
+    /// Folds the items in `others` into `survivor`: their connections and the
+    /// references to them move over, then they are removed. Links that would
+    /// end up looping or doubled are dropped.
+    fn merge_into(&mut self, survivor: usize, others: &HashSet<usize>) {
+        self.notes.retain(|n| !others.contains(&n.id));
+        self.todos.retain(|t| !others.contains(&t.id));
+        let map: HashMap<usize, usize> = others.iter().map(|&id| (id, survivor)).collect();
+        self.remap_ids(&map);
+        let mut seen = HashSet::new();
+        self.connections
+            .retain(|c| c.from != c.to && seen.insert((c.from, c.to)));
+        for todo in self.todos.iter_mut().chain(self.archived.iter_mut()) {
+            todo.waiting_on = todo.waiting_on.filter(|&id| id != todo.id);
+        }
+    }
+    /// Moves the items in `ids` to the trash, with every connection touching
+    /// them. References to them from other items are cleared.
+    fn delete_items(&mut self, ids: &HashSet<usize>, now: DateTime<Local>) {
+        let connections = self
+            .connections
+            .iter()
+            .filter(|c| ids.contains(&c.from) || ids.contains(&c.to))
+            .cloned()
+            .collect();
+        let taken = self.take_items(ids);
+        if !taken.notes.is_empty() || !taken.todos.is_empty() {
+            self.trash.push(Trashed {
+                deleted_at: now,
+                notes: taken.notes,
+                todos: taken.todos,
+                connections,
+            });
+        }
+        if self.connecting_from_id.is_some_and(|id| ids.contains(&id)) {
+            self.connecting_from_id = None;
+        }
+        for bookmark in self.bookmarks.iter_mut() {
+            bookmark.anchor = bookmark.anchor.filter(|id| !ids.contains(id));
+        }
+    }
+    /// Copies the items in `ids`, and the connections among them, a little
+    /// down and to the right under fresh ids. Copied todos start undone with
+    /// no tracked time. References to items left behind are dropped. Returns
+    /// the copies' ids.
+    fn duplicate_items(&mut self, ids: &HashSet<usize>) -> HashSet<usize> {
+        let mut copy = AppState {
+            notes: self
+                .notes
+                .iter()
+                .filter(|n| ids.contains(&n.id))
+                .cloned()
+                .collect(),
+            todos: self
+                .todos
+                .iter()
+                .filter(|t| ids.contains(&t.id))
+                .cloned()
+                .collect(),
+            connections: self
+                .connections
+                .iter()
+                .filter(|c| ids.contains(&c.from) && ids.contains(&c.to))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        for note in copy.notes.iter_mut() {
+            note.position += DUPLICATE_OFFSET;
+        }
+        for todo in copy.todos.iter_mut() {
+            todo.position += DUPLICATE_OFFSET;
+            todo.is_done = false;
+            todo.done_at = None;
+            todo.rearm_reminders();
+            todo.time_log.clear();
+            todo.pomodoros.clear();
+        }
+        let first = self.next_id;
+        self.absorb(copy);
+        (first..self.next_id).collect()
+    }
+    /// Adds another board's items and connections under fresh ids.
+    fn absorb(&mut self, mut other: AppState) {
+        // Links and references leaving `other` would otherwise keep ids that
+        // mean something else here.
+        let own = other.item_ids();
+        other
+            .connections
+            .retain(|c| own.contains(&c.from) && own.contains(&c.to));
+        other.prune_dangling_refs();
+        let mut ids = HashMap::new();
+        for id in own {
+            ids.insert(id, self.next_id);
+            self.next_id += 1;
+        }
+        other.remap_ids(&ids);
+        self.notes.extend(other.notes);
+        self.todos.extend(other.todos);
+        self.connections.extend(other.connections);
+    }
+    /// Handles todo `id` being completed: todos that were waiting on it, by
+    /// `waiting_on` or a dependency link, and have nothing else holding them
+    /// up become unblocked. Returns their texts for the "unblocked"
+    /// notification.
+    fn unblock_waiting_on(&mut self, id: usize, now: DateTime<Local>) -> Vec<String> {
+        let set_due = self.settings.unblocked_due_now;
+        let still_blocked = blocked_ids(&self.todos, &self.connections);
+        let connections = &self.connections;
+        let waited_on = |t: &Todo| {
+            t.waiting_on == Some(id)
+                || connections
+                    .iter()
+                    .any(|c| c.dependency && c.from == id && c.to == t.id)
+        };
+        self.todos
+            .iter_mut()
+            .filter(|t| !t.is_done && waited_on(t) && !still_blocked.contains(&t.id))
+            .map(|t| {
+                if set_due {
+                    t.due = Some(now);
+                    // The unblocked notification stands in for the due one.
+                    t.notified = t.loop_freq == LoopFrequency::Once;
+                }
+                t.text.clone()
+            })
+            .collect()
+    }
+    /// Carries out a button pressed on todo `id`'s reminder. Returns the
+    /// texts of todos that completing it unblocked.
+    fn reminder_action(
+        &mut self,
+        id: usize,
+        action: ReminderAction,
+        now: DateTime<Local>,
+    ) -> Vec<String> {
+        let Some(todo) = self.todos.iter_mut().find(|t| t.id == id) else {
+            return Vec::new();
+        };
+        match action {
+            ReminderAction::Done if !todo.is_done => {
+                todo.set_done(true, now);
+                move_to_done_lane(todo, &self.lanes);
+                return self.unblock_waiting_on(id, now);
+            }
+            ReminderAction::Done => {}
+            ReminderAction::Snooze => {
+                let until = now + chrono::Duration::minutes(SNOOZE_MINUTES);
+                if todo.loop_freq == LoopFrequency::Once && todo.due.is_some_and(|d| d <= now) {
+                    todo.due = Some(until);
+                    todo.rearm_reminders();
+                } else {
+                    todo.snoozed_until = Some(until);
+                }
+            }
+        }
+        Vec::new()
+    }
+    /// Pans the view so the item sits in the middle of `screen`.
+    fn center_on(&mut self, id: usize, screen: Rect) {
+        if let Some(pos) = self.get_item_pos(id) {
+            let offset = screen.center().to_vec2() - pos.to_vec2() * self.zoom;
+            self.view_goal = Some((offset, self.zoom));
+        }
+    }
+    /// Glides to where the items in `ids` (every item for `None`) fill
+    /// `screen`, less a margin. Nothing happens if there are none.
+    fn fit_view(&mut self, ids: Option<&HashSet<usize>>, screen: Rect) {
+        let notes = self.notes.iter().map(|n| (n.id, n.position, n.size));
+        let todos = self.todos.iter().map(|t| (t.id, t.position, t.size));
+        let Some(bounds) = notes
+            .chain(todos)
+            .filter(|(id, _, _)| ids.is_none_or(|ids| ids.contains(id)))
+            .map(|(_, pos, size)| Rect::from_min_size(pos, size))
+            .reduce(Rect::union)
+        else {
+            return;
+        };
+        let room = screen.shrink(FIT_MARGIN).size().max(Vec2::splat(1.0));
+        let zoom = (room / bounds.size().max(Vec2::splat(1.0)))
+            .min_elem()
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+        let offset = screen.center().to_vec2() - bounds.center().to_vec2() * zoom;
+        self.view_goal = Some((offset, zoom));
+    }
+    /// Glides back to 100% zoom, keeping the middle of `screen` in place.
+    fn reset_zoom(&mut self, screen: Rect) {
+        let center = screen.center().to_vec2();
+        let offset = center - (center - self.offset) / self.zoom;
+        self.view_goal = Some((offset, 1.0));
+    }
+    /// Glides to item `id` and makes it flash briefly, so it is easy to spot.
+    fn jump_to(&mut self, id: usize, screen: Rect, time: f64) {
+        self.center_on(id, screen);
+        self.flash = Some((id, time));
+    }
+
+    /// Moves the view a step towards `view_goal`. Returns whether it is still
+    /// on its way.
+    fn animate_view(&mut self, dt: f32) -> bool {
+        let Some((offset, zoom)) = self.view_goal else {
+            return false;
+        };
+        let t = 1.0 - (-12.0 * dt).exp();
+        self.offset += (offset - self.offset) * t;
+        self.zoom += (zoom - self.zoom) * t;
+        if (offset - self.offset).length() < 0.5 && (zoom - self.zoom).abs() < 0.001 {
+            self.offset = offset;
+            self.zoom = zoom;
+            self.view_goal = None;
+        }
+        true
+    }
+    fn get_item_rect(&self, id: usize) -> Option<Rect> {
+        self.notes
+            .iter()
+            .find(|n| n.id == id)
+            .map(|n| Rect::from_min_size(n.position, n.size))
+            .or_else(|| {
+                self.todos
+                    .iter()
+                    .find(|t| t.id == id)
+                    .map(|t| Rect::from_min_size(t.position, t.size))
+            })
+    }
+    /// De-overlaps all items. Returns how many of them moved.
+    fn tidy_overlaps(&mut self) -> usize {
+        let mut rects: Vec<Rect> = self
+            .notes
+            .iter()
+            .map(|n| Rect::from_min_size(n.position, n.size))
+            .chain(
+                self.todos
+                    .iter()
+                    .map(|t| Rect::from_min_size(t.position, t.size)),
+            )
+            .collect();
+        let moved = spread_overlaps(&mut rects, 10.0, 50);
+        let positions = self
+            .notes
+            .iter_mut()
+            .map(|n| &mut n.position)
+            .chain(self.todos.iter_mut().map(|t| &mut t.position));
+        for (pos, rect) in positions.zip(&rects) {
+            *pos = rect.min;
+        }
+        moved.iter().filter(|&&m| m).count()
+    }
+    fn add_note(&mut self, pos: Pos2) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut note = Note::new(id, pos);
+        note.size = self.settings.note_size;
+        self.notes.push(note);
+        id
+    }
+    /// Adds a todo with the size, repeat and due defaults from the settings.
+    fn add_todo(&mut self, pos: Pos2, now: DateTime<Local>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut todo = Todo::new(id, pos);
+        todo.size = self.settings.todo_size;
+        todo.loop_freq = self.settings.new_todo_freq;
+        todo.due = match self.settings.new_todo_due {
+            NewTodoDue::None => None,
+            NewTodoDue::InMinutes(m) => Some(now + chrono::Duration::minutes(m as i64)),
+            NewTodoDue::AtHour(hour) => {
+                let zone = self.zone();
+                let today = zone.wall_clock(now).date().and_hms_opt(hour, 0, 0);
+                match today.map(|t| zone.instant_at(t)) {
+                    Some(due) if due > now => Some(due),
+                    _ => Some(tomorrow_at(now, zone, hour)),
+                }
+            }
+        };
+        self.todos.push(todo);
+        id
+    }
+    /// Links two items unless the same link already exists.
+    fn add_connection(&mut self, from: usize, to: usize) {
+        if self
+            .connections
+            .iter()
+            .any(|c| c.from == from && c.to == to)
+        {
+            return;
+        }
+        let conn = Connection::new(from, to);
+        self.added_connections.push(conn.clone());
+        self.connections.push(conn);
+    }
+    /// Completes a link-button click from `from` onto `to`, honoring the
+    /// configured [`LinkClickMode`].
+    fn link_clicked(&mut self, from: usize, to: usize) {
+        let linked =
+            |c: &Connection| (c.from == from && c.to == to) || (c.from == to && c.to == from);
+        if self.settings.link_click_mode == LinkClickMode::Toggle
+            && self.connections.iter().any(linked)
+        {
+            self.connections.retain(|c| !linked(c));
+        } else {
+            self.add_connection(from, to);
+        }
+    }
+    /// Moves one end of connection `index` onto `target`. Landing on a link
+    /// that already exists merges the two; looping back onto the other end
+    /// is ignored.
+    fn rewire_connection(&mut self, index: usize, end: ConnectionEnd, target: usize) {
+        let Some(conn) = self.connections.get(index) else {
+            return;
+        };
+        let (from, to) = match end {
+            ConnectionEnd::From => (target, conn.to),
+            ConnectionEnd::To => (conn.from, target),
+        };
+        if from == to {
+            return;
+        }
+        if self
+            .connections
+            .iter()
+            .enumerate()
+            .any(|(i, c)| i != index && c.from == from && c.to == to)
+        {
+            self.connections.remove(index);
+            return;
+        }
+        let conn = &mut self.connections[index];
+        match end {
+            ConnectionEnd::From => {
+                conn.from = target;
+                conn.from_side = None;
+            }
+            ConnectionEnd::To => {
+                conn.to = target;
+                conn.to_side = None;
+            }
+        }
+    }
+    /// Topmost visible item under world position `pos`.
+    fn item_at(&self, pos: Pos2, hidden: &HashSet<usize>) -> Option<usize> {
+        let rects = self
+            .notes
+            .iter()
+            .map(|n| (n.id, Rect::from_min_size(n.position, n.size)))
+            .chain(
+                self.todos
+                    .iter()
+                    .map(|t| (t.id, Rect::from_min_size(t.position, t.size))),
+            );
+        rects
+            .rev()
+            .find(|(id, rect)| !hidden.contains(id) && rect.contains(pos))
+            .map(|(id, _)| id)
+    }
+    /// Removes the most recently added connection that still exists.
+    fn undo_last_connection(&mut self) -> bool {
+        while let Some(conn) = self.added_connections.pop() {
+            if let Some(i) = self.connections.iter().rposition(|c| *c == conn) {
+                self.connections.remove(i);
+                return true;
+            }
+        }
+        false
+    }
+    fn connection_anchors(&self, conn: &Connection) -> Option<(Anchor, Anchor)> {
+        let from = self.get_item_rect(conn.from)?;
+        let to = self.get_item_rect(conn.to)?;
+        let start = Anchor::on(from, conn.from_side, to.center());
+        let end = Anchor::on(to, conn.to_side, start.pos);
+        Some((start, end))
+    }
 }
 
 struct EndlessCanvasApp {
     state: Arc<Mutex<AppState>>,
+    /// The main board's file. Other boards sit next to it.
     storage_path: Option<PathBuf>,
-    background_texture: Option<TextureHandle>,
+    /// The open board; `None` is the main one.
+    current_board: Option<String>,
+    /// Shared with the reminder thread, which checks the other boards' files.
+    open_board: OpenBoard,
+    board_names: Vec<String>,
+    new_board_name: String,
+    background: Option<Background>,
+    /// The theme file read and what came of it; `None` inside when it
+    /// couldn't be read, so that is reported once rather than every frame.
+    custom_theme: Option<(String, Option<CustomTheme>)>,
+    toasts: Toasts,
+    /// `ctx.input().time` of the last periodic auto-archive pass.
+    last_archive_pass: f64,
+    file_watch: FileWatch,
+    /// False when the data folder can't be written; the session then lives
+    /// in memory until the user picks another folder.
+    storage_writable: bool,
+    old_data_files: Vec<PathBuf>,
+    /// A move of the selection to another board waiting on confirmation,
+    /// with the number of connections it would drop.
+    pending_board_move: Option<(PathBuf, usize)>,
+    images: ImageCache,
+    tray: Option<Tray>,
+    global_hotkey: Option<GlobalHotkey>,
+    /// The shortcut last asked of [`GlobalHotkey::register`], so a failure
+    /// is reported once rather than retried every frame.
+    global_hotkey_wanted: Option<KeyboardShortcut>,
+    /// The quick capture window, while it is open.
+    quick_capture: Option<QuickCapture>,
+    /// Hidden to the tray; the reminder thread carries on meanwhile.
+    window_hidden: bool,
+    /// Quit was picked in the tray, so closing must not hide the window.
+    quitting: bool,
 }
 
 fn load_image_from_path(path: &Path) -> Result<ColorImage, image::ImageError> {
@@ -111,299 +1098,2404 @@ fn load_image_from_path(path: &Path) -> Result<ColorImage, image::ImageError> {
     Ok(ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()))
 }
 
-fn get_next_due(current_due: &DateTime<Local>, freq: LoopFrequency) -> DateTime<Local> {
-    let now = Local::now();
-    let mut next_due = *current_due;
-    while next_due <= now {
-        next_due = match freq {
-            LoopFrequency::Once => next_due,
-            LoopFrequency::Daily => next_due + chrono::Duration::days(1),
-            LoopFrequency::Weekly => next_due + chrono::Duration::weeks(1),
-            LoopFrequency::Monthly => next_due + chrono::Duration::days(30),
-            LoopFrequency::Yearly => next_due + chrono::Duration::days(365),
-            LoopFrequency::Sunday
-            | LoopFrequency::Monday
-            | LoopFrequency::Tuesday
-            | LoopFrequency::Wednesday
-            | LoopFrequency::Thursday
-            | LoopFrequency::Friday
-            | LoopFrequency::Saturday => {
-                let target_weekday = match freq {
-                    LoopFrequency::Sunday => Weekday::Sun,
-                    LoopFrequency::Monday => Weekday::Mon,
-                    LoopFrequency::Tuesday => Weekday::Tue,
-                    LoopFrequency::Wednesday => Weekday::Wed,
-                    LoopFrequency::Thursday => Weekday::Thu,
-                    LoopFrequency::Friday => Weekday::Fri,
-                    _ => Weekday::Sat,
-                };
-                let mut next = next_due + chrono::Duration::days(1);
-                while next.weekday() != target_weekday {
-                    next = next + chrono::Duration::days(1);
-                }
-                next
-            }
-        };
+const ARCHIVE_PASS_SECONDS: f64 = 60.0;
+const FLASH_SECONDS: f64 = 1.5;
+/// How close, in screen points, the pointer has to be to pick a connection.
+const CONNECTION_HIT_RADIUS: f32 = 6.0;
+
+fn report_archived(toasts: &mut Toasts, count: usize) {
+    if count > 0 {
+        toasts.info(format!("Archived {} completed todo(s)", count));
     }
-    next_due
 }
-fn generate_title(text: &str) -> String {
-    let title = text
-        .split_whitespace()
-        .take(4)
-        .collect::<Vec<_>>()
-        .join(" ");
-    if title.is_empty() {
-        " ".to_string()
-    } else {
-        title
+
+/// Hands the saved collapse state of every item window back to egui.
+fn restore_window_state(ctx: &Context, state: &AppState) {
+    for note in &state.notes {
+        set_window_collapsed(ctx, note_window_id(note.id), note.meta.collapsed);
+    }
+    for todo in &state.todos {
+        set_window_collapsed(ctx, todo_window_id(todo.id), todo.meta.collapsed);
     }
 }
-#[derive(Clone, Copy)]
-struct Transformer {
-    offset: Vec2,
-    zoom: f32,
+
+struct Background {
+    texture: TextureHandle,
+    /// Average brightness, for picking readable line colors on top of it.
+    luminance: f32,
+}
+
+/// Asks for a background image and loads it, returning its path too.
+fn pick_background(ctx: &Context) -> Option<(String, Background)> {
+    let path = FileDialog::new()
+        .add_filter("Image", &["png", "jpg", "jpeg"])
+        .pick_file()?;
+    let background = load_background(ctx, &path)?;
+    Some((path.display().to_string(), background))
+}
+
+fn pick_bundle_dest(file_name: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .add_filter("Bundle", &["zip"])
+        .set_file_name(file_name)
+        .save_file()
+}
+
+fn load_background(ctx: &Context, path: &Path) -> Option<Background> {
+    let image = load_image_from_path(path).ok()?;
+    let luminance = image_luminance(&image);
+    Some(Background {
+        texture: ctx.load_texture("background", image, Default::default()),
+        luminance,
+    })
 }
-impl Transformer {
-    fn to_screen(&self, pos: Pos2) -> Pos2 {
-        pos * self.zoom + self.offset
+
+/// Copies the state file and any backups next to it (files sharing its
+/// stem, e.g. `app_state.json.bak`) into `dir`. Returns the copied sources.
+fn copy_data_files(from: &Path, dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut copied = Vec::new();
+    let (Some(parent), Some(stem)) = (from.parent(), from.file_stem().and_then(|s| s.to_str()))
+    else {
+        return Ok(copied);
+    };
+    if !parent.is_dir() {
+        return Ok(copied);
     }
-    fn from_screen(&self, pos: Pos2) -> Pos2 {
-        (pos - self.offset) / self.zoom
+    for entry in std::fs::read_dir(parent)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        if path.is_file() && name.to_string_lossy().starts_with(stem) {
+            std::fs::copy(&path, dir.join(name))?;
+            copied.push(path);
+        }
     }
+    Ok(copied)
 }
 
-impl EndlessCanvasApp {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let storage_path = Self::get_storage_path();
-        let state = Self::from_disk(storage_path.as_deref()).unwrap_or_default();
-
-        let mut background_texture = None;
-        if let Some(path_str) = &state.background_image_path {
-            if let Ok(image) = load_image_from_path(Path::new(path_str)) {
-                background_texture = Some(cc.egui_ctx.load_texture(
-                    "background",
-                    image,
-                    Default::default(),
-                ));
-            }
-        }
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    next.and_then(|d| d.pred_opt()).map_or(31, |d| d.day())
+}
 
-        let app_state = Arc::new(Mutex::new(state));
-        let notification_state = Arc::clone(&app_state);
-        thread::spawn(move || loop {
-            thread::sleep(Duration::from_secs(15));
-            let mut state = notification_state.lock().unwrap();
-            let now = Local::now();
-            for todo in state.todos.iter_mut() {
-                if !todo.is_done {
-                    if let Some(due_time) = todo.due {
-                        if now > due_time && !todo.notified {
-                            Notification::new()
-                                .summary("Todo Due!")
-                                .body(&todo.text)
-                                .show()
-                                .ok();
-                            if todo.loop_freq == LoopFrequency::Once {
-                                todo.notified = true;
-                            } else {
-                                todo.due = Some(get_next_due(&due_time, todo.loop_freq));
-                            }
-                        }
-                    }
+/// The day of the month a Monthly/Yearly repeat on `date` aims for. That is
+/// `date`'s own day, unless `anchor` says an earlier short month clamped a
+/// later one (the 31st landing on Feb 28), in which case the anchor wins.
+/// Anchors that don't explain `date` are stale and ignored.
+fn anchored_day(anchor: Option<u32>, date: NaiveDate) -> u32 {
+    anchor
+        .filter(|&a| a > date.day() && date.day() == days_in_month(date.year(), date.month()))
+        .unwrap_or(date.day())
+}
+
+/// The wall-clock time a repeat now due at `due` was aimed at: `due`'s own,
+/// unless `anchor` says a spring-forward gap pushed it later (02:30 landing
+/// on 03:30), so the next occurrence goes back to 02:30. Anchors that don't
+/// explain `due` are stale and ignored.
+fn anchored_wall(anchor: Option<NaiveTime>, due: DateTime<Local>, zone: BoardZone) -> NaiveDateTime {
+    let wall = zone.wall_clock(due);
+    anchor
+        .map(|t| wall.date().and_time(t))
+        .filter(|&aimed| aimed != wall && zone.instant_at(aimed) == due)
+        .unwrap_or(wall)
+}
+
+/// `wall` moved `months` calendar months on, landing on `day` or on the last
+/// day of months too short for it.
+fn add_months(wall: NaiveDateTime, months: u32, day: u32) -> NaiveDateTime {
+    let index = wall.year() * 12 + wall.month0() as i32 + months as i32;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    NaiveDate::from_ymd_opt(year, month, day.min(days_in_month(year, month)))
+        .map_or(wall, |d| d.and_time(wall.time()))
+}
+
+/// The first occurrence after `now` of a todo repeating at `freq`, or `None`
+/// if it doesn't repeat. The stepping happens on the board zone's wall clock,
+/// so a daily 09:00 stays at 09:00 across DST changes instead of drifting by
+/// the hour gained or lost. Monthly and Yearly repeats move by calendar
+/// months and stay on their day of the month; see [`anchored_day`] and
+/// [`anchored_wall`] for the anchors.
+fn get_next_due(
+    current_due: &DateTime<Local>,
+    freq: LoopFrequency,
+    zone: BoardZone,
+    now: DateTime<Local>,
+    anchor: Option<u32>,
+    time_anchor: Option<NaiveTime>,
+) -> Option<DateTime<Local>> {
+    let target_weekday = match freq {
+        // Custom rules step with `Recurrence::advance`, which keeps count.
+        LoopFrequency::Once | LoopFrequency::Custom => return None,
+        LoopFrequency::Sunday => Some(Weekday::Sun),
+        LoopFrequency::Monday => Some(Weekday::Mon),
+        LoopFrequency::Tuesday => Some(Weekday::Tue),
+        LoopFrequency::Wednesday => Some(Weekday::Wed),
+        LoopFrequency::Thursday => Some(Weekday::Thu),
+        LoopFrequency::Friday => Some(Weekday::Fri),
+        LoopFrequency::Saturday => Some(Weekday::Sat),
+        LoopFrequency::Daily
+        | LoopFrequency::Weekly
+        | LoopFrequency::Monthly
+        | LoopFrequency::Yearly => None,
+    };
+    let start = anchored_wall(time_anchor, *current_due, zone);
+    let day = anchored_day(anchor, start.date());
+    let mut wall = start;
+    let mut months = 0;
+    let mut next_due = *current_due;
+    while next_due <= now {
+        wall = match (freq, target_weekday) {
+            (_, Some(weekday)) => {
+                let mut next = wall + chrono::Duration::days(1);
+                while next.weekday() != weekday {
+                    next += chrono::Duration::days(1);
                 }
+                next
             }
-        });
+            (LoopFrequency::Weekly, _) => wall + chrono::Duration::weeks(1),
+            // Always counted from the start, so one short month can't pull
+            // the rest of the run off its day.
+            (LoopFrequency::Monthly, _) => {
+                months += 1;
+                add_months(start, months, day)
+            }
+            (LoopFrequency::Yearly, _) => {
+                months += 12;
+                add_months(start, months, day)
+            }
+            _ => wall + chrono::Duration::days(1),
+        };
+        next_due = zone.instant_at(wall);
+    }
+    Some(next_due)
+}
+/// `hour`:00 on the day after `now`, on the board zone's clock.
+fn tomorrow_at(now: DateTime<Local>, zone: BoardZone, hour: u32) -> DateTime<Local> {
+    let date = zone.wall_clock(now).date() + chrono::Duration::days(1);
+    zone.instant_at(date.and_hms_opt(hour, 0, 0).unwrap_or_default())
+}
 
-        Self {
-            state: app_state,
-            storage_path,
-            background_texture,
+/// For each blocked todo, the open todos holding it up: the one it is
+/// waiting on and the start of every dependency link ending at it.
+fn blockers(todos: &[Todo], connections: &[Connection]) -> HashMap<usize, Vec<usize>> {
+    let open: HashSet<usize> = todos.iter().filter(|t| !t.is_done).map(|t| t.id).collect();
+    let mut blockers: HashMap<usize, Vec<usize>> = HashMap::new();
+    for todo in todos {
+        if let Some(id) = todo.waiting_on.filter(|id| open.contains(id)) {
+            blockers.entry(todo.id).or_default().push(id);
         }
     }
-
-    fn get_storage_path() -> Option<PathBuf> {
-        directories::ProjectDirs::from("com", "gemini", "endlesscanvas")
-            .map(|p| p.data_dir().join("app_state.json"))
+    for conn in connections {
+        if conn.dependency && open.contains(&conn.from) && todos.iter().any(|t| t.id == conn.to) {
+            let list = blockers.entry(conn.to).or_default();
+            if !list.contains(&conn.from) {
+                list.push(conn.from);
+            }
+        }
     }
-    fn from_disk(path: Option<&Path>) -> Option<AppState> {
-        let p = path?;
-        std::fs::read_to_string(p)
-            .ok()
-            .and_then(|c| serde_json::from_str(&c).ok())
+    blockers
+}
+
+/// Todos that can't be started yet; see [`blockers`].
+fn blocked_ids(todos: &[Todo], connections: &[Connection]) -> HashSet<usize> {
+    blockers(todos, connections).into_keys().collect()
+}
+
+fn reminder_for(todo: &Todo, kind: ReminderKind) -> Reminder {
+    Reminder {
+        id: todo.id,
+        text: todo.text.clone(),
+        starred: todo.meta.starred,
+        priority: todo.priority,
+        kind,
     }
-    fn save_state(&self) {
-        if let Some(path) = &self.storage_path {
-            if let Ok(json) = serde_json::to_string_pretty(&*self.state.lock().unwrap()) {
-                if let Some(p) = path.parent() {
-                    _ = std::fs::create_dir_all(p);
+}
+
+/// Marks open, past-due todos as notified (or moves recurring ones to their
+/// next occurrence) and returns the reminders to show. Blocked todos are left
+/// alone until they are unblocked. Completed repeats start their next cycle
+/// once their due time has passed, or straight away with
+/// `repeat_on_completion`.
+fn fire_due_reminders(
+    todos: &mut [Todo],
+    connections: &[Connection],
+    now: DateTime<Local>,
+    zone: BoardZone,
+    default_lead: u32,
+    repeat_on_completion: bool,
+) -> Vec<Reminder> {
+    let blocked = blocked_ids(todos, connections);
+    let mut fired = Vec::new();
+    for todo in todos.iter_mut() {
+        if todo.is_done && (repeat_on_completion || todo.due.is_some_and(|d| now > d)) {
+            todo.start_next_cycle(zone, now);
+        }
+        if todo.is_done || todo.notified || blocked.contains(&todo.id) {
+            continue;
+        }
+        if todo.snoozed_until.is_some_and(|t| now >= t) {
+            todo.snoozed_until = None;
+            fired.push(reminder_for(todo, ReminderKind::Snoozed));
+        }
+        if let Some(due_time) = todo.due {
+            let lead = chrono::Duration::minutes(todo.remind_before.unwrap_or(default_lead) as i64);
+            if lead > chrono::Duration::zero()
+                && now <= due_time
+                && now >= due_time - lead
+                && todo.early_notified != Some(due_time)
+            {
+                todo.early_notified = Some(due_time);
+                let minutes_left = ((due_time - now).num_seconds() + 59) / 60;
+                fired.push(reminder_for(todo, ReminderKind::Early(minutes_left as u32)));
+            }
+            if now > due_time {
+                fired.push(reminder_for(todo, ReminderKind::Overdue));
+                match todo.next_occurrence(due_time, zone, now) {
+                    Some(next) => todo.due = Some(next),
+                    None => todo.notified = true,
                 }
-                _ = std::fs::write(path, json);
             }
         }
     }
+    fired
+}
+
+/// The main board's file and the open board's, for the reminder thread. Held
+/// while board files are read and rewritten, so the thread never writes a
+/// board the window is loading.
+type OpenBoard = Arc<Mutex<Option<(PathBuf, PathBuf)>>>;
+
+/// Written next to the main board while the app runs and rewritten every
+/// reminder poll. The app fires every board's reminders itself, so
+/// `--check-reminders` leaves them alone while it is fresh.
+fn running_file(main: &Path) -> PathBuf {
+    let stem = main.file_stem().unwrap_or_default().to_string_lossy();
+    main.with_file_name(format!("{}.running", stem))
+}
+
+/// Whether the app has written [`running_file`] within a couple of polls.
+/// One that quit without removing it goes stale on its own.
+fn app_running(main: &Path, poll_seconds: u32) -> bool {
+    let fresh = Duration::from_secs(2 * poll_seconds.max(1) as u64 + 5);
+    std::fs::metadata(running_file(main))
+        .and_then(|m| m.modified())
+        .is_ok_and(|at| at.elapsed().is_ok_and(|age| age < fresh))
+}
+
+/// Fires due reminders on the board saved at `path`, which is not open in
+/// the window, and saves it back if that changed anything: repeats may move
+/// on without a reminder going out.
+fn fire_board_file_reminders(
+    path: &Path,
+    settings: &Settings,
+    now: DateTime<Local>,
+) -> Vec<Reminder> {
+    let Some(mut state) = EndlessCanvasApp::from_disk(Some(path)) else {
+        return Vec::new();
+    };
+    let zone = state.zone();
+    let before = serde_json::to_value(&state.todos).ok();
+    let fired = fire_due_reminders(
+        &mut state.todos,
+        &state.connections,
+        now,
+        zone,
+        settings.remind_before_minutes,
+        settings.repeat_on_completion,
+    );
+    if serde_json::to_value(&state.todos).ok() != before {
+        _ = write_state(path, &state);
+    }
+    fired
+}
+
+/// Names the board a reminder comes from, unless it is the main one.
+fn on_board(mut reminder: Reminder, board: Option<&str>) -> Reminder {
+    if let Some(name) = board {
+        reminder.text = format!("[{}] {}", name, reminder.text);
+    }
+    reminder
+}
+
+fn write_state(path: &Path, state: &AppState) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&state.to_persisted())?;
+    if let Some(p) = path.parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    std::fs::write(path, json)
+}
+
+/// Whether files can be created in `dir`, found by writing and removing a
+/// probe file.
+fn dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(".write_probe");
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .is_ok()
+}
+
+fn details_preview(text: &str) -> String {
+    let mut preview: String = text.lines().take(3).collect::<Vec<_>>().join("\n");
+    if preview.chars().count() > 200 {
+        preview = preview.chars().take(200).collect::<String>() + "\u{2026}";
+    }
+    preview
+}
+/// Cuts `text` to at most `limit` characters. Returns whether anything was cut.
+fn truncate_chars(text: &mut String, limit: usize) -> bool {
+    match text.char_indices().nth(limit) {
+        Some((end, _)) => {
+            text.truncate(end);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Character count shown once a note nears `soft_limit`, red past it.
+fn length_counter(ui: &mut egui::Ui, text: &str, soft_limit: usize) {
+    if soft_limit == 0 {
+        return;
+    }
+    let len = text.chars().count();
+    if len * 10 < soft_limit * 8 {
+        return;
+    }
+    let label = format!("{} / {}", len, soft_limit);
+    let color = if len >= soft_limit {
+        Color32::RED
+    } else {
+        ui.visuals().weak_text_color()
+    };
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+        ui.colored_label(color, label);
+    });
+}
+
+fn item_title(text: &str, starred: bool) -> String {
+    if starred {
+        format!("{} {}", ICON_STAR, generate_title(text))
+    } else {
+        generate_title(text)
+    }
+}
+fn generate_title(text: &str) -> String {
+    let title = text
+        .split_whitespace()
+        .take(4)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if title.is_empty() {
+        " ".to_string()
+    } else {
+        title
+    }
+}
+/// Pushes the due date of every selected todo forward by `by`, skipping todos
+/// without a due date. Returns how many were moved.
+fn shift_due(todos: &mut [Todo], selected: &HashSet<usize>, by: chrono::Duration) -> usize {
+    let mut moved = 0;
+    for todo in todos.iter_mut().filter(|t| selected.contains(&t.id)) {
+        if let Some(due) = todo.due {
+            todo.due = Some(due + by);
+            todo.rearm_reminders();
+            moved += 1;
+        }
+    }
+    moved
+}
+
+fn draw_count_badge(ctx: &Context, window: &egui::Response, count: usize) {
+    let painter = ctx.layer_painter(window.layer_id);
+    let center = window.rect.right_top();
+    painter.circle_filled(center, 9.0, Color32::from_rgb(70, 110, 200));
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        count.to_string(),
+        egui::FontId::proportional(11.0),
+        Color32::WHITE,
+    );
+}
+
+/// Strength of the jump-to flash on item `id` at `time`, while it lasts.
+fn flash_pulse(flash: Option<(usize, f64)>, id: usize, time: f64) -> Option<f32> {
+    let (flashing, start) = flash?;
+    let t = time - start;
+    (flashing == id && t < FLASH_SECONDS).then(|| (0.5 + 0.5 * (t * 10.0).cos()) as f32)
+}
+
+/// True for open todos whose due time falls within `window` from `now`.
+fn is_due_soon(todo: &Todo, now: DateTime<Local>, window: chrono::Duration) -> bool {
+    !todo.is_done && todo.due.is_some_and(|due| due > now && due - now <= window)
+}
+
+/// Per-frame visual state of an item window.
+struct ItemLook {
+    selected: bool,
+    /// Under the pointer while a connection is being made.
+    link_target: bool,
+    /// Matches the list panel search.
+    matched: bool,
+    starred: bool,
+    color: Option<ItemColor>,
+    /// Fill from the theme file, in place of the window fill.
+    fill: Option<Color32>,
+    /// Pulse strength in `0.0..=1.0` for items that need attention.
+    glow: Option<f32>,
+    opacity: f32,
+}
+impl Default for ItemLook {
+    fn default() -> Self {
+        Self {
+            selected: false,
+            link_target: false,
+            matched: false,
+            starred: false,
+            color: None,
+            fill: None,
+            glow: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// What the item context menu asks the caller to do with the item.
+enum ItemCommand {
+    Focus,
+    Delete,
+}
+
+fn item_context_menu(
+    ui: &mut egui::Ui,
+    enabled: &HashSet<MenuAction>,
+    id: usize,
+    meta: &mut ItemMeta,
+    connections: &mut [Connection],
+    titles: &HashMap<usize, String>,
+    pan_to: &mut Option<usize>,
+) -> Option<ItemCommand> {
+    if enabled.contains(&MenuAction::ToggleStar) {
+        let label = if meta.starred { "Unstar" } else { "Star" };
+        if ui.button(label).clicked() {
+            meta.starred = !meta.starred;
+            ui.close_menu();
+        }
+    }
+    if enabled.contains(&MenuAction::Color) {
+        ui.menu_button("Color", |ui| {
+            if color_picker(ui, &mut meta.color) {
+                ui.close_menu();
+            }
+        });
+    }
+    if enabled.contains(&MenuAction::Opacity) {
+        ui.horizontal(|ui| {
+            ui.label("Opacity");
+            ui.add(egui::Slider::new(&mut meta.opacity, MIN_OPACITY..=1.0));
+        });
+    }
+    if enabled.contains(&MenuAction::Connections) {
+        ui.menu_button("Connections", |ui| {
+            connection_sides_menu(ui, id, connections, titles);
+        });
+    }
+    if enabled.contains(&MenuAction::CenterView) && ui.button("Center view").clicked() {
+        *pan_to = Some(id);
+        ui.close_menu();
+    }
+    let mut command = None;
+    if enabled.contains(&MenuAction::Focus) && ui.button("Focus").clicked() {
+        command = Some(ItemCommand::Focus);
+    }
+    if enabled.contains(&MenuAction::Delete) && ui.button("Delete").clicked() {
+        command = Some(ItemCommand::Delete);
+    }
+    if command.is_some() {
+        ui.close_menu();
+    }
+    command
+}
+
+/// Asks before completing a todo whose predecessors are still open. Returns
+/// its id once confirmed.
+fn confirm_early_done(
+    ctx: &Context,
+    state: &mut AppState,
+    titles: &HashMap<usize, String>,
+) -> Option<usize> {
+    let id = state.confirm_early_done?;
+    let blocked_by = blockers(&state.todos, &state.connections).remove(&id);
+    let Some(ids) = blocked_by else {
+        // Unblocked meanwhile, so there is nothing to warn about.
+        state.confirm_early_done = None;
+        return Some(id);
+    };
+    let mut answer = None;
+    egui::Window::new("Still blocked")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "\"{}\" is waiting on:",
+                titles.get(&id).map_or("?", String::as_str)
+            ));
+            for blocker in &ids {
+                ui.label(format!(
+                    "\u{2022} {}",
+                    titles.get(blocker).map_or("?", String::as_str)
+                ));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Mark done anyway").clicked() {
+                    answer = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    answer = Some(false);
+                }
+            });
+        });
+    let answer = answer?;
+    state.confirm_early_done = None;
+    answer.then_some(id)
+}
+
+fn star_toggle(ui: &mut egui::Ui, starred: &mut bool) {
+    let icon = if *starred {
+        ICON_STAR
+    } else {
+        ICON_STAR_OUTLINE
+    };
+    if ui
+        .selectable_label(*starred, icon)
+        .on_hover_text("Star")
+        .clicked()
+    {
+        *starred = !*starred;
+    }
+}
+
+fn item_frame(ctx: &Context, look: &ItemLook) -> egui::Frame {
+    let mut frame = egui::Frame::window(&ctx.style());
+    if let Some(fill) = look.fill {
+        frame.fill = fill;
+    }
+    if let Some(color) = look.color {
+        frame.fill = color.tint(frame.fill);
+    }
+    if look.link_target {
+        frame = frame.stroke(Stroke::new(3.0, Color32::LIGHT_BLUE));
+    } else if look.selected {
+        frame = frame.stroke(Stroke::new(2.0, Color32::LIGHT_BLUE));
+    } else if look.matched {
+        frame = frame.stroke(Stroke::new(2.0, Color32::LIGHT_GREEN));
+    } else if look.starred {
+        frame = frame.stroke(Stroke::new(1.5, Color32::GOLD));
+    } else if let Some(color) = look.color {
+        frame = frame.stroke(Stroke::new(1.5, color.rgb()));
+    }
+    if let Some(pulse) = look.glow {
+        frame.shadow = egui::epaint::Shadow {
+            offset: Vec2::ZERO,
+            blur: 16.0,
+            spread: 2.0 + 4.0 * pulse,
+            color: Color32::from_rgba_unmultiplied(255, 170, 0, (60.0 + 120.0 * pulse) as u8),
+        };
+    }
+    frame.multiply_with_opacity(look.opacity)
+}
+
+/// Ctrl/Cmd-clicking an item window toggles it in the selection.
+fn toggle_selection_on_click(
+    ctx: &Context,
+    response: &egui::Response,
+    id: usize,
+    selected: &mut HashSet<usize>,
+) {
+    let click_pos = ctx.input(|i| {
+        (i.modifiers.command && i.pointer.primary_clicked())
+            .then(|| i.pointer.interact_pos())
+            .flatten()
+    });
+    if let Some(pos) = click_pos {
+        if response.rect.contains(pos)
+            && ctx.layer_id_at(pos) == Some(response.layer_id)
+            && !selected.remove(&id)
+        {
+            selected.insert(id);
+        }
+    }
+}
+
+/// Warns that nothing is being saved. Returns whether the user asked to pick
+/// another folder.
+fn unwritable_banner(ctx: &Context, dir: Option<&Path>) -> bool {
+    let mut choose = false;
+    egui::Area::new(Id::new("unwritable_banner"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 48.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let place = dir.map_or("the data folder".to_string(), |d| d.display().to_string());
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "\u{26A0} Can't save to {}. Changes are kept in memory only.",
+                        place
+                    ),
+                );
+                choose = ui.button("Choose another folder...").clicked();
+            });
+        });
+    choose
+}
+
+/// Lists linked files that changed on disk. Returns a file the user dealt
+/// with and whether they asked to reload it.
+fn stale_files_banner(ctx: &Context, files: &[PathBuf]) -> Option<(PathBuf, bool)> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut handled = None;
+    egui::Area::new(Id::new("stale_files"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for file in files {
+                    ui.horizontal(|ui| {
+                        let state = if file.exists() { "changed" } else { "is gone" };
+                        ui.label(format!("\u{26A0} {} {}", file.display(), state));
+                        if ui.button("Reload").clicked() {
+                            handled = Some((file.clone(), true));
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            handled = Some((file.clone(), false));
+                        }
+                    });
+                }
+            });
+        });
+    handled
+}
+
+/// Far zoomed out, items are drawn as plain boxes with their title, which is
+/// much cheaper than full windows and easier to read at that size.
+fn draw_item_boxes(
+    painter: &egui::Painter,
+    state: &AppState,
+    transformer: Transformer,
+    hidden: &HashSet<usize>,
+) {
+    let boxes = state
+        .notes
+        .iter()
+        .map(|n| {
+            (
+                n.id,
+                n.position,
+                n.size,
+                &n.text,
+                &n.meta,
+                Color32::from_gray(70),
+            )
+        })
+        .chain(state.todos.iter().map(|t| {
+            let fill = if t.is_done {
+                Color32::from_gray(45)
+            } else {
+                Color32::from_rgb(40, 60, 90)
+            };
+            (t.id, t.position, t.size, &t.text, &t.meta, fill)
+        }));
+    for (id, pos, size, text, meta, fill) in boxes {
+        if hidden.contains(&id) {
+            continue;
+        }
+        let rect = Rect::from_min_size(transformer.to_screen(pos), size * transformer.zoom());
+        let fill = meta.color.map_or(fill, |c| c.tint(fill));
+        let fill = fill.gamma_multiply(meta.opacity.clamp(MIN_OPACITY, 1.0));
+        let stroke = if state.selected.contains(&id) {
+            Stroke::new(1.5, Color32::LIGHT_BLUE)
+        } else if meta.starred {
+            Stroke::new(1.0, Color32::GOLD)
+        } else {
+            Stroke::NONE
+        };
+        painter.rect(rect, 2.0, fill, stroke);
+        if rect.height() >= 10.0 {
+            painter.with_clip_rect(rect).text(
+                rect.left_top() + Vec2::splat(2.0),
+                egui::Align2::LEFT_TOP,
+                generate_title(text),
+                egui::FontId::proportional(rect.height().min(12.0) - 2.0),
+                Color32::from_gray(220),
+            );
+        }
+    }
+}
+
+/// egui raises a window whenever it is pressed or dragged. Asking for every
+/// window to be raised in the same frame keeps their relative order, which
+/// cancels that out.
+fn keep_window_order(ctx: &Context) {
+    if !ctx.input(|i| i.pointer.any_down() || i.pointer.any_released()) {
+        return;
+    }
+    ctx.memory_mut(|m| {
+        let layers: Vec<egui::LayerId> = m
+            .layer_ids()
+            .filter(|l| l.order == egui::Order::Middle && m.areas().visible_last_frame(l))
+            .collect();
+        for layer in layers {
+            m.areas_mut().move_to_top(layer);
+        }
+    });
+}
+
+fn draw_origin_marker(painter: &egui::Painter, at: Pos2, color: Color32) {
+    let stroke = Stroke::new(1.0, color);
+    let arm = 8.0;
+    painter.line_segment([at - Vec2::X * arm, at + Vec2::X * arm], stroke);
+    painter.line_segment([at - Vec2::Y * arm, at + Vec2::Y * arm], stroke);
+    painter.text(
+        at + Vec2::splat(4.0),
+        egui::Align2::LEFT_TOP,
+        "0,0",
+        egui::FontId::proportional(10.0),
+        stroke.color,
+    );
+}
+
+/// One pass of the reminder thread over the open board; returns the
+/// settings, for the pass over the others.
+fn fire_open_board_reminders(app_state: &Arc<Mutex<AppState>>, ctx: &Context) -> Settings {
+    let mut state = app_state.lock().unwrap();
+    let zone = state.zone();
+    let state = &mut *state;
+    let fired = state.reminder_pass(|state| {
+        fire_due_reminders(
+            &mut state.todos,
+            &state.connections,
+            Local::now(),
+            zone,
+            state.settings.remind_before_minutes,
+            state.settings.repeat_on_completion,
+        )
+    });
+    let notifier = Notifier::new(&state.settings);
+    // Also ticked here, so sessions move on while the window is hidden.
+    if let Some((summary, text)) = state.tick_pomodoro(Local::now()) {
+        notifier.pomodoro(summary, &text);
+        ctx.request_repaint();
+    }
+    for reminder in &fired {
+        // A locked board can't be completed or snoozed from outside.
+        if state.read_only() {
+            notifier.due(reminder, None);
+            continue;
+        }
+        let id = reminder.id;
+        let action_state = Arc::clone(app_state);
+        let action_ctx = ctx.clone();
+        let on_action: ActionHandler = Box::new(move |action| {
+            let mut state = action_state.lock().unwrap();
+            // It may have been locked since the reminder went out.
+            if state.read_only() {
+                return;
+            }
+            let unblocked = state.reminder_action(id, action, Local::now());
+            let notifier = Notifier::new(&state.settings);
+            if action == ReminderAction::Done {
+                notifier.cue(Cue::Completed);
+            }
+            for text in unblocked {
+                notifier.unblocked(&text);
+            }
+            action_ctx.request_repaint();
+        });
+        notifier.due(reminder, Some(on_action));
+    }
+    if !fired.is_empty() {
+        ctx.request_repaint();
+    }
+    state.settings.clone()
+}
+
+impl EndlessCanvasApp {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        install_fonts(&cc.egui_ctx);
+        let storage_path = Self::get_storage_path();
+        let storage_writable = storage_path
+            .as_deref()
+            .and_then(Path::parent)
+            .is_some_and(dir_writable);
+        let mut state = Self::from_disk(storage_path.as_deref()).unwrap_or_default();
+        if let Some(main) = &storage_path {
+            state.settings = Settings::load(main);
+        }
+        state.prune_dangling_refs();
+        let mut toasts = Toasts::default();
+        // Like the periodic pass, none of this touches a locked board.
+        if !state.read_only() {
+            let archive_after_days = state.board_settings.archive_after_days;
+            report_archived(
+                &mut toasts,
+                state.archive_done_todos(Local::now(), archive_after_days),
+            );
+            let trash_days = state.board_settings.trash_retention_days;
+            state.purge_trash(Local::now(), trash_days);
+            if state.settings.auto_arrange_on_load {
+                let moved = state.tidy_overlaps();
+                if moved > 0 {
+                    toasts.info(format!("Moved {} item(s)", moved));
+                }
+            }
+        }
+        state.checkpoint();
+
+        restore_window_state(&cc.egui_ctx, &state);
+        let background = state
+            .background_image_path
+            .as_deref()
+            .and_then(|p| load_background(&cc.egui_ctx, Path::new(p)));
+
+        let app_state = Arc::new(Mutex::new(state));
+        let open_board = storage_path.clone().map(|main| (main.clone(), main));
+        let open_board: OpenBoard = Arc::new(Mutex::new(open_board));
+        let notification_state = Arc::clone(&app_state);
+        let closed_boards = Arc::clone(&open_board);
+        let repaint_ctx = cc.egui_ctx.clone();
+        thread::spawn(move || loop {
+            let poll = notification_state
+                .lock()
+                .unwrap()
+                .settings
+                .reminder_poll_seconds;
+            if let Some((main, _)) = &*closed_boards.lock().unwrap() {
+                _ = std::fs::write(running_file(main), std::process::id().to_string());
+            }
+            thread::sleep(Duration::from_secs(poll.max(1) as u64));
+            let settings = fire_open_board_reminders(&notification_state, &repaint_ctx);
+            // The other boards only live on disk, and offer no buttons as
+            // there is nothing loaded for them to act on.
+            let boards = closed_boards.lock().unwrap();
+            if let Some((main, open)) = &*boards {
+                let notifier = Notifier::new(&settings);
+                for (name, path) in board_files(main) {
+                    if path == *open {
+                        continue;
+                    }
+                    for reminder in fire_board_file_reminders(&path, &settings, Local::now()) {
+                        notifier.due(&on_board(reminder, name.as_deref()), None);
+                    }
+                }
+            }
+        });
+
+        Self {
+            state: app_state,
+            board_names: storage_path.as_deref().map(list_boards).unwrap_or_default(),
+            storage_path,
+            current_board: None,
+            open_board,
+            new_board_name: String::new(),
+            background,
+            custom_theme: None,
+            toasts,
+            last_archive_pass: 0.0,
+            file_watch: FileWatch::default(),
+            storage_writable,
+            old_data_files: Vec::new(),
+            pending_board_move: None,
+            images: ImageCache::default(),
+            tray: Tray::spawn(&cc.egui_ctx),
+            global_hotkey: None,
+            global_hotkey_wanted: None,
+            quick_capture: None,
+            window_hidden: false,
+            quitting: false,
+        }
+    }
+
+    fn default_data_dir() -> Option<PathBuf> {
+        directories::ProjectDirs::from("com", "gemini", "endlesscanvas")
+            .map(|p| p.data_dir().to_path_buf())
+    }
+    /// The state file, honoring a location chosen via "Move data to...".
+    fn get_storage_path() -> Option<PathBuf> {
+        let dir = Self::default_data_dir()?;
+        let moved = std::fs::read_to_string(dir.join(LOCATION_FILE))
+            .ok()
+            .map(|s| PathBuf::from(s.trim()))
+            .filter(|p| !p.as_os_str().is_empty());
+        Some(moved.unwrap_or_else(|| dir.join(STATE_FILE)))
+    }
+    fn remember_storage_path(path: &Path) -> std::io::Result<()> {
+        let Some(dir) = Self::default_data_dir() else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&dir)?;
+        if path == dir.join(STATE_FILE) {
+            match std::fs::remove_file(dir.join(LOCATION_FILE)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        } else {
+            std::fs::write(dir.join(LOCATION_FILE), path.display().to_string())
+        }
+    }
+    fn from_disk(path: Option<&Path>) -> Option<AppState> {
+        let p = path?;
+        std::fs::read_to_string(p)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .map(AppState::from_persisted)
+    }
+    /// File of the open board.
+    fn board_path(&self) -> Option<PathBuf> {
+        let main = self.storage_path.as_deref()?;
+        Some(board_file(main, self.current_board.as_deref()))
+    }
+    /// Keeps the system-wide quick capture shortcut in step with its
+    /// setting and binding, and opens the capture window when it is pressed.
+    fn sync_global_hotkey(&mut self, ctx: &Context) {
+        let wanted = {
+            let state = self.state.lock().unwrap();
+            let settings = &state.settings;
+            settings
+                .global_quick_capture
+                .then(|| hotkeys::binding(&settings.keybindings, HotkeyAction::QuickCapture))
+        };
+        if wanted != self.global_hotkey_wanted {
+            self.global_hotkey = None;
+            self.global_hotkey_wanted = wanted;
+            if let Some(shortcut) = wanted {
+                match GlobalHotkey::register(ctx, shortcut) {
+                    Ok(hotkey) => self.global_hotkey = Some(hotkey),
+                    Err(e) => self
+                        .toasts
+                        .info(format!("Quick capture works in this window only: {}", e)),
+                }
+            }
+        }
+        if self
+            .global_hotkey
+            .as_ref()
+            .is_some_and(GlobalHotkey::pressed)
+        {
+            self.quick_capture.get_or_insert_with(QuickCapture::default);
+        }
+    }
+    /// Shows the quick capture window if it is open. Captured todos land in
+    /// the middle of the canvas view.
+    fn show_quick_capture(&mut self, ctx: &Context) {
+        let Some(capture) = &mut self.quick_capture else {
+            return;
+        };
+        let now = Local::now();
+        let zone = self.state.lock().unwrap().zone();
+        match quick_capture_window(ctx, capture, now, zone) {
+            Some(CaptureResult::Add(text, due)) => {
+                let mut state = self.state.lock().unwrap();
+                if state.read_only() {
+                    self.toasts
+                        .info("The board is locked; the todo was not added");
+                } else {
+                    let center = Transformer::new(state.offset, state.zoom)
+                        .from_screen(ctx.screen_rect().center());
+                    let id = state.add_todo(center, now);
+                    if let Some(todo) = state.todos.iter_mut().find(|t| t.id == id) {
+                        todo.text = text;
+                        todo.due = due.or(todo.due);
+                    }
+                    self.toasts.info("Todo added");
+                }
+                self.quick_capture = None;
+            }
+            Some(CaptureResult::Cancel) => self.quick_capture = None,
+            None => {}
+        }
+    }
+    /// Applies tray menu picks. With "run in background" on, closing the
+    /// window hides it instead, so the reminder thread keeps running.
+    fn handle_tray(&mut self, ctx: &Context) {
+        let Some(tray) = &self.tray else {
+            return;
+        };
+        let commands: Vec<TrayCommand> = tray.commands().collect();
+        for command in commands {
+            match command {
+                TrayCommand::Toggle => self.set_window_hidden(ctx, !self.window_hidden),
+                TrayCommand::QuickAdd => {
+                    self.set_window_hidden(ctx, false);
+                    let mut state = self.state.lock().unwrap();
+                    if !state.read_only() {
+                        state.settings.view_mode = ViewMode::Canvas;
+                        let center = Transformer::new(state.offset, state.zoom)
+                            .from_screen(ctx.screen_rect().center());
+                        state.focus_item = Some(state.add_todo(center, Local::now()));
+                    }
+                }
+                TrayCommand::Quit => {
+                    self.quitting = true;
+                    ctx.send_viewport_cmd(ViewportCommand::Close);
+                }
+            }
+        }
+        let background = self.state.lock().unwrap().settings.run_in_background;
+        if background && !self.quitting && ctx.input(|i| i.viewport().close_requested()) {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.set_window_hidden(ctx, true);
+        }
+    }
+    /// Switches egui between dark and light visuals to match the setting.
+    fn apply_theme(&mut self, ctx: &Context, frame: &Frame) {
+        let (theme, file, reload) = {
+            let mut state = self.state.lock().unwrap();
+            let reload = std::mem::take(&mut state.reload_theme);
+            (
+                state.settings.theme,
+                state.settings.theme_file.clone(),
+                reload,
+            )
+        };
+        if reload || self.custom_theme.as_ref().map(|(path, _)| path) != file.as_ref() {
+            self.custom_theme = file.map(|path| {
+                let loaded = CustomTheme::load(Path::new(&path));
+                if let Err(e) = &loaded {
+                    self.toasts.info(format!("Could not load theme: {}", e));
+                }
+                (path, loaded.ok())
+            });
+        }
+        let dark = match theme {
+            Theme::System => frame.info().system_theme != Some(eframe::Theme::Light),
+            Theme::Dark => true,
+            Theme::Light => false,
+        };
+        let visuals = match self.theme_colors() {
+            Some(custom) => custom.visuals(dark),
+            None if dark => egui::Visuals::dark(),
+            None => egui::Visuals::light(),
+        };
+        if ctx.style().visuals != visuals {
+            ctx.set_visuals(visuals);
+        }
+    }
+    fn theme_colors(&self) -> Option<&CustomTheme> {
+        self.custom_theme.as_ref()?.1.as_ref()
+    }
+    fn set_window_hidden(&mut self, ctx: &Context, hidden: bool) {
+        if hidden {
+            // Nothing may draw a frame, and so autosave, until it is shown.
+            self.save_state();
+        }
+        self.window_hidden = hidden;
+        ctx.send_viewport_cmd(ViewportCommand::Visible(!hidden));
+        if !hidden {
+            ctx.send_viewport_cmd(ViewportCommand::Focus);
+        }
+    }
+    /// Writes the open board and the app-wide settings.
+    fn save_state(&mut self) {
+        let (Some(main), Some(path)) = (self.storage_path.clone(), self.board_path()) else {
+            return;
+        };
+        let state = self.state.lock().unwrap();
+        let board = write_state(&path, &state);
+        let settings = state.settings.save(&main);
+        self.storage_writable = board.is_ok() && settings.is_ok();
+    }
+    /// The board after the open one in the tab order, wrapping around to the
+    /// main board. `None` when there is only the one.
+    fn next_board(&self) -> Option<Option<String>> {
+        if self.storage_path.is_none() || self.board_names.is_empty() {
+            return None;
+        }
+        let boards: Vec<Option<&String>> = std::iter::once(None)
+            .chain(self.board_names.iter().map(Some))
+            .collect();
+        let open = boards
+            .iter()
+            .position(|b| b.map(String::as_str) == self.current_board.as_deref())
+            .unwrap_or(0);
+        Some(boards[(open + 1) % boards.len()].cloned())
+    }
+    /// Saves the open board and opens `name` instead, creating it empty if
+    /// it has no file yet.
+    fn switch_board(&mut self, name: Option<String>, ctx: &Context) {
+        let Some(main) = self.storage_path.clone() else {
+            return;
+        };
+        let open_board = Arc::clone(&self.open_board);
+        let mut open = open_board.lock().unwrap();
+        self.save_state();
+        let path = board_file(&main, name.as_deref());
+        let mut state = Self::from_disk(Some(&path)).unwrap_or_default();
+        state.settings = self.state.lock().unwrap().settings.clone();
+        state.prune_dangling_refs();
+        state.checkpoint();
+        // Window sizes and collapse state are keyed by item id, which boards
+        // reuse, so start from a clean slate.
+        ctx.memory_mut(|m| {
+            m.data.clear();
+            m.reset_areas();
+        });
+        restore_window_state(ctx, &state);
+        self.background = state
+            .background_image_path
+            .as_deref()
+            .and_then(|p| load_background(ctx, Path::new(p)));
+        *self.state.lock().unwrap() = state;
+        self.current_board = name;
+        self.save_state();
+        *open = Some((main.clone(), path));
+        self.board_names = list_boards(&main);
+    }
+
+    fn move_data_to(&mut self, dir: &Path) {
+        let new_path = dir.join(STATE_FILE);
+        if self.storage_path.as_deref() == Some(new_path.as_path()) {
+            return;
+        }
+        let open_board = Arc::clone(&self.open_board);
+        let mut open = open_board.lock().unwrap();
+        self.save_state();
+        let copied = match &self.storage_path {
+            Some(old) => copy_data_files(old, dir),
+            None => std::fs::create_dir_all(dir).map(|_| Vec::new()),
+        };
+        match copied {
+            Ok(copied) => {
+                if let Err(e) = Self::remember_storage_path(&new_path) {
+                    self.toasts
+                        .info(format!("Could not remember the new location: {}", e));
+                }
+                self.storage_path = Some(new_path.clone());
+                self.save_state();
+                *open = self.board_path().map(|board| (new_path, board));
+                self.toasts.info(format!("Moved data to {}", dir.display()));
+                self.old_data_files = copied;
+            }
+            Err(e) => self.toasts.info(format!("Could not move data: {}", e)),
+        }
+    }
+
+    /// Exports the board, or only the items in `only`.
+    fn export_bundle_to(&mut self, dest: &Path, only: Option<&HashSet<usize>>) {
+        let result = export_bundle(&self.state.lock().unwrap(), dest, only);
+        match result {
+            Ok(warnings) => {
+                for warning in warnings {
+                    self.toasts.info(warning);
+                }
+                self.toasts
+                    .info(format!("Exported bundle to {}", dest.display()));
+            }
+            Err(e) => self.toasts.info(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn export_image_to(
+        &mut self,
+        dest: &Path,
+        format: ImageFormat,
+        scale: f32,
+        style: ExportStyle,
+    ) {
+        let result = export_image(&self.state.lock().unwrap(), style, scale, format, dest);
+        match result {
+            Ok(()) => self
+                .toasts
+                .info(format!("Exported image to {}", dest.display())),
+            Err(e) => self.toasts.info(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// Replaces the board with a bundle's contents. The previous state file is
+    /// kept next to it as `<name>.bak`.
+    fn import_bundle_from(&mut self, src: &Path, ctx: &Context) {
+        let Some(path) = self.board_path() else {
+            return;
+        };
+        let data_dir = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+        match import_bundle(src, &data_dir) {
+            Ok(mut imported) => {
+                self.save_state();
+                let mut backup = path.clone().into_os_string();
+                backup.push(".bak");
+                _ = std::fs::copy(&path, backup);
+                imported.settings = self.state.lock().unwrap().settings.clone();
+                imported.prune_dangling_refs();
+                imported.checkpoint();
+                self.background = imported
+                    .background_image_path
+                    .as_deref()
+                    .and_then(|p| load_background(ctx, Path::new(p)));
+                *self.state.lock().unwrap() = imported;
+                self.save_state();
+                self.toasts.info("Imported bundle");
+            }
+            Err(e) => self.toasts.info(format!("Import failed: {}", e)),
+        }
+    }
+
+    /// Moves the selected items into the board saved at `target`, asking
+    /// first if that drops connections to items that stay behind.
+    fn move_selection_to_board(&mut self, target: PathBuf) {
+        let broken = {
+            let state = self.state.lock().unwrap();
+            state.links_leaving(&state.selected)
+        };
+        if broken > 0 {
+            self.pending_board_move = Some((target, broken));
+        } else {
+            self.finish_board_move(&target);
+        }
+    }
+
+    fn finish_board_move(&mut self, target: &Path) {
+        if self.board_path().as_deref() == Some(target) {
+            self.toasts.info("Those items are already on this board");
+            return;
+        }
+        let open_board = Arc::clone(&self.open_board);
+        let _open = open_board.lock().unwrap();
+        let Some(mut board) = Self::from_disk(Some(target)) else {
+            self.toasts
+                .info(format!("Could not read a board from {}", target.display()));
+            return;
+        };
+        // Write the target first and only then take the items out here, so a
+        // failed write can't lose them.
+        let selected = self.state.lock().unwrap().selected.clone();
+        let moved = AppState::from_persisted(self.state.lock().unwrap().to_persisted())
+            .take_items(&selected);
+        let count = moved.notes.len() + moved.todos.len();
+        board.absorb(moved);
+        if let Err(e) = write_state(target, &board) {
+            self.toasts
+                .info(format!("Could not write {}: {}", target.display(), e));
+            return;
+        }
+        self.state.lock().unwrap().take_items(&selected);
+        self.save_state();
+        self.toasts
+            .info(format!("Moved {} item(s) to {}", count, target.display()));
+    }
+
+    fn confirm_board_move(&mut self, ctx: &Context) {
+        let Some((target, broken)) = &self.pending_board_move else {
+            return;
+        };
+        let mut go = None;
+        egui::Window::new("Move to board?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("Moving the selection to {}.", target.display()));
+                ui.label(format!(
+                    "{} connection(s) to items outside the selection will be removed.",
+                    broken
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Move").clicked() {
+                        go = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        go = Some(false);
+                    }
+                });
+            });
+        if let Some(go) = go {
+            if let Some((target, _)) = self.pending_board_move.take() {
+                if go {
+                    self.finish_board_move(&target);
+                }
+            }
+        }
+    }
+
+    fn confirm_delete_old_data(&mut self, ctx: &Context) {
+        if self.old_data_files.is_empty() {
+            return;
+        }
+        let mut delete = None;
+        egui::Window::new("Delete old data?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Remove these files from the previous location?");
+                for file in &self.old_data_files {
+                    ui.monospace(file.display().to_string());
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        delete = Some(true);
+                    }
+                    if ui.button("Keep").clicked() {
+                        delete = Some(false);
+                    }
+                });
+            });
+        match delete {
+            Some(true) => {
+                for file in self.old_data_files.drain(..) {
+                    _ = std::fs::remove_file(file);
+                }
+            }
+            Some(false) => self.old_data_files.clear(),
+            None => {}
+        }
+    }
 }
 
 //TODO
-//+add remove todo or note action
-//+run in background for notifications
-//+add focus mode
 //...
 
 impl App for EndlessCanvasApp {
-    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        let mut state = self.state.lock().unwrap();
-        let transformer = Transformer {
-            offset: state.offset,
-            zoom: state.zoom,
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+        self.handle_tray(ctx);
+        self.sync_global_hotkey(ctx);
+        self.show_quick_capture(ctx);
+        self.apply_theme(ctx, frame);
+        self.images.poll(ctx);
+        if self.storage_path.is_some() {
+            let choice = board_tabs(
+                ctx,
+                &self.board_names,
+                self.current_board.as_deref(),
+                &mut self.new_board_name,
+            );
+            match choice {
+                Some(BoardChoice::Open(name)) => self.switch_board(name, ctx),
+                Some(BoardChoice::Create(name)) => {
+                    self.toasts.info(format!("Created board \"{}\"", name));
+                    self.switch_board(Some(name), ctx);
+                }
+                None => {}
+            }
+        }
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if state.animate_view(ctx.input(|i| i.stable_dt)) {
+            ctx.request_repaint();
+        }
+        let transformer = Transformer::new(state.offset, state.zoom);
+        // A fresh board starts with zoom 0; keep the stored view usable.
+        state.offset = transformer.offset();
+        state.zoom = transformer.zoom();
+        let now = Local::now();
+        let view_now = state.simulated_now.unwrap_or(now);
+        let zone = state.zone();
+        let notifier = Notifier::new(&state.settings);
+        let read_only = state.read_only();
+        let time = ctx.input(|i| i.time);
+        if state
+            .flash
+            .is_some_and(|(_, start)| time - start >= FLASH_SECONDS)
+        {
+            state.flash = None;
+        }
+        if !read_only && time - self.last_archive_pass >= ARCHIVE_PASS_SECONDS {
+            self.last_archive_pass = time;
+            let days = state.board_settings.archive_after_days;
+            report_archived(&mut self.toasts, state.archive_done_todos(now, days));
+            let trash_days = state.board_settings.trash_retention_days;
+            state.purge_trash(now, trash_days);
+        }
+        if let Some((summary, text)) = state.tick_pomodoro(now) {
+            notifier.pomodoro(summary, &text);
+        }
+        if state.pomodoro.is_some() {
+            // Keeps the countdown in the todo's title ticking.
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        let due_soon_window = chrono::Duration::minutes(state.settings.due_soon_minutes as i64);
+        let blockers = blockers(&state.todos, &state.connections);
+        let glow = if state
+            .todos
+            .iter()
+            .any(|t| !blockers.contains_key(&t.id) && is_due_soon(t, view_now, due_soon_window))
+        {
+            // Keep animating the pulse only while something is due soon.
+            ctx.request_repaint();
+            Some(0.5 + 0.5 * (ctx.input(|i| i.time) as f32 * 3.0).sin())
+        } else {
+            if state.flash.is_some() {
+                ctx.request_repaint();
+            }
+            // Wake up now and then so time-based styling catches up while idle.
+            ctx.request_repaint_after(Duration::from_secs(30));
+            None
         };
-        ctx.request_repaint();
+        let watched = if state.settings.watch_linked_files {
+            state.linked_files()
+        } else {
+            HashSet::new()
+        };
+        self.file_watch.sync(ctx, watched);
+        let mut stale_files: Vec<PathBuf> = self.file_watch.changed().iter().cloned().collect();
+        stale_files.sort();
+        let mut move_data_to = None;
+        let mut export_to = None;
+        let mut import_from = None;
+        let mut export_image_to = None;
+        let mut move_to_board = None;
+        let mut save_now = false;
+        let mut open_capture = false;
+        let mut switch_to = None;
+
+        let boards: &[String] = if self.storage_path.is_some() {
+            &self.board_names
+        } else {
+            &[]
+        };
+        let has_selection = !state.selected.is_empty();
+        let mut jump_to_bookmark = None;
+        let toolbar_action =
+            match view_mode_bar(ctx, &mut state.settings, has_selection, &state.bookmarks) {
+                Some(ViewBarAction::Hotkey(action)) => Some(action),
+                Some(ViewBarAction::Bookmark(i)) => {
+                    jump_to_bookmark = Some(i);
+                    None
+                }
+                None => None,
+            };
+        let mut palette_action = None;
+        match command_palette(ctx, state, boards, read_only) {
+            Some(Command::Hotkey(action)) => palette_action = Some(action),
+            Some(Command::ChangeBackground) => {
+                if let Some((path, background)) = pick_background(ctx) {
+                    state.background_image_path = Some(path);
+                    self.background = Some(background);
+                }
+            }
+            Some(Command::ExportBundle) => {
+                export_to = pick_bundle_dest("board.zip").map(|dest| (dest, None));
+            }
+            Some(Command::ExportImage) => state.image_export = Some(ImageExport::default()),
+            Some(Command::OpenBoard(name)) => switch_to = Some(name),
+            Some(Command::JumpTo(id)) => state.jump_to(id, ctx.available_rect(), time),
+            Some(Command::Bookmark(i)) => jump_to_bookmark = Some(i),
+            None => {}
+        }
+
+        if state.rebinding.is_none() {
+            let hotkey_pos = transformer.from_screen(
+                ctx.pointer_hover_pos()
+                    .unwrap_or_else(|| ctx.screen_rect().center()),
+            );
+            let pressed: Vec<HotkeyAction> = HotkeyAction::ALL
+                .into_iter()
+                .filter(|&a| action_pressed(ctx, &state.settings.keybindings, a))
+                .collect();
+            let picked = palette_action.into_iter().chain(toolbar_action);
+            for action in pressed.into_iter().chain(picked) {
+                match action {
+                    HotkeyAction::AddNote if !read_only => {
+                        state.add_note(hotkey_pos);
+                        notifier.cue(Cue::Created);
+                    }
+                    HotkeyAction::AddTodo if !read_only => {
+                        state.focus_item = Some(state.add_todo(hotkey_pos, now));
+                        notifier.cue(Cue::Created);
+                    }
+                    HotkeyAction::UndoConnection if !read_only => {
+                        state.undo_last_connection();
+                    }
+                    HotkeyAction::Undo if !read_only => {
+                        state.undo();
+                    }
+                    HotkeyAction::Redo if !read_only => {
+                        state.redo();
+                    }
+                    HotkeyAction::DeleteSelection if !read_only => {
+                        let selected = std::mem::take(&mut state.selected);
+                        state.delete_items(&selected, now);
+                    }
+                    HotkeyAction::ToggleItemList => {
+                        state.settings.show_list_panel = !state.settings.show_list_panel;
+                    }
+                    HotkeyAction::OpenSettings => state.settings_open = true,
+                    HotkeyAction::ToggleFocus => {
+                        let single = state.selected.iter().next().copied();
+                        state.focus = match state.focus {
+                            None if state.selected.len() == 1 => single,
+                            _ => None,
+                        };
+                        state.focus_item = state.focus.or(state.focus_item);
+                    }
+                    HotkeyAction::Search => {
+                        state.settings.show_list_panel = true;
+                        state.search.focus = true;
+                    }
+                    HotkeyAction::Duplicate if !read_only && !state.selected.is_empty() => {
+                        let selected = state.selected.clone();
+                        state.selected = state.duplicate_items(&selected);
+                    }
+                    HotkeyAction::ResetZoom => state.reset_zoom(ctx.screen_rect()),
+                    HotkeyAction::FitAll => state.fit_view(None, ctx.available_rect()),
+                    HotkeyAction::FitSelection => {
+                        let selected = state.selected.clone();
+                        state.fit_view(Some(&selected), ctx.available_rect());
+                    }
+                    HotkeyAction::Save => save_now = true,
+                    HotkeyAction::NextBoard => switch_to = self.next_board(),
+                    HotkeyAction::CommandPalette => {
+                        state.palette = Some(String::new());
+                    }
+                    HotkeyAction::QuickCapture if !read_only => open_capture = true,
+                    HotkeyAction::Bookmarks => state.bookmarks_open = true,
+                    _ => {}
+                }
+            }
+            if let Some(i) = bookmark_key_pressed(ctx, state.bookmarks.len()) {
+                jump_to_bookmark = Some(i);
+            }
+        }
+        if let Some(bookmark) = jump_to_bookmark.and_then(|i| state.bookmarks.get(i)) {
+            state.settings.view_mode = ViewMode::Canvas;
+            state.view_goal = Some(bookmark_view(state, bookmark, ctx.available_rect()));
+        }
+
+        tag_filter_bar(ctx, state);
+        let matches = matching_ids(state);
+        if state.settings.show_list_panel {
+            if let Some(id) = list_panel(ctx, state, matches.as_ref()) {
+                state.jump_to(id, ctx.available_rect(), time);
+            }
+        }
+        if state.settings.show_time_report {
+            time_report_panel(ctx, state, now);
+        }
+        if state.settings.show_agenda {
+            if let Some(id) = agenda_panel(ctx, state, view_now) {
+                state.settings.view_mode = ViewMode::Canvas;
+                state.jump_to(id, ctx.available_rect(), time);
+            }
+        }
 
+        let brightness = state.settings.background_brightness.clamp(0.0, 1.0);
+        let overlay = state.settings.background_overlay;
+        let accent = if state.settings.auto_contrast {
+            let image = self.background.as_ref().map(|b| b.luminance * brightness);
+            let mut lum = image.unwrap_or_else(|| luminance(ctx.style().visuals.panel_fill));
+            if let Some(color) = overlay.filter(|_| image.is_some()) {
+                let alpha = color.a() as f32 / 255.0;
+                lum = lum * (1.0 - alpha) + luminance(color.to_opaque()) * alpha;
+            }
+            accent_for(lum)
+        } else {
+            Color32::from_gray(128)
+        };
+        let todo_fill = self.theme_colors().and_then(|t| t.todo).map(|h| h.0);
+        let link_theme = self.theme_colors().and_then(|t| {
+            let color = t.connection.map_or(accent, |h| h.0);
+            (t.connection.is_some() || t.connection_width.is_some())
+                .then(|| (color, t.connection_width.unwrap_or(2.0)))
+        });
+        let titles: HashMap<usize, String> = state
+            .notes
+            .iter()
+            .map(|n| (n.id, generate_title(&n.text)))
+            .chain(state.todos.iter().map(|t| (t.id, generate_title(&t.text))))
+            .collect();
+        let mut completed = Vec::new();
+        let mut kanban_action = None;
+        let mut calendar_action = None;
+        let mut timeline_clicked = None;
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(texture) = &self.background_texture {
+            match state.settings.view_mode {
+                ViewMode::Canvas => {}
+                ViewMode::Kanban => {
+                    kanban_action = kanban_view(ui, state, view_now, read_only);
+                    return;
+                }
+                ViewMode::Calendar => {
+                    calendar_action = calendar_view(ui, state, view_now, read_only);
+                    return;
+                }
+                ViewMode::Timeline => {
+                    timeline_clicked = timeline_view(ui, state, view_now);
+                    return;
+                }
+            }
+            if let Some(background) = &self.background {
                 ui.painter().image(
-                    texture.id(),
+                    background.texture.id(),
                     ui.max_rect(),
                     Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                    Color32::WHITE,
+                    Color32::from_gray((brightness * 255.0).round() as u8),
                 );
+                if let Some(color) = overlay {
+                    ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+                }
             }
 
             let response = ui.interact(ui.max_rect(), ui.id(), Sense::click_and_drag());
-            if response.dragged() {
-                state.offset += response.drag_delta();
+            // Focus mode holds the view still.
+            let view_locked = state.focus.is_some();
+            if response.dragged() && !view_locked {
+                state.view_goal = None;
+                let sign = if state.settings.invert_pan { -1.0 } else { 1.0 };
+                state.offset += response.drag_delta() * state.settings.pan_sensitivity * sign;
             }
             if response.clicked() && state.connecting_from_id.is_some() {
                 state.connecting_from_id = None;
             }
-            if let Some(hover_pos) = response.hover_pos() {
+            if response.clicked() && !ui.input(|i| i.modifiers.command) {
+                state.selected.clear();
+            }
+            if let Some(hover_pos) = response.hover_pos().filter(|_| !view_locked) {
                 ui.input(|i| {
-                    let z = i.zoom_delta();
+                    let mut z = i.zoom_delta();
+                    if state.settings.invert_zoom {
+                        z = 1.0 / z;
+                    }
                     if z != 1.0 {
+                        state.view_goal = None;
                         let oz = state.zoom;
-                        state.zoom = (state.zoom * z).clamp(0.1, 10.0);
+                        state.zoom = (state.zoom * z).clamp(MIN_ZOOM, MAX_ZOOM);
                         state.offset = hover_pos - (hover_pos - state.offset) * (state.zoom / oz);
                     }
                 });
             }
+            let hidden = state.filter.hidden_ids(state);
+            let focus_visible = focus_visible(state);
+            let dimmed = |id: usize| focus_visible.as_ref().is_some_and(|v| !v.contains(&id));
             let painter = ui.painter();
-            for (id1, id2) in state.connections.clone() {
-                if let (Some(wp1), Some(wp2)) = (state.get_item_pos(id1), state.get_item_pos(id2)) {
-                    painter.line_segment(
-                        [transformer.to_screen(wp1), transformer.to_screen(wp2)],
-                        Stroke::new(2.0, Color32::from_gray(128)),
+            if state.settings.show_origin {
+                draw_origin_marker(painter, transformer.to_screen(Pos2::ZERO), accent);
+            }
+            if state.settings.snap_to_grid {
+                draw_grid(
+                    painter,
+                    response.rect,
+                    transformer,
+                    state.settings.grid_size,
+                );
+            }
+            draw_lanes(painter, response.rect, &state.lanes, accent, |x| {
+                transformer.to_screen(Pos2::new(x, 0.0)).x
+            });
+            let lod = transformer.zoom() < state.settings.lod_zoom;
+            let link_stroke = match link_theme {
+                Some((color, width)) => Stroke::new(if lod { 1.0 } else { width }, color),
+                None => Stroke::new(if lod { 1.0 } else { 2.0 }, accent),
+            };
+            let pointer = ctx.pointer_interact_pos();
+            let handle_radius = (6.0 * transformer.zoom()).clamp(4.0, 10.0);
+            let mut rewire_dropped = None;
+            // Closest connection within reach of the pointer, with what it
+            // takes to draw it again highlighted.
+            let mut hovered_link: Option<(usize, f32, Anchor, Anchor, Option<f32>)> = None;
+            let hover_pos = pointer.filter(|_| response.hovered() && state.rewiring.is_none());
+            for (i, conn) in state.connections.iter().enumerate() {
+                if hidden.contains(&conn.from) || hidden.contains(&conn.to) {
+                    continue;
+                }
+                let Some((start, end)) = state.connection_anchors(conn) else {
+                    continue;
+                };
+                let (start, end) = (
+                    transformer.anchor_to_screen(start),
+                    transformer.anchor_to_screen(end),
+                );
+                let (mut drawn_start, mut drawn_end) = (start, end);
+                if let (Some(rewire), Some(p)) = (state.rewiring, pointer) {
+                    if rewire.index == i {
+                        match rewire.end {
+                            ConnectionEnd::From => drawn_start.pos = p,
+                            ConnectionEnd::To => drawn_end.pos = p,
+                        }
+                    }
+                }
+                let tension = state
+                    .settings
+                    .curved_connections
+                    .then(|| conn.tension.unwrap_or(state.settings.curve_tension));
+                let mut stroke = link_stroke;
+                if dimmed(conn.from) || dimmed(conn.to) {
+                    stroke.color = stroke.color.gamma_multiply(FOCUS_DIM);
+                }
+                painter.extend(connection_shapes(
+                    conn,
+                    drawn_start,
+                    drawn_end,
+                    tension,
+                    stroke,
+                    transformer.zoom(),
+                ));
+                if let Some(p) = hover_pos {
+                    let d = distance_to_connection(drawn_start, drawn_end, tension, p);
+                    if d <= CONNECTION_HIT_RADIUS && hovered_link.is_none_or(|h| d < h.1) {
+                        hovered_link = Some((i, d, drawn_start, drawn_end, tension));
+                    }
+                }
+                if !lod && !conn.label.is_empty() {
+                    draw_connection_label(
+                        painter,
+                        connection_midpoint(drawn_start, drawn_end, tension),
+                        &conn.label,
+                        (13.0 * transformer.zoom()).clamp(8.0, 32.0),
+                        ctx.style().visuals.text_color(),
+                        ctx.style().visuals.window_fill,
+                        Stroke::new(1.0, accent),
                     );
                 }
+                if read_only {
+                    continue;
+                }
+                for (end_kind, anchor) in [(ConnectionEnd::From, start), (ConnectionEnd::To, end)] {
+                    let handle = endpoint_handle(
+                        ui,
+                        ui.id().with(("conn_end", i, end_kind)),
+                        anchor.pos,
+                        handle_radius,
+                    );
+                    if handle.drag_started() {
+                        state.rewiring = Some(Rewire {
+                            index: i,
+                            end: end_kind,
+                        });
+                    }
+                    if handle.drag_stopped() {
+                        rewire_dropped = Some((i, end_kind));
+                    }
+                }
+            }
+            if let Some((i, _, start, end, tension)) = hovered_link.filter(|_| !read_only) {
+                let stroke = Stroke::new(link_stroke.width + 2.0, accent);
+                painter.extend(connection_shapes(
+                    &state.connections[i],
+                    start,
+                    end,
+                    tension,
+                    stroke,
+                    transformer.zoom(),
+                ));
+            }
+            if response.secondary_clicked() {
+                state.connection_menu = hovered_link
+                    .filter(|_| !read_only)
+                    .map(|h| (state.connections[h.0].from, state.connections[h.0].to));
             }
+            if let Some((index, end)) = rewire_dropped {
+                state.rewiring = None;
+                // Dropping anywhere but on an item cancels the rewire.
+                if let Some(target) =
+                    pointer.and_then(|p| state.item_at(transformer.from_screen(p), &hidden))
+                {
+                    state.rewire_connection(index, end, target);
+                }
+            }
+            if lod {
+                draw_item_boxes(painter, state, transformer, &hidden);
+            }
+            for todo in &state.todos {
+                let Some(note_id) = todo.details_note else {
+                    continue;
+                };
+                if hidden.contains(&todo.id) || hidden.contains(&note_id) {
+                    continue;
+                }
+                if let (Some(wp1), Some(wp2)) =
+                    (state.get_item_pos(todo.id), state.get_item_pos(note_id))
+                {
+                    painter.extend(egui::Shape::dashed_line(
+                        &[transformer.to_screen(wp1), transformer.to_screen(wp2)],
+                        Stroke::new(1.5, accent.gamma_multiply(0.8)),
+                        6.0,
+                        4.0,
+                    ));
+                }
+            }
+            // The item a pending connection would land on, snapped to.
+            let mut link_target = None;
             if let (Some(sid), Some(cpos)) = (state.connecting_from_id, ctx.pointer_interact_pos())
             {
-                if let Some(wpos) = state.get_item_pos(sid) {
+                link_target = state
+                    .item_at(transformer.from_screen(cpos), &hidden)
+                    .filter(|&id| id != sid);
+                let target_rect = link_target.and_then(|id| state.get_item_rect(id));
+                if let Some(from) = state.get_item_rect(sid) {
+                    let (start, end) = match target_rect {
+                        Some(to) => {
+                            let start = Anchor::on(from, None, to.center());
+                            (start.pos, Anchor::on(to, None, start.pos).pos)
+                        }
+                        None => (from.min, transformer.from_screen(cpos)),
+                    };
                     painter.line_segment(
-                        [transformer.to_screen(wpos), cpos],
+                        [transformer.to_screen(start), transformer.to_screen(end)],
                         Stroke::new(2.0, Color32::LIGHT_BLUE),
                     );
                 }
-            }
-
-            response.context_menu(|ui| {
-                if ui.button("Add Note").clicked() {
-                    let np = transformer.from_screen(
-                        ui.ctx()
-                            .pointer_interact_pos()
-                            .unwrap_or(ui.max_rect().center()),
-                    );
-                    let nid = state.next_id;
-                    state.next_id += 1;
-                    state.notes.push(Note {
-                        id: nid,
-                        position: np,
-                        text: "New note".to_string(),
-                        size: Vec2::new(200.0, 100.0),
-                    });
+            }
+
+            let enabled: HashSet<MenuAction> = MenuAction::ALL
+                .into_iter()
+                .filter(|&a| state.settings.menu_enabled(a) && !(read_only && a.edits_board()))
+                .collect();
+            response.context_menu(|ui| {
+                if let Some((from, to)) = state.connection_menu {
+                    if ui.button("Edit...").clicked() {
+                        state.editing_connection = Some((from, to));
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete connection").clicked() {
+                        state.connections.retain(|c| (c.from, c.to) != (from, to));
+                        state.connection_menu = None;
+                        state.editing_connection = None;
+                        ui.close_menu();
+                    }
+                    return;
+                }
+                let new_pos = transformer.from_screen(
+                    ui.ctx()
+                        .pointer_interact_pos()
+                        .unwrap_or(ui.max_rect().center()),
+                );
+                let bindings = &state.settings.keybindings;
+                let shortcut_text = |action| {
+                    ui.ctx()
+                        .format_shortcut(&hotkeys::binding(bindings, action))
+                };
+                let add_note = egui::Button::new("Add Note")
+                    .shortcut_text(shortcut_text(HotkeyAction::AddNote));
+                let add_todo = egui::Button::new("Add Todo")
+                    .shortcut_text(shortcut_text(HotkeyAction::AddTodo));
+                let undo = egui::Button::new("Undo last connection")
+                    .shortcut_text(shortcut_text(HotkeyAction::UndoConnection));
+                let undo_step =
+                    egui::Button::new("Undo").shortcut_text(shortcut_text(HotkeyAction::Undo));
+                let redo_step =
+                    egui::Button::new("Redo").shortcut_text(shortcut_text(HotkeyAction::Redo));
+                if enabled.contains(&MenuAction::AddNote) && ui.add(add_note).clicked() {
+                    state.add_note(new_pos);
+                    notifier.cue(Cue::Created);
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::AddTodo) && ui.add(add_todo).clicked() {
+                    state.add_todo(new_pos, now);
+                    notifier.cue(Cue::Created);
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::QuickReminder) {
+                    ui.menu_button("Quick reminder", |ui| {
+                        let mut due = None;
+                        for (label, minutes) in
+                            [("In 5 min", 5), ("In 15 min", 15), ("In 1 hour", 60)]
+                        {
+                            if ui.button(label).clicked() {
+                                due = Some(now + chrono::Duration::minutes(minutes));
+                            }
+                        }
+                        if ui.button("Tomorrow 9:00").clicked() {
+                            due = Some(tomorrow_at(now, zone, 9));
+                        }
+                        if let Some(due) = due {
+                            let nid = state.add_todo(new_pos, now);
+                            if let Some(todo) = state.todos.last_mut() {
+                                todo.text = "Reminder".to_string();
+                                todo.due = Some(due);
+                                todo.loop_freq = LoopFrequency::Once;
+                            }
+                            state.focus_item = Some(nid);
+                            notifier.cue(Cue::Created);
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if enabled.contains(&MenuAction::ShiftDue) && !state.selected.is_empty() {
+                    ui.separator();
+                    ui.menu_button("Shift due by...", |ui| {
+                        let mut by = None;
+                        if ui.button("+1 day").clicked() {
+                            by = Some(chrono::Duration::days(1));
+                        }
+                        if ui.button("+1 week").clicked() {
+                            by = Some(chrono::Duration::weeks(1));
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                DragValue::new(&mut state.shift_custom_hours)
+                                    .speed(1)
+                                    .suffix(" h"),
+                            );
+                            if ui.button("Apply").clicked() {
+                                by = Some(chrono::Duration::hours(state.shift_custom_hours));
+                            }
+                        });
+                        if let Some(by) = by {
+                            shift_due(&mut state.todos, &state.selected, by);
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if enabled.contains(&MenuAction::MoveToBoard)
+                    && !state.selected.is_empty()
+                    && ui.button("Move selection to board...").clicked()
+                {
+                    move_to_board = FileDialog::new().add_filter("Board", &["json"]).pick_file();
+                    ui.close_menu();
+                }
+                ui.separator();
+                if enabled.contains(&MenuAction::UndoRedo) {
+                    if ui.add_enabled(state.can_undo(), undo_step).clicked() {
+                        state.undo();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(state.can_redo(), redo_step).clicked() {
+                        state.redo();
+                        ui.close_menu();
+                    }
+                }
+                if enabled.contains(&MenuAction::UndoConnection)
+                    && ui
+                        .add_enabled(!state.added_connections.is_empty(), undo)
+                        .clicked()
+                {
+                    state.undo_last_connection();
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::ImportImages)
+                    && ui.button("Import images...").clicked()
+                {
+                    if let Some(files) = FileDialog::new()
+                        .add_filter("Image", &["png", "jpg", "jpeg"])
+                        .pick_files()
+                    {
+                        let (added, failed) = import_images(state, &files, new_pos);
+                        self.toasts.info(format!("Added {} image note(s)", added));
+                        if !failed.is_empty() {
+                            self.toasts
+                                .info(format!("Couldn't read {} file(s) as images", failed.len()));
+                        }
+                    }
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::ChangeBackground)
+                    && ui.button("Change Background").clicked()
+                {
+                    if let Some((path, background)) = pick_background(ctx) {
+                        state.background_image_path = Some(path);
+                        self.background = Some(background);
+                    }
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::TidyOverlaps)
+                    && ui.button("Tidy overlaps").clicked()
+                {
+                    let moved = state.tidy_overlaps();
+                    self.toasts.info(format!("Moved {} item(s)", moved));
                     ui.close_menu();
                 }
-                if ui.button("Add Todo").clicked() {
-                    let np = transformer.from_screen(
-                        ui.ctx()
-                            .pointer_interact_pos()
-                            .unwrap_or(ui.max_rect().center()),
-                    );
-                    let nid = state.next_id;
-                    state.next_id += 1;
-                    state.todos.push(Todo {
-                        id: nid,
-                        position: np,
-                        text: "New todo".to_string(),
-                        due: None,
-                        is_done: false,
-                        size: Vec2::new(200.0, 150.0),
-                        loop_freq: LoopFrequency::Once,
-                        notified: false,
-                    });
+                if enabled.contains(&MenuAction::FindDuplicates)
+                    && ui.button("Find duplicates").clicked()
+                {
+                    state.duplicates = Some(find_duplicates(state));
                     ui.close_menu();
                 }
-                ui.separator();
-                if ui.button("Change Background").clicked() {
-                    if let Some(path) = FileDialog::new()
-                        .add_filter("Image", &["png", "jpg", "jpeg"])
-                        .pick_file()
+                if enabled.contains(&MenuAction::ExportBundle)
+                    && ui.button("Export bundle...").clicked()
+                {
+                    export_to = pick_bundle_dest("board.zip").map(|dest| (dest, None));
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::ExportBundle)
+                    && (state.filter.is_active() || matches.is_some())
+                    && ui
+                        .button("Export visible...")
+                        .on_hover_text("Only the items the filter and search leave")
+                        .clicked()
+                {
+                    export_to = pick_bundle_dest("board-filtered.zip")
+                        .map(|dest| (dest, Some(visible_ids(state, matches.as_ref()))));
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::ExportImage)
+                    && ui.button("Export image...").clicked()
+                {
+                    state.image_export = Some(ImageExport::default());
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::ExportCalendar)
+                    && ui
+                        .add_enabled(
+                            exportable_count(state) > 0,
+                            egui::Button::new("Export calendar..."),
+                        )
+                        .on_disabled_hover_text("No todo has a due date")
+                        .clicked()
+                {
+                    if let Some(dest) = FileDialog::new()
+                        .add_filter("Calendar", &["ics"])
+                        .set_file_name("todos.ics")
+                        .save_file()
                     {
-                        if let Ok(image) = load_image_from_path(&path) {
-                            state.background_image_path = Some(path.display().to_string());
-                            self.background_texture =
-                                Some(ctx.load_texture("background", image, Default::default()));
+                        match std::fs::write(&dest, todos_to_ics(state, Local::now())) {
+                            Ok(()) => self.toasts.info(format!(
+                                "Exported {} todo(s) to {}",
+                                exportable_count(state),
+                                dest.display()
+                            )),
+                            Err(e) => self.toasts.info(format!("Export failed: {}", e)),
                         }
                     }
                     ui.close_menu();
                 }
+                if enabled.contains(&MenuAction::ImportBundle)
+                    && ui.button("Import bundle...").clicked()
+                {
+                    import_from = FileDialog::new().add_filter("Bundle", &["zip"]).pick_file();
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::MoveData) && ui.button("Move data to...").clicked()
+                {
+                    move_data_to = FileDialog::new().pick_folder();
+                    ui.close_menu();
+                }
+                ui.separator();
+                if enabled.contains(&MenuAction::Bookmarks) {
+                    ui.menu_button("Bookmarks", |ui| {
+                        let mut jump = None;
+                        for (i, bookmark) in state.bookmarks.iter().enumerate() {
+                            let button =
+                                egui::Button::new(&bookmark.name).shortcut_text(key_hint(i));
+                            if ui.add(button).clicked() {
+                                jump = Some(bookmark_view(state, bookmark, ctx.available_rect()));
+                            }
+                        }
+                        if !state.bookmarks.is_empty() {
+                            ui.separator();
+                        }
+                        if ui.button("Manage bookmarks...").clicked() {
+                            state.bookmarks_open = true;
+                            ui.close_menu();
+                        }
+                        if jump.is_some() {
+                            state.view_goal = jump;
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if enabled.contains(&MenuAction::ItemList) {
+                    ui.checkbox(&mut state.settings.show_list_panel, "Item list");
+                }
+                if enabled.contains(&MenuAction::ArchivePanel) && ui.button("Archive...").clicked()
+                {
+                    state.archive_open = true;
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::TrashPanel) && ui.button("Trash...").clicked() {
+                    state.trash_open = true;
+                    ui.close_menu();
+                }
+                if enabled.contains(&MenuAction::OnlyStarred) {
+                    ui.checkbox(&mut state.filter.only_starred, "Only starred");
+                }
+                if ui.button("Settings...").clicked() {
+                    state.settings_open = true;
+                    ui.close_menu();
+                }
             });
 
+            let snapping = Snapping::new(state, transformer);
+            let mut drag_guides = Guides::default();
+            let counts = if state.settings.show_connection_badges {
+                state.connection_counts()
+            } else {
+                HashMap::new()
+            };
             let mut clicked_ch: Option<usize> = None;
+            let mut pan_to: Option<usize> = None;
+            let mut delete_item = None;
+            let mut focus_on = None;
+            let mut start_timer = None;
+            let mut archive_item = None;
+            let flash = state.flash;
             for note in state.notes.iter_mut() {
-                let r = egui::Window::new(generate_title(&note.text))
-                    .id(Id::new("note").with(note.id))
-                    .default_pos(transformer.to_screen(note.position))
-                    .default_size(note.size * transformer.zoom)
+                // The focused item shows in the focus window instead.
+                if lod || hidden.contains(&note.id) || state.focus == Some(note.id) {
+                    continue;
+                }
+                let note_opacity = note.meta.opacity.clamp(MIN_OPACITY, 1.0)
+                    * if dimmed(note.id) { FOCUS_DIM } else { 1.0 };
+                let r = egui::Window::new(item_title(&note.text, note.meta.starred))
+                    .id(note_window_id(note.id))
+                    .current_pos(transformer.to_screen(note.position))
+                    .constrain(false)
+                    .interactable(!dimmed(note.id))
+                    .movable(!read_only)
+                    .resizable(!read_only)
+                    .default_size(note.size * transformer.zoom())
+                    .frame(item_frame(
+                        ctx,
+                        &ItemLook {
+                            selected: state.selected.contains(&note.id),
+                            link_target: link_target == Some(note.id),
+                            matched: matches.as_ref().is_some_and(|m| m.contains(&note.id)),
+                            starred: note.meta.starred,
+                            color: note.meta.color,
+                            fill: None,
+                            opacity: note_opacity,
+                            glow: flash_pulse(flash, note.id, time),
+                        },
+                    ))
                     .show(ctx, |ui| {
-                        if ui.button("🔗").clicked() {
-                            clicked_ch = Some(note.id);
+                        let bg = ui.interact(ui.max_rect(), ui.id().with("bg"), Sense::click());
+                        ui.set_opacity(note_opacity);
+                        bg.context_menu(|ui| {
+                            match item_context_menu(
+                                ui,
+                                &enabled,
+                                note.id,
+                                &mut note.meta,
+                                &mut state.connections,
+                                &titles,
+                                &mut pan_to,
+                            ) {
+                                Some(ItemCommand::Focus) => focus_on = Some(note.id),
+                                Some(ItemCommand::Delete) => delete_item = Some(note.id),
+                                None => {}
+                            }
+                        });
+                        ui.set_enabled(!read_only);
+                        ui.horizontal(|ui| {
+                            let link = ui.button(ICON_LINK);
+                            if link.clicked() {
+                                clicked_ch = Some(note.id);
+                            }
+                            link.context_menu(|ui| {
+                                connection_sides_menu(ui, note.id, &mut state.connections, &titles);
+                            });
+                            star_toggle(ui, &mut note.meta.starred);
+                            if ui
+                                .selectable_label(note.editing, ICON_EDIT)
+                                .on_hover_text("Edit the Markdown source")
+                                .clicked()
+                            {
+                                note.editing = !note.editing;
+                            }
+                        });
+                        tag_editor(ui, note_window_id(note.id).with("tag"), &mut note.meta.tags);
+                        if let Some(path) = &note.image {
+                            self.images.show(ui, path);
+                        }
+                        let progress = note.checklist.get(&note.text);
+                        if progress.total > 0 {
+                            ui.add(
+                                egui::ProgressBar::new(progress.fraction())
+                                    .text(format!("{}/{}", progress.done, progress.total)),
+                            );
+                        }
+                        if !note.editing || read_only {
+                            show_markdown(ui, &note.text);
+                        } else {
+                            let edit =
+                                ui.add(egui::TextEdit::multiline(&mut note.text).frame(false));
+                            if edit.changed() {
+                                note.search_cache.invalidate();
+                            }
+                            if edit.changed()
+                                && truncate_chars(&mut note.text, state.settings.note_hard_limit)
+                            {
+                                self.toasts.info(format!(
+                                    "Note cut to {} characters",
+                                    state.settings.note_hard_limit
+                                ));
+                            }
+                            // Leaving the editor goes back to the rendered view.
+                            if edit.lost_focus() {
+                                note.editing = false;
+                            }
+                            length_counter(ui, &note.text, state.settings.note_soft_limit);
                         }
-                        ui.add(egui::TextEdit::multiline(&mut note.text).frame(false));
                     });
                 if let Some(r) = r {
+                    toggle_selection_on_click(ctx, &r.response, note.id, &mut state.selected);
+                    note.meta.collapsed = window_collapsed(ctx, note_window_id(note.id));
+                    if let Some(&count) = counts.get(&note.id) {
+                        draw_count_badge(ctx, &r.response, count);
+                    }
                     note.position = transformer.from_screen(r.response.rect.min);
-                    note.size = r.response.rect.size() / transformer.zoom;
+                    let rect = Rect::from_min_size(note.position, note.size);
+                    let (guides, landed) = snapping.drag(note.id, &r.response, rect);
+                    if guides.x.is_some() || guides.y.is_some() {
+                        drag_guides = guides;
+                    }
+                    note.position = landed.unwrap_or(note.position);
+                    // Keep the expanded size while collapsed so it survives a restart.
+                    if !note.meta.collapsed {
+                        note.size = r.response.rect.size() / transformer.zoom();
+                    }
                 }
             }
+            let todo_titles: Vec<(usize, String)> = state
+                .todos
+                .iter()
+                .map(|t| (t.id, generate_title(&t.text)))
+                .collect();
             for todo in state.todos.iter_mut() {
-                let r = egui::Window::new(generate_title(&todo.text))
-                    .id(Id::new("todo").with(todo.id))
-                    .default_pos(transformer.to_screen(todo.position))
-                    .default_size(todo.size * transformer.zoom)
+                if lod || hidden.contains(&todo.id) || state.focus == Some(todo.id) {
+                    continue;
+                }
+                let blocked_by = blockers.get(&todo.id);
+                let is_blocked = blocked_by.is_some();
+                // Blocked todos fade back so the ones that can be worked on stand out.
+                let todo_opacity = todo.meta.opacity.clamp(MIN_OPACITY, 1.0)
+                    * if is_blocked { 0.5 } else { 1.0 }
+                    * if dimmed(todo.id) { FOCUS_DIM } else { 1.0 };
+                let mut title = item_title(&todo.text, todo.meta.starred);
+                let progress = subtask_progress(&todo.subtasks);
+                if progress.total > 0 {
+                    title = format!("{} ({}/{})", title, progress.done, progress.total);
+                }
+                if let Some(session) = state.pomodoro.filter(|p| p.todo == todo.id) {
+                    title = format!("{} \u{2014} {}", title, session.label(now));
+                }
+                let r = egui::Window::new(title)
+                    .id(todo_window_id(todo.id))
+                    .current_pos(transformer.to_screen(todo.position))
+                    .constrain(false)
+                    .interactable(!dimmed(todo.id))
+                    .movable(!read_only)
+                    .resizable(!read_only)
+                    .default_size(todo.size * transformer.zoom())
+                    .frame(item_frame(
+                        ctx,
+                        &ItemLook {
+                            selected: state.selected.contains(&todo.id),
+                            link_target: link_target == Some(todo.id),
+                            matched: matches.as_ref().is_some_and(|m| m.contains(&todo.id)),
+                            starred: todo.meta.starred,
+                            color: todo.meta.color,
+                            fill: todo_fill,
+                            opacity: todo_opacity,
+                            glow: flash_pulse(flash, todo.id, time).or(glow.filter(|_| {
+                                !is_blocked && is_due_soon(todo, view_now, due_soon_window)
+                            })),
+                        },
+                    ))
                     .show(ctx, |ui| {
+                        let mut toggled = None;
+                        let bg = ui.interact(ui.max_rect(), ui.id().with("bg"), Sense::click());
+                        ui.set_opacity(todo_opacity);
+                        bg.context_menu(|ui| {
+                            match item_context_menu(
+                                ui,
+                                &enabled,
+                                todo.id,
+                                &mut todo.meta,
+                                &mut state.connections,
+                                &titles,
+                                &mut pan_to,
+                            ) {
+                                Some(ItemCommand::Focus) => focus_on = Some(todo.id),
+                                Some(ItemCommand::Delete) => delete_item = Some(todo.id),
+                                None => {}
+                            }
+                            if enabled.contains(&MenuAction::Snooze) && !todo.is_done {
+                                ui.menu_button("Snooze", |ui| {
+                                    if let Some(preset) = snooze_menu(ui) {
+                                        todo.snooze(preset.until(view_now, zone));
+                                    }
+                                });
+                            }
+                            if enabled.contains(&MenuAction::Archive)
+                                && todo.is_done
+                                && ui.button("Archive").clicked()
+                            {
+                                archive_item = Some(todo.id);
+                                ui.close_menu();
+                            }
+                        });
+                        ui.set_enabled(!read_only);
                         ui.horizontal(|ui| {
-                            if ui.button("🔗").clicked() {
+                            let link = ui.button(ICON_LINK);
+                            if link.clicked() {
                                 clicked_ch = Some(todo.id);
                             }
-                            ui.checkbox(&mut todo.is_done, "");
-                            ui.add(egui::TextEdit::singleline(&mut todo.text).frame(false));
+                            link.context_menu(|ui| {
+                                connection_sides_menu(ui, todo.id, &mut state.connections, &titles);
+                            });
+                            star_toggle(ui, &mut todo.meta.starred);
+                            let mut done = todo.is_done;
+                            let check = ui.checkbox(&mut done, "");
+                            if check.changed() {
+                                toggled = Some(done);
+                            }
+                            if let Some(done_at) = todo.done_at {
+                                check.on_hover_text(format!(
+                                    "Done at {}",
+                                    done_at.format("%Y-%m-%d %H:%M")
+                                ));
+                            }
+                            let text =
+                                ui.add(egui::TextEdit::singleline(&mut todo.text).frame(false));
+                            if text.changed() {
+                                todo.search_cache.invalidate();
+                            }
+                            if state.focus_item == Some(todo.id) {
+                                text.request_focus();
+                                state.focus_item = None;
+                            }
                         });
+                        if let Some(ids) = blocked_by {
+                            let names: Vec<&str> = ids
+                                .iter()
+                                .map(|id| titles.get(id).map_or("?", String::as_str))
+                                .collect();
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("Blocked by {}", names.join(", ")),
+                            );
+                        }
+                        tag_editor(ui, todo_window_id(todo.id).with("tag"), &mut todo.meta.tags);
+                        if subtask_list(ui, &mut todo.subtasks) && !todo.subtasks.is_empty() {
+                            // The parent follows its checklist: done once every
+                            // subtask is, open again when one is unticked.
+                            let all_done = todo.subtasks.iter().all(|s| s.done);
+                            if all_done != todo.is_done {
+                                toggled = Some(all_done);
+                            }
+                        }
+                        // Finishing a blocked todo early needs confirming.
+                        if toggled == Some(true) && is_blocked {
+                            state.confirm_early_done = Some(todo.id);
+                            toggled = None;
+                        }
+                        if let Some(done) = toggled {
+                            todo.set_done(done, Local::now());
+                            if done {
+                                notifier.cue(Cue::Completed);
+                                completed.push(todo.id);
+                                move_to_done_lane(todo, &state.lanes);
+                            }
+                        }
                         ui.separator();
                         let mut dc = false;
-                        let mut td = todo.due.unwrap_or_else(Local::now);
+                        let mut wall = zone.wall_clock(todo.due.unwrap_or_else(Local::now));
                         ui.horizontal(|ui| {
                             ui.label("Due:");
-                            let mut d = td.date_naive();
+                            let mut d = wall.date();
                             if ui.add(DatePickerButton::new(&mut d)).changed() {
-                                if let Some(nt) =
-                                    d.and_time(td.time()).and_local_timezone(Local).single()
-                                {
-                                    td = nt;
-                                    dc = true;
-                                }
+                                wall = d.and_time(wall.time());
+                                dc = true;
                             }
-                            let mut h = td.hour();
-                            let mut m = td.minute();
+                            let mut h = wall.hour();
+                            let mut m = wall.minute();
                             if ui
                                 .add(DragValue::new(&mut h).speed(1).clamp_range(0..=23))
                                 .changed()
@@ -411,16 +3503,38 @@ impl App for EndlessCanvasApp {
                                     .add(DragValue::new(&mut m).speed(1).clamp_range(0..=59))
                                     .changed()
                             {
-                                if let (Some(hr), Some(min)) = (td.with_hour(h), td.with_minute(m))
-                                {
-                                    td = hr.with_minute(min.minute()).unwrap_or(td);
+                                if let Some(t) = wall.with_hour(h).and_then(|w| w.with_minute(m)) {
+                                    wall = t;
                                     dc = true;
                                 }
                             }
+                            if let Some(name) = zone.name() {
+                                ui.weak(name);
+                            }
+                            if !todo.is_done && todo.due.is_some_and(|d| d < view_now) {
+                                ui.colored_label(Color32::LIGHT_RED, "Overdue");
+                            }
+                            if !todo.is_done && todo.due.is_some() {
+                                ui.menu_button("Snooze", |ui| {
+                                    if let Some(preset) = snooze_menu(ui) {
+                                        todo.snooze(preset.until(view_now, zone));
+                                    }
+                                });
+                            }
                         });
+                        if !read_only {
+                            ui.horizontal(|ui| {
+                                ui.label("Type:");
+                                let id = Id::new("due_text").with(todo.id);
+                                if let Some(due) = due_text_field(ui, id, view_now, zone) {
+                                    wall = zone.wall_clock(due);
+                                    dc = true;
+                                }
+                            });
+                        }
                         if dc {
-                            todo.due = Some(td);
-                            todo.notified = false;
+                            todo.due = Some(zone.instant_at(wall));
+                            todo.rearm_reminders();
                         }
                         egui::ComboBox::from_label("Frequency")
                             .selected_text(format!("{:?}", todo.loop_freq))
@@ -429,30 +3543,400 @@ impl App for EndlessCanvasApp {
                                     ui.selectable_value(&mut todo.loop_freq, f, format!("{:?}", f));
                                 }
                             });
+                        if todo.loop_freq == LoopFrequency::Custom {
+                            ui.indent("recurrence", |ui| {
+                                recurrence_editor(
+                                    ui,
+                                    Id::new("recurrence").with(todo.id),
+                                    &mut todo.recurrence,
+                                    todo.due.map(|d| zone.wall_clock(d).date()),
+                                );
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Remind before:");
+                            remind_before_picker(
+                                ui,
+                                todo.id,
+                                &mut todo.remind_before,
+                                state.settings.remind_before_minutes,
+                            );
+                        });
+                        egui::ComboBox::from_label("Priority")
+                            .selected_text(format!("{:?}", todo.priority))
+                            .show_ui(ui, |ui| {
+                                for p in Priority::ALL {
+                                    ui.selectable_value(&mut todo.priority, p, format!("{:?}", p));
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            match state.pomodoro.filter(|p| p.todo == todo.id) {
+                                Some(session) => {
+                                    ui.label(session.label(now));
+                                    if ui.button("Stop").clicked() {
+                                        state.pomodoro = None;
+                                    }
+                                }
+                                None if !todo.is_done => {
+                                    let hint = format!(
+                                        "{} min of focus, then a {} min break, until stopped",
+                                        WORK_MINUTES, BREAK_MINUTES
+                                    );
+                                    if ui
+                                        .button("Start focus session")
+                                        .on_hover_text(hint)
+                                        .clicked()
+                                    {
+                                        state.pomodoro = Some(Pomodoro::start(todo.id, now));
+                                    }
+                                }
+                                None => {}
+                            }
+                            if !todo.pomodoros.is_empty() {
+                                ui.weak(format!("{} session(s) done", todo.pomodoros.len()));
+                            }
+                        });
+                        match tracking_row(ui, todo, now) {
+                            Some(true) => start_timer = Some(todo.id),
+                            Some(false) => todo.stop_tracking(now),
+                            None => {}
+                        }
+                        streak_strip(
+                            ui,
+                            &todo.history,
+                            todo.loop_freq,
+                            &todo.recurrence,
+                            view_now,
+                            zone,
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label("Details:");
+                            let selected = todo
+                                .details_note
+                                .and_then(|id| titles.get(&id))
+                                .map_or("None", String::as_str);
+                            egui::ComboBox::from_id_source(Id::new("details_note").with(todo.id))
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut todo.details_note, None, "None");
+                                    for note in &state.notes {
+                                        ui.selectable_value(
+                                            &mut todo.details_note,
+                                            Some(note.id),
+                                            generate_title(&note.text),
+                                        );
+                                    }
+                                });
+                            if todo.details_note.is_some() && ui.button("Open").clicked() {
+                                pan_to = todo.details_note;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Waiting on:");
+                            let selected = todo
+                                .waiting_on
+                                .and_then(|id| titles.get(&id))
+                                .map_or("Nothing", String::as_str);
+                            egui::ComboBox::from_id_source(Id::new("waiting_on").with(todo.id))
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut todo.waiting_on, None, "Nothing");
+                                    for (id, title) in &todo_titles {
+                                        if *id != todo.id {
+                                            ui.selectable_value(
+                                                &mut todo.waiting_on,
+                                                Some(*id),
+                                                title,
+                                            );
+                                        }
+                                    }
+                                });
+                        });
+                        if let Some(note) = todo
+                            .details_note
+                            .and_then(|id| state.notes.iter().find(|n| n.id == id))
+                        {
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(details_preview(&note.text)).weak(),
+                                )
+                                .wrap(true),
+                            );
+                        }
                     });
                 if let Some(r) = r {
+                    toggle_selection_on_click(ctx, &r.response, todo.id, &mut state.selected);
+                    todo.meta.collapsed = window_collapsed(ctx, todo_window_id(todo.id));
+                    if let Some(color) = todo.priority.color() {
+                        draw_title_tint(ctx, &r.response, color.gamma_multiply(todo_opacity));
+                    }
+                    if let Some(&count) = counts.get(&todo.id) {
+                        draw_count_badge(ctx, &r.response, count);
+                    }
                     todo.position = transformer.from_screen(r.response.rect.min);
-                    todo.size = r.response.rect.size() / transformer.zoom;
+                    let rect = Rect::from_min_size(todo.position, todo.size);
+                    let (guides, landed) = snapping.drag(todo.id, &r.response, rect);
+                    if guides.x.is_some() || guides.y.is_some() {
+                        drag_guides = guides;
+                    }
+                    todo.position = landed.unwrap_or(todo.position);
+                    // Keep the expanded size while collapsed so it survives a restart.
+                    if !todo.meta.collapsed {
+                        todo.size = r.response.rect.size() / transformer.zoom();
+                    }
+                    if r.response.drag_stopped() {
+                        if let Some(done) = drop_into_lanes(todo, &state.lanes) {
+                            todo.set_done(done, now);
+                            if done {
+                                notifier.cue(Cue::Completed);
+                                completed.push(todo.id);
+                            }
+                        }
+                    }
+                }
+            }
+            draw_guides(
+                &ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    Id::new("guides"),
+                ))
+                .with_clip_rect(response.rect),
+                drag_guides,
+                transformer,
+                ctx.style().visuals.selection.bg_fill,
+            );
+            if let Some(id) = pan_to {
+                state.center_on(id, ctx.available_rect());
+            }
+            if let Some(id) = delete_item {
+                state.delete_items(&HashSet::from([id]), now);
+            }
+            if let Some(id) = focus_on {
+                state.focus = Some(id);
+                state.focus_item = Some(id);
+            }
+            if let Some(id) = start_timer {
+                state.start_tracking(id, now);
+            }
+            if let Some(id) = archive_item {
+                if state.archive_todo(id) {
+                    self.toasts.info("Archived 1 todo");
                 }
             }
+            if focus_window(ctx, state, read_only) {
+                self.toasts.info(format!(
+                    "Note cut to {} characters",
+                    state.settings.note_hard_limit
+                ));
+            }
             if let Some(cid) = clicked_ch {
                 if let Some(sid) = state.connecting_from_id.take() {
                     if sid != cid {
-                        state.connections.push((sid, cid));
+                        state.link_clicked(sid, cid);
                     }
                 } else {
                     state.connecting_from_id = Some(cid);
                 }
             }
+            if state.settings.show_hud {
+                let pointer_world = ctx.pointer_hover_pos().map(|p| transformer.from_screen(p));
+                show_hud(ctx, state.zoom, pointer_world, state.settings.hud_precision);
+            }
+            if state.settings.show_minimap && state.focus.is_none() {
+                minimap(ctx, state, response.rect, &hidden);
+            }
         });
+        match kanban_action {
+            Some(KanbanAction::Move(id, Column::Done)) => {
+                if blockers.contains_key(&id) {
+                    state.confirm_early_done = Some(id);
+                } else if let Some(todo) = state.todos.iter_mut().find(|t| t.id == id && !t.is_done)
+                {
+                    todo.set_done(true, now);
+                    notifier.cue(Cue::Completed);
+                    completed.push(id);
+                    move_to_done_lane(todo, &state.lanes);
+                }
+            }
+            Some(KanbanAction::Move(id, column)) => {
+                state.move_to_column(id, column, view_now);
+            }
+            Some(KanbanAction::Show(id)) => {
+                state.settings.view_mode = ViewMode::Canvas;
+                state.jump_to(id, ctx.available_rect(), time);
+            }
+            None => {}
+        }
+        match calendar_action {
+            Some(CalendarAction::Reschedule(id, days)) => state.reschedule(id, days),
+            Some(CalendarAction::Show(id)) => {
+                state.settings.view_mode = ViewMode::Canvas;
+                state.jump_to(id, ctx.available_rect(), time);
+            }
+            None => {}
+        }
+        if let Some(id) = timeline_clicked {
+            state.settings.view_mode = ViewMode::Canvas;
+            state.jump_to(id, ctx.available_rect(), time);
+        }
+        if let Some(id) = confirm_early_done(ctx, state, &titles) {
+            if let Some(todo) = state.todos.iter_mut().find(|t| t.id == id) {
+                todo.set_done(true, now);
+                notifier.cue(Cue::Completed);
+                completed.push(id);
+                move_to_done_lane(todo, &state.lanes);
+            }
+        }
+        for id in completed {
+            for text in state.unblock_waiting_on(id, now) {
+                notifier.unblocked(&text);
+            }
+            // After unblocking, which needs the todo still done.
+            if state.settings.repeat_on_completion {
+                if let Some(todo) = state.todos.iter_mut().find(|t| t.id == id) {
+                    todo.start_next_cycle(zone, now);
+                }
+            }
+        }
+        let data_dir = self.storage_path.as_deref().and_then(Path::parent);
+        if let Some(dir) = settings_window(ctx, state, data_dir) {
+            move_data_to = Some(dir);
+        }
+        if !read_only {
+            duplicates_window(ctx, state);
+            connection_editor(
+                ctx,
+                &mut state.connections,
+                &mut state.editing_connection,
+                &titles,
+            );
+        }
+        if let Some((format, scale)) = image_export_window(ctx, state) {
+            let ext = format.extension();
+            export_image_to = FileDialog::new()
+                .add_filter(ext.to_uppercase(), &[ext])
+                .set_file_name(format!("board.{}", ext))
+                .save_file()
+                .map(|dest| (dest, format, scale, ExportStyle::from_ctx(ctx, accent)));
+        }
+        if let Some(id) = trash_window(ctx, state, read_only) {
+            state.settings.view_mode = ViewMode::Canvas;
+            state.jump_to(id, ctx.available_rect(), time);
+        }
+        if let Some(id) = archive_window(ctx, state, read_only) {
+            state.settings.view_mode = ViewMode::Canvas;
+            state.jump_to(id, ctx.available_rect(), time);
+        }
+        if let Some(i) = bookmarks_window(ctx, state, read_only) {
+            let view = bookmark_view(state, &state.bookmarks[i], ctx.available_rect());
+            state.view_goal = Some(view);
+        }
+        lock_banner(ctx, state);
+        simulated_time_banner(ctx, state);
+        if !state.settings.raise_on_interaction {
+            keep_window_order(ctx);
+        }
+        if edit_may_have_ended(ctx) {
+            state.checkpoint();
+        }
+        let background_path = state.background_image_path.clone();
+        drop(guard);
+
+        if !self.storage_writable
+            && unwritable_banner(ctx, self.storage_path.as_deref().and_then(Path::parent))
+        {
+            move_data_to = FileDialog::new().pick_folder();
+        }
+
+        if let Some((file, reload)) = stale_files_banner(ctx, &stale_files) {
+            if reload && background_path.as_deref().map(Path::new) == Some(file.as_path()) {
+                self.background = load_background(ctx, &file);
+            }
+            if reload {
+                self.images.forget(&file);
+            }
+            self.file_watch.acknowledge(&file);
+        }
+
+        if save_now {
+            self.save_state();
+            self.toasts.info(if self.storage_writable {
+                "Saved"
+            } else {
+                "Could not save"
+            });
+        }
+        if open_capture {
+            self.quick_capture.get_or_insert_with(QuickCapture::default);
+        }
+        if let Some(name) = switch_to {
+            self.switch_board(name, ctx);
+        }
+        if let Some(dir) = move_data_to {
+            self.move_data_to(&dir);
+        }
+        if let Some((dest, only)) = export_to {
+            self.export_bundle_to(&dest, only.as_ref());
+        }
+        if let Some((dest, format, scale, style)) = export_image_to {
+            self.export_image_to(&dest, format, scale, style);
+        }
+        if let Some(src) = import_from {
+            self.import_bundle_from(&src, ctx);
+        }
+        if let Some(target) = move_to_board {
+            self.move_selection_to_board(target);
+        }
+        self.confirm_board_move(ctx);
+        self.confirm_delete_old_data(ctx);
+        self.toasts.show(ctx);
     }
 
     fn save(&mut self, _storage: &mut dyn eframe::Storage) {
         self.save_state();
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(main) = &self.storage_path {
+            _ = std::fs::remove_file(running_file(main));
+        }
+    }
+
+    fn auto_save_interval(&self) -> Duration {
+        let seconds = self.state.lock().unwrap().settings.autosave_seconds;
+        Duration::from_secs(seconds.max(5) as u64)
+    }
+}
+
+/// `--check-reminders`: fire due reminders from every saved board and exit,
+/// so an external scheduler can deliver them while the app is closed. Never
+/// touches egui/eframe, and does nothing while the app is running, as it
+/// would save over what this writes. Returns the number of reminders fired.
+fn check_reminders_headless() -> usize {
+    let Some(main) = EndlessCanvasApp::get_storage_path() else {
+        return 0;
+    };
+    let settings = Settings::load(&main);
+    if app_running(&main, settings.reminder_poll_seconds) {
+        return 0;
+    }
+    let notifier = Notifier::new(&settings);
+    let mut count = 0;
+    for (name, path) in board_files(&main) {
+        // Nothing is left running to act on buttons, so none are offered.
+        for reminder in fire_board_file_reminders(&path, &settings, Local::now()) {
+            notifier.due(&on_board(reminder, name.as_deref()), None);
+            count += 1;
+        }
+    }
+    count
 }
 
 fn main() -> Result<(), eframe::Error> {
+    if std::env::args().any(|a| a == "--check-reminders") {
+        let fired = check_reminders_headless();
+        std::process::exit(fired.min(255) as i32);
+    }
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1280.0, 720.0]),
         ..Default::default()
@@ -463,3 +3947,488 @@ fn main() -> Result<(), eframe::Error> {
         Box::new(|cc| Box::new(EndlessCanvasApp::new(cc))),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::scratch_dir;
+
+    fn at(text: &str) -> DateTime<Local> {
+        let wall = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        BoardZone::Local.instant_at(wall)
+    }
+
+    #[test]
+    fn set_done_records_and_clears_done_at() {
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        let now = at("2026-03-11 15:00");
+        todo.set_done(true, now);
+        assert!(todo.is_done);
+        assert_eq!(todo.done_at, Some(now));
+        assert_eq!(todo.history, vec![now]);
+
+        // Ticking an already done todo keeps the first completion.
+        todo.set_done(true, at("2026-03-11 16:00"));
+        assert_eq!(todo.done_at, Some(now));
+        assert_eq!(todo.history, vec![now]);
+
+        todo.set_done(false, at("2026-03-11 17:00"));
+        assert!(!todo.is_done);
+        assert_eq!(todo.done_at, None);
+        assert!(todo.history.is_empty());
+    }
+
+    #[test]
+    fn shifting_due_dates_rearms_reminders() {
+        let mut todos: Vec<Todo> = (0..3).map(|id| Todo::new(id, Pos2::ZERO)).collect();
+        todos[0].due = Some(at("2026-03-10 08:00"));
+        todos[0].notified = true;
+        todos[0].early_notified = todos[0].due;
+        todos[1].due = Some(at("2026-03-10 12:00"));
+        todos[1].snoozed_until = Some(at("2026-03-10 09:00"));
+        let selected = HashSet::from([0, 1, 2]);
+        assert_eq!(shift_due(&mut todos, &selected, chrono::Duration::days(1)), 2);
+        assert_eq!(todos[0].due, Some(at("2026-03-11 08:00")));
+        assert!(!todos[0].notified);
+        assert_eq!(todos[0].early_notified, None);
+        assert_eq!(todos[1].snoozed_until, None);
+        assert_eq!(todos[2].due, None);
+        // Nothing goes off at the old snooze time.
+        let now = at("2026-03-10 09:30");
+        let fired = fire_due_reminders(&mut todos, &[], now, BoardZone::Local, 0, false);
+        assert!(fired.is_empty());
+    }
+
+    #[test]
+    fn unticking_a_repeat_keeps_earlier_cycles() {
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        todo.loop_freq = LoopFrequency::Daily;
+        todo.due = Some(at("2026-03-10 08:00"));
+        let first = at("2026-03-10 09:00");
+        todo.set_done(true, first);
+        assert!(todo.start_next_cycle(BoardZone::Local, first));
+        assert_eq!(todo.done_at, None);
+        todo.set_done(true, at("2026-03-11 09:00"));
+        todo.set_done(false, at("2026-03-11 10:00"));
+        assert_eq!(todo.history, vec![first]);
+    }
+
+    fn new_york() -> BoardZone {
+        BoardZone::Named(chrono_tz::America::New_York)
+    }
+
+    fn wall_at(zone: BoardZone, text: &str) -> DateTime<Local> {
+        zone.instant_at(NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap())
+    }
+
+    /// The next `count` occurrences of a todo due at `due`, as New York
+    /// wall-clock text.
+    fn occurrences(freq: LoopFrequency, due: &str, count: usize) -> Vec<String> {
+        let zone = new_york();
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        todo.loop_freq = freq;
+        let mut current = wall_at(zone, due);
+        (0..count)
+            .map(|_| {
+                current = todo.next_occurrence(current, zone, current).unwrap();
+                zone.wall_clock(current).format("%Y-%m-%d %H:%M").to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn daily_repeat_keeps_its_time_across_dst() {
+        // Clocks go forward on 2026-03-08 and back on 2026-11-01.
+        assert_eq!(
+            occurrences(LoopFrequency::Daily, "2026-03-07 09:00", 2),
+            ["2026-03-08 09:00", "2026-03-09 09:00"]
+        );
+        assert_eq!(
+            occurrences(LoopFrequency::Daily, "2026-10-31 09:00", 2),
+            ["2026-11-01 09:00", "2026-11-02 09:00"]
+        );
+    }
+
+    #[test]
+    fn repeat_inside_the_spring_gap_is_not_skipped() {
+        // 02:30 doesn't exist on 2026-03-08; that day's occurrence is 03:30,
+        // and the day after is back at 02:30.
+        assert_eq!(
+            occurrences(LoopFrequency::Daily, "2026-03-07 02:30", 3),
+            ["2026-03-08 03:30", "2026-03-09 02:30", "2026-03-10 02:30"]
+        );
+        assert_eq!(
+            occurrences(LoopFrequency::Sunday, "2026-03-01 02:30", 2),
+            ["2026-03-08 03:30", "2026-03-15 02:30"]
+        );
+    }
+
+    #[test]
+    fn stale_time_anchor_is_ignored() {
+        // Moved to 10:00 by hand after the gap remembered 02:30.
+        let zone = new_york();
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        todo.loop_freq = LoopFrequency::Daily;
+        todo.repeat_time = NaiveTime::from_hms_opt(2, 30, 0);
+        let due = wall_at(zone, "2026-03-08 10:00");
+        let next = todo.next_occurrence(due, zone, due).unwrap();
+        assert_eq!(next, wall_at(zone, "2026-03-09 10:00"));
+    }
+
+    #[test]
+    fn repeat_inside_the_fall_overlap_happens_once() {
+        // 01:30 happens twice on 2026-11-01; only the first one counts.
+        let zone = new_york();
+        let daily = |from| get_next_due(&from, LoopFrequency::Daily, zone, from, None, None);
+        let before = wall_at(zone, "2026-10-31 01:30");
+        let first = daily(before).unwrap();
+        assert_eq!(first - before, chrono::Duration::hours(24));
+        let second = daily(first).unwrap();
+        assert_eq!(second - first, chrono::Duration::hours(25));
+        assert_eq!(
+            zone.wall_clock(second).format("%Y-%m-%d %H:%M").to_string(),
+            "2026-11-02 01:30"
+        );
+    }
+
+    #[test]
+    fn weekly_repeat_keeps_its_time_across_dst() {
+        assert_eq!(
+            occurrences(LoopFrequency::Weekly, "2026-03-02 18:00", 2),
+            ["2026-03-09 18:00", "2026-03-16 18:00"]
+        );
+        assert_eq!(
+            occurrences(LoopFrequency::Sunday, "2026-10-25 01:30", 2),
+            ["2026-11-01 01:30", "2026-11-08 01:30"]
+        );
+    }
+
+    #[test]
+    fn writable_dir_is_probed_cleanly() {
+        let dir = scratch_dir("writable");
+        assert!(dir_writable(&dir));
+        assert!(dir_writable(&dir.join("not/made/yet")));
+        assert!(!dir.join(".write_probe").exists());
+        _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dir_under_a_file_is_not_writable() {
+        let dir = scratch_dir("under_file");
+        let file = dir.join("file");
+        std::fs::write(&file, b"").unwrap();
+        assert!(!dir_writable(&file.join("data")));
+        _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_only_dir_is_not_writable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("read_only");
+        let locked = dir.join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+        // Root writes through permissions, so there is nothing to check.
+        let enforced = std::fs::write(locked.join("try"), b"").is_err();
+        if enforced {
+            assert!(!dir_writable(&locked));
+            let path = locked.join("app_state.json");
+            assert!(write_state(&path, &AppState::default()).is_err());
+        }
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Fires every board saved next to `main` as the reminder thread does
+    /// for those not open, returning what was shown.
+    fn fire_saved_boards(main: &Path, now: DateTime<Local>) -> Vec<String> {
+        let mut shown = Vec::new();
+        for (name, path) in board_files(main) {
+            for reminder in fire_board_file_reminders(&path, &Settings::default(), now) {
+                shown.push(on_board(reminder, name.as_deref()).text);
+            }
+        }
+        shown
+    }
+
+    #[test]
+    fn saved_boards_fire_their_reminders() {
+        let dir = scratch_dir("saved-boards");
+        let main = dir.join(STATE_FILE);
+        let now = at("2026-03-10 09:00");
+        for name in [None, Some("work")] {
+            let mut board = AppState::default();
+            board.add_todo(Pos2::ZERO, now);
+            board.todos[0].text = "Call".into();
+            board.todos[0].due = Some(at("2026-03-10 08:00"));
+            write_state(&board_file(&main, name), &board).unwrap();
+        }
+        assert_eq!(fire_saved_boards(&main, now), ["Call", "[work] Call"]);
+        // Saved as notified, so they are not shown again.
+        assert_eq!(fire_saved_boards(&main, now), Vec::<String>::new());
+    }
+
+    #[test]
+    fn saved_boards_are_saved_when_a_repeat_moves_on() {
+        let dir = scratch_dir("repeat-moves-on");
+        let main = dir.join(STATE_FILE);
+        let mut board = AppState::default();
+        board.add_todo(Pos2::ZERO, at("2026-03-10 07:00"));
+        board.todos[0].loop_freq = LoopFrequency::Daily;
+        board.todos[0].due = Some(at("2026-03-10 08:00"));
+        board.todos[0].set_done(true, at("2026-03-10 07:30"));
+        write_state(&main, &board).unwrap();
+        // The next cycle starts, due tomorrow, so nothing is shown.
+        assert_eq!(fire_saved_boards(&main, at("2026-03-10 09:00")), Vec::<String>::new());
+        let saved = EndlessCanvasApp::from_disk(Some(&main)).unwrap();
+        assert!(!saved.todos[0].is_done);
+        assert_eq!(saved.todos[0].due, Some(at("2026-03-11 08:00")));
+    }
+
+    #[test]
+    fn running_app_is_seen_until_its_file_goes_stale() {
+        let dir = scratch_dir("running");
+        let main = dir.join(STATE_FILE);
+        assert!(!app_running(&main, 15));
+        std::fs::write(running_file(&main), "1").unwrap();
+        assert!(app_running(&main, 15));
+        let file = std::fs::File::options().write(true).open(running_file(&main)).unwrap();
+        file.set_modified(std::time::SystemTime::now() - Duration::from_secs(60)).unwrap();
+        assert!(!app_running(&main, 15));
+        assert!(app_running(&main, 60));
+        assert!(list_boards(&main).is_empty());
+    }
+
+    fn utc() -> BoardZone {
+        BoardZone::Named(chrono_tz::UTC)
+    }
+
+    /// `get_next_due` from `due` at `now`, both and the result as UTC text.
+    fn next_due(freq: LoopFrequency, due: &str, now: &str) -> Option<String> {
+        let zone = utc();
+        let (due, now) = (wall_at(zone, due), wall_at(zone, now));
+        get_next_due(&due, freq, zone, now, None, None)
+            .map(|t| zone.wall_clock(t).format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn next_due_for_every_frequency() {
+        // 2026-03-11 is a Wednesday.
+        let due = "2026-03-11 09:00";
+        let now = "2026-03-11 10:00";
+        let expected = [
+            (LoopFrequency::Once, None),
+            (LoopFrequency::Custom, None),
+            (LoopFrequency::Daily, Some("2026-03-12 09:00")),
+            (LoopFrequency::Weekly, Some("2026-03-18 09:00")),
+            (LoopFrequency::Monthly, Some("2026-04-11 09:00")),
+            (LoopFrequency::Yearly, Some("2027-03-11 09:00")),
+            (LoopFrequency::Sunday, Some("2026-03-15 09:00")),
+            (LoopFrequency::Monday, Some("2026-03-16 09:00")),
+            (LoopFrequency::Tuesday, Some("2026-03-17 09:00")),
+            (LoopFrequency::Wednesday, Some("2026-03-18 09:00")),
+            (LoopFrequency::Thursday, Some("2026-03-12 09:00")),
+            (LoopFrequency::Friday, Some("2026-03-13 09:00")),
+            (LoopFrequency::Saturday, Some("2026-03-14 09:00")),
+        ];
+        assert_eq!(expected.len(), LoopFrequency::ALL.len());
+        for (freq, next) in expected {
+            assert_eq!(next_due(freq, due, now).as_deref(), next, "{freq:?}");
+        }
+    }
+
+    #[test]
+    fn once_returns_none_even_when_not_yet_due() {
+        assert_eq!(next_due(LoopFrequency::Once, "2026-03-11 09:00", "2026-03-01 09:00"), None);
+    }
+
+    #[test]
+    fn next_due_is_always_after_now() {
+        // Long overdue: skips every missed occurrence in one go.
+        let due = "2026-01-01 09:00";
+        for (freq, next) in [
+            (LoopFrequency::Daily, "2026-03-12 09:00"),
+            (LoopFrequency::Weekly, "2026-03-12 09:00"),
+            (LoopFrequency::Monthly, "2026-04-01 09:00"),
+            (LoopFrequency::Friday, "2026-03-13 09:00"),
+        ] {
+            assert_eq!(next_due(freq, due, "2026-03-11 10:00").as_deref(), Some(next), "{freq:?}");
+        }
+        // An occurrence exactly at `now` counts as passed.
+        assert_eq!(
+            next_due(LoopFrequency::Daily, "2026-03-11 09:00", "2026-03-12 09:00").as_deref(),
+            Some("2026-03-13 09:00")
+        );
+    }
+
+    #[test]
+    fn monthly_repeat_stays_on_its_day() {
+        let zone = utc();
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        todo.loop_freq = LoopFrequency::Monthly;
+        let mut due = wall_at(zone, "2026-01-31 09:00");
+        let mut days = Vec::new();
+        for _ in 0..5 {
+            due = todo.next_occurrence(due, zone, due).unwrap();
+            days.push(zone.wall_clock(due).format("%m-%d").to_string());
+        }
+        // Short months clamp to their last day; the 31st comes back after.
+        assert_eq!(days, ["02-28", "03-31", "04-30", "05-31", "06-30"]);
+    }
+
+    #[test]
+    fn yearly_repeat_on_leap_day() {
+        let zone = utc();
+        let mut todo = Todo::new(1, Pos2::ZERO);
+        todo.loop_freq = LoopFrequency::Yearly;
+        let mut due = wall_at(zone, "2024-02-29 09:00");
+        let mut days = Vec::new();
+        for _ in 0..4 {
+            due = todo.next_occurrence(due, zone, due).unwrap();
+            days.push(zone.wall_clock(due).format("%Y-%m-%d").to_string());
+        }
+        assert_eq!(days, ["2025-02-28", "2026-02-28", "2027-02-28", "2028-02-29"]);
+    }
+
+    #[test]
+    fn month_arithmetic() {
+        assert_eq!(days_in_month(2026, 2), 28);
+        assert_eq!(days_in_month(2028, 2), 29);
+        assert_eq!(days_in_month(2026, 12), 31);
+        let jan31 = NaiveDateTime::parse_from_str("2026-01-31 09:00", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(add_months(jan31, 1, 31).date(), NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        assert_eq!(add_months(jan31, 11, 31).date(), NaiveDate::from_ymd_opt(2026, 12, 31).unwrap());
+        assert_eq!(add_months(jan31, 13, 31).date(), NaiveDate::from_ymd_opt(2027, 2, 28).unwrap());
+        // Feb 28 remembered as a clamped 31st; a real 28th keeps its day.
+        let feb28 = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        assert_eq!(anchored_day(Some(31), feb28), 31);
+        assert_eq!(anchored_day(None, feb28), 28);
+        let mar15 = NaiveDate::from_ymd_opt(2026, 3, 15).unwrap();
+        assert_eq!(anchored_day(Some(31), mar15), 15);
+    }
+
+    /// Every id stored anywhere on `state`, items and references alike.
+    fn referenced_ids(state: &AppState) -> Vec<usize> {
+        let mut ids: Vec<usize> = state.notes.iter().map(|n| n.id).collect();
+        for todo in state.todos.iter().chain(&state.archived) {
+            ids.push(todo.id);
+            ids.extend(todo.details_note);
+            ids.extend(todo.waiting_on);
+        }
+        let trashed = state.trash.iter().flat_map(|t| &t.connections);
+        for conn in state.connections.iter().chain(&state.added_connections).chain(trashed) {
+            ids.extend([conn.from, conn.to]);
+        }
+        ids.extend(state.bookmarks.iter().filter_map(|b| b.anchor));
+        ids.extend(state.connecting_from_id);
+        ids.extend(state.focus_item);
+        ids.extend(state.focus);
+        ids.extend(state.confirm_early_done);
+        for (from, to) in state.connection_menu.iter().chain(&state.editing_connection) {
+            ids.extend([*from, *to]);
+        }
+        ids.extend(state.flash.map(|(id, _)| id));
+        ids.extend(state.pomodoro.as_ref().map(|p| p.todo));
+        ids.extend(state.selected.iter().copied());
+        ids
+    }
+
+    /// Notes 0 and 1 and todos 2 and 3, linked 0-2, 1-3 and 2-3, with todo 3
+    /// waiting on 2 and detailed by note 1, and every other id slot filled.
+    fn linked_board() -> AppState {
+        let mut state = AppState::default();
+        let now = at("2026-03-11 09:00");
+        state.add_note(Pos2::ZERO);
+        state.add_note(Pos2::new(0.0, 200.0));
+        state.add_todo(Pos2::new(300.0, 0.0), now);
+        state.add_todo(Pos2::new(300.0, 200.0), now);
+        for (from, to) in [(0, 2), (1, 3), (2, 3)] {
+            state.add_connection(from, to);
+        }
+        state.added_connections = state.connections.clone();
+        state.todos[1].waiting_on = Some(2);
+        state.todos[1].details_note = Some(1);
+        state.trash.push(Trashed {
+            deleted_at: now,
+            notes: Vec::new(),
+            todos: Vec::new(),
+            connections: vec![Connection::new(99, 3)],
+        });
+        state.connecting_from_id = Some(0);
+        state.focus_item = Some(1);
+        state.focus = Some(2);
+        state.confirm_early_done = Some(3);
+        state.connection_menu = Some((1, 3));
+        state.editing_connection = Some((2, 3));
+        state.flash = Some((2, 0.0));
+        state.selected = HashSet::from([0, 3]);
+        state
+    }
+
+    #[test]
+    fn remap_ids_leaves_no_stale_id() {
+        let mut state = linked_board();
+        let links_before: Vec<(usize, usize)> =
+            state.connections.iter().map(|c| (c.from, c.to)).collect();
+        let map: HashMap<usize, usize> = (0..4).map(|id| (id, id + 10)).collect();
+        state.remap_ids(&map);
+
+        let stale: Vec<usize> = referenced_ids(&state)
+            .into_iter()
+            .filter(|id| map.contains_key(id))
+            .collect();
+        assert_eq!(stale, Vec::<usize>::new());
+        let links_after: Vec<(usize, usize)> =
+            state.connections.iter().map(|c| (c.from, c.to)).collect();
+        let expected: Vec<(usize, usize)> = links_before
+            .iter()
+            .map(|&(from, to)| (map[&from], map[&to]))
+            .collect();
+        assert_eq!(links_after, expected);
+        assert_eq!(state.todos[1].waiting_on, Some(12));
+        assert_eq!(state.todos[1].details_note, Some(11));
+        // Ids outside the map stay.
+        assert_eq!(state.trash[0].connections[0].from, 99);
+    }
+
+    #[test]
+    fn absorbed_board_keeps_its_links_under_new_ids() {
+        let mut state = linked_board();
+        let other = linked_board();
+        state.absorb(other);
+        assert_eq!(state.notes.len() + state.todos.len(), 8);
+        let ids: HashSet<usize> = state.item_ids();
+        assert_eq!(ids.len(), 8);
+        assert_eq!(state.connections.len(), 6);
+        for conn in &state.connections {
+            assert!(ids.contains(&conn.from) && ids.contains(&conn.to), "{conn:?}");
+        }
+        // Fresh ids come in no particular order, so compare by position.
+        let pos = |state: &AppState, id| (state.get_item_pos(id).unwrap() * 1000.0).round();
+        let by_pos = |state: &AppState, conns: &[Connection]| -> Vec<_> {
+            conns.iter().map(|c| (pos(state, c.from), pos(state, c.to))).collect()
+        };
+        let original = linked_board();
+        assert_eq!(
+            by_pos(&state, &state.connections[3..]),
+            by_pos(&original, &original.connections)
+        );
+        let waiting = &state.todos[3];
+        assert_eq!(pos(&state, waiting.waiting_on.unwrap()), pos(&state, state.todos[2].id));
+        assert_eq!(pos(&state, waiting.details_note.unwrap()), pos(&state, state.notes[3].id));
+    }
+
+    #[test]
+    fn merged_items_hand_over_their_links() {
+        let mut state = linked_board();
+        // Note 1 folds into note 0: 1-3 becomes 0-3, and todo 3's details
+        // follow.
+        state.merge_into(0, &HashSet::from([1]));
+        let links: Vec<(usize, usize)> = state.connections.iter().map(|c| (c.from, c.to)).collect();
+        assert_eq!(links, [(0, 2), (0, 3), (2, 3)]);
+        assert_eq!(state.todos[1].details_note, Some(0));
+        assert!(!referenced_ids(&state).contains(&1));
+    }
+}
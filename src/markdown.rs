@@ -0,0 +1,184 @@
+use eframe::egui::{self, RichText, Ui};
+
+use crate::fonts::{ICON_BOX, ICON_BOX_CHECKED};
+
+/// A run of inline text sharing one style.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Span {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Block<'a> {
+    Heading(usize, &'a str),
+    /// Indent level, optional task box state (`Some(done)`), and the text.
+    Bullet(usize, Option<bool>, &'a str),
+    Paragraph(&'a str),
+    Blank,
+}
+
+fn parse_block(line: &str) -> Block<'_> {
+    let indent = line.len() - line.trim_start().len();
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return Block::Blank;
+    }
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) {
+        if let Some(text) = trimmed[hashes..].strip_prefix(' ') {
+            return Block::Heading(hashes, text.trim());
+        }
+    }
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(marker) {
+            let task = match rest.get(..3) {
+                Some("[ ]") => Some(false),
+                Some("[x]") | Some("[X]") => Some(true),
+                _ => None,
+            };
+            let text = if task.is_some() {
+                rest[3..].trim_start()
+            } else {
+                rest
+            };
+            return Block::Bullet(indent / 2, task, text);
+        }
+    }
+    Block::Paragraph(trimmed)
+}
+
+/// Splits a line into styled spans. Handles `**bold**`, `*italic*` /
+/// `_italic_`, `` `code` `` and `[text](url)`; an unclosed marker is kept as
+/// literal text.
+fn parse_inline(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut current = Span::default();
+    let mut rest = text;
+    let flush = |spans: &mut Vec<Span>, current: &mut Span| {
+        if !current.text.is_empty() {
+            let style = Span {
+                text: String::new(),
+                ..current.clone()
+            };
+            spans.push(std::mem::replace(current, style));
+        }
+    };
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            if current.bold || after.contains("**") {
+                flush(&mut spans, &mut current);
+                current.bold = !current.bold;
+                rest = after;
+                continue;
+            }
+        } else if c == '*' || c == '_' {
+            let after = &rest[1..];
+            if current.italic || after.contains(c) {
+                flush(&mut spans, &mut current);
+                current.italic = !current.italic;
+                rest = after;
+                continue;
+            }
+        } else if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                flush(&mut spans, &mut current);
+                spans.push(Span {
+                    text: rest[1..1 + end].to_string(),
+                    code: true,
+                    ..Default::default()
+                });
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if c == '[' {
+            if let Some((label, url, after)) = split_link(rest) {
+                flush(&mut spans, &mut current);
+                spans.push(Span {
+                    text: label.to_string(),
+                    link: Some(url.to_string()),
+                    ..current.clone()
+                });
+                rest = after;
+                continue;
+            }
+        }
+        current.text.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    flush(&mut spans, &mut current);
+    spans
+}
+
+/// `[label](url)rest` into its three parts.
+fn split_link(text: &str) -> Option<(&str, &str, &str)> {
+    let close = text.find("](")?;
+    let label = &text[1..close];
+    let after = &text[close + 2..];
+    let end = after.find(')')?;
+    Some((label, &after[..end], &after[end + 1..]))
+}
+
+fn show_spans(ui: &mut Ui, spans: &[Span], size: Option<f32>, strong: bool) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            let mut text = RichText::new(&span.text);
+            if let Some(size) = size {
+                text = text.size(size);
+            }
+            if span.bold || strong {
+                text = text.strong();
+            }
+            if span.italic {
+                text = text.italics();
+            }
+            if span.code {
+                text = text.code();
+            }
+            match &span.link {
+                Some(url) => {
+                    ui.hyperlink_to(text, url);
+                }
+                None => {
+                    ui.label(text);
+                }
+            }
+        }
+    });
+}
+
+/// Draws `text` as Markdown: headings, bold/italic, inline code, bullet and
+/// task lists, and links. Anything else shows as plain paragraphs.
+pub fn show_markdown(ui: &mut Ui, text: &str) {
+    let body_size = egui::TextStyle::Body.resolve(ui.style()).size;
+    for line in text.lines() {
+        match parse_block(line) {
+            Block::Heading(level, text) => {
+                let scale = match level {
+                    1 => 1.6,
+                    2 => 1.35,
+                    3 => 1.15,
+                    _ => 1.0,
+                };
+                show_spans(ui, &parse_inline(text), Some(body_size * scale), true);
+            }
+            Block::Bullet(indent, task, text) => {
+                ui.horizontal(|ui| {
+                    ui.add_space(12.0 * indent as f32);
+                    ui.label(match task {
+                        Some(true) => ICON_BOX_CHECKED,
+                        Some(false) => ICON_BOX,
+                        None => "\u{2022}",
+                    });
+                    show_spans(ui, &parse_inline(text), None, false);
+                });
+            }
+            Block::Paragraph(text) => show_spans(ui, &parse_inline(text), None, false),
+            Block::Blank => ui.add_space(body_size * 0.5),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+/// Every entry that can appear in the canvas or item context menus. Users can
+/// switch entries off in the settings; "Settings..." itself is always shown so
+/// the menus can't be configured into a dead end.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    AddNote,
+    AddTodo,
+    QuickReminder,
+    ShiftDue,
+    MoveToBoard,
+    UndoRedo,
+    UndoConnection,
+    ImportImages,
+    ChangeBackground,
+    TidyOverlaps,
+    FindDuplicates,
+    MoveData,
+    ExportBundle,
+    ExportImage,
+    ExportCalendar,
+    ImportBundle,
+    Bookmarks,
+    ItemList,
+    ArchivePanel,
+    TrashPanel,
+    OnlyStarred,
+    ToggleStar,
+    Connections,
+    CenterView,
+    Focus,
+    Snooze,
+    Archive,
+    Color,
+    Opacity,
+    Delete,
+}
+
+impl MenuAction {
+    pub const ALL: [Self; 30] = [
+        Self::AddNote,
+        Self::AddTodo,
+        Self::QuickReminder,
+        Self::ShiftDue,
+        Self::MoveToBoard,
+        Self::UndoRedo,
+        Self::UndoConnection,
+        Self::ImportImages,
+        Self::ChangeBackground,
+        Self::TidyOverlaps,
+        Self::FindDuplicates,
+        Self::MoveData,
+        Self::ExportBundle,
+        Self::ExportImage,
+        Self::ExportCalendar,
+        Self::ImportBundle,
+        Self::Bookmarks,
+        Self::ItemList,
+        Self::ArchivePanel,
+        Self::TrashPanel,
+        Self::OnlyStarred,
+        Self::ToggleStar,
+        Self::Connections,
+        Self::CenterView,
+        Self::Focus,
+        Self::Snooze,
+        Self::Archive,
+        Self::Color,
+        Self::Opacity,
+        Self::Delete,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::AddNote => "Add Note",
+            Self::AddTodo => "Add Todo",
+            Self::QuickReminder => "Quick reminder",
+            Self::ShiftDue => "Shift due by...",
+            Self::MoveToBoard => "Move selection to board...",
+            Self::UndoRedo => "Undo / Redo",
+            Self::UndoConnection => "Undo last connection",
+            Self::ImportImages => "Import images...",
+            Self::ChangeBackground => "Change Background",
+            Self::TidyOverlaps => "Tidy overlaps",
+            Self::FindDuplicates => "Find duplicates",
+            Self::MoveData => "Move data to...",
+            Self::ExportBundle => "Export bundle...",
+            Self::ExportImage => "Export image...",
+            Self::ExportCalendar => "Export calendar...",
+            Self::ImportBundle => "Import bundle...",
+            Self::Bookmarks => "Bookmarks",
+            Self::ItemList => "Item list",
+            Self::ArchivePanel => "Archive...",
+            Self::TrashPanel => "Trash...",
+            Self::OnlyStarred => "Only starred",
+            Self::ToggleStar => "Star",
+            Self::Connections => "Connections",
+            Self::CenterView => "Center view",
+            Self::Focus => "Focus",
+            Self::Snooze => "Snooze",
+            Self::Archive => "Archive",
+            Self::Color => "Color",
+            Self::Opacity => "Opacity",
+            Self::Delete => "Delete",
+        }
+    }
+
+    /// Actions that change the board, hidden while it is locked.
+    pub fn edits_board(self) -> bool {
+        !matches!(
+            self,
+            Self::ExportBundle
+                | Self::ExportImage
+                | Self::ExportCalendar
+                | Self::Bookmarks
+                | Self::ItemList
+                | Self::ArchivePanel
+                | Self::TrashPanel
+                | Self::OnlyStarred
+                | Self::CenterView
+                | Self::Focus
+        )
+    }
+
+    /// Item actions live in the menu of a note/todo window, the rest in the
+    /// canvas menu.
+    pub fn is_item_action(self) -> bool {
+        matches!(
+            self,
+            Self::ToggleStar
+                | Self::Connections
+                | Self::CenterView
+                | Self::Focus
+                | Self::Snooze
+                | Self::Archive
+                | Self::Color
+                | Self::Opacity
+                | Self::Delete
+        )
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, Color32, Context, Id, Order, Pos2, Rect, Sense, Stroke, Vec2};
+
+use crate::transform::Transformer;
+use crate::AppState;
+
+const SIZE: Vec2 = Vec2::new(200.0, 140.0);
+/// Room left around the outermost items, as a share of the map.
+const MARGIN: f32 = 0.05;
+
+/// Maps between canvas units and points on the minimap.
+#[derive(Clone, Copy)]
+struct MapScale {
+    world: Rect,
+    map: Rect,
+    scale: f32,
+}
+
+impl MapScale {
+    /// Fits `world` into `map` keeping its proportions, centered.
+    fn fit(world: Rect, map: Rect) -> Self {
+        let world = world.expand(world.size().max_elem() * MARGIN);
+        let scale = (map.width() / world.width()).min(map.height() / world.height());
+        Self { world, map, scale }
+    }
+
+    fn to_map(self, pos: Pos2) -> Pos2 {
+        self.map.center() + (pos - self.world.center()) * self.scale
+    }
+
+    fn to_world(self, pos: Pos2) -> Pos2 {
+        self.world.center() + (pos - self.map.center()) / self.scale
+    }
+}
+
+/// A small overview in the top-right corner of `canvas`: every item the
+/// filter shows as a box, and the part of the canvas on screen outlined.
+/// Clicking glides the view there; dragging moves it along with the
+/// pointer.
+pub fn minimap(ctx: &Context, state: &mut AppState, canvas: Rect, hidden: &HashSet<usize>) {
+    let transformer = Transformer::new(state.offset, state.zoom);
+    let view = Rect::from_min_max(
+        transformer.from_screen(canvas.min),
+        transformer.from_screen(canvas.max),
+    );
+    let items: Vec<(Rect, Color32)> = state
+        .notes
+        .iter()
+        .filter(|n| !hidden.contains(&n.id))
+        .map(|n| (n.position, n.size, n.meta.color, Color32::from_gray(150)))
+        .chain(
+            state
+                .todos
+                .iter()
+                .filter(|t| !hidden.contains(&t.id))
+                .map(|t| {
+                    let gray = if t.is_done { 90 } else { 200 };
+                    (t.position, t.size, t.meta.color, Color32::from_gray(gray))
+                }),
+        )
+        .map(|(pos, size, color, gray)| {
+            (
+                Rect::from_min_size(pos, size),
+                color.map_or(gray, |c| c.rgb()),
+            )
+        })
+        .collect();
+
+    let bounds_id = Id::new("minimap_bounds");
+    let origin = canvas.right_top() + Vec2::new(-SIZE.x - 8.0, 8.0);
+    egui::Area::new(Id::new("minimap"))
+        .order(Order::Foreground)
+        .fixed_pos(origin)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let (response, painter) = ui.allocate_painter(SIZE, Sense::click_and_drag());
+                // Held still while dragging, since the outline itself grows
+                // the bounds as it nears the edge.
+                let world = match ui.data(|d| d.get_temp::<Rect>(bounds_id)) {
+                    Some(held) if response.dragged() => held,
+                    _ => items.iter().fold(view, |all, (r, _)| all.union(*r)),
+                };
+                if response.drag_started() {
+                    ui.data_mut(|d| d.insert_temp(bounds_id, world));
+                }
+                let map = MapScale::fit(world, response.rect);
+                for (rect, color) in &items {
+                    let on_map = Rect::from_min_max(map.to_map(rect.min), map.to_map(rect.max));
+                    let on_map = Rect::from_center_size(
+                        on_map.center(),
+                        on_map.size().max(Vec2::splat(2.0)),
+                    );
+                    painter.rect_filled(on_map, 0.0, *color);
+                }
+                let outline = Rect::from_min_max(map.to_map(view.min), map.to_map(view.max))
+                    .intersect(response.rect);
+                painter.rect_stroke(
+                    outline,
+                    0.0,
+                    Stroke::new(1.5, ui.visuals().selection.bg_fill),
+                );
+
+                let Some(pointer) = response.interact_pointer_pos() else {
+                    return;
+                };
+                let target = map.to_world(pointer);
+                let offset = canvas.center().to_vec2() - target.to_vec2() * state.zoom;
+                if response.dragged() {
+                    state.view_goal = None;
+                    state.offset = offset;
+                } else if response.clicked() {
+                    state.view_goal = Some((offset, state.zoom));
+                }
+            });
+        });
+}
@@ -0,0 +1,257 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use eframe::egui::{self, Id, Key, Ui};
+
+use crate::timezone::BoardZone;
+
+/// Time of day for phrases that only name a day ("tomorrow", "next fri").
+const DEFAULT_HOUR: u32 = 9;
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let day = match word {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// "9", "9am", "9:30", "9:30pm", "21:00". `suffix` is a separate "am"/"pm"
+/// word following the number, if any.
+fn time_of_day(word: &str, suffix: Option<&str>) -> Option<NaiveTime> {
+    let (digits, meridiem) = if let Some(d) = word.strip_suffix("am") {
+        (d, Some(false))
+    } else if let Some(d) = word.strip_suffix("pm") {
+        (d, Some(true))
+    } else {
+        match suffix {
+            Some("am") => (word, Some(false)),
+            Some("pm") => (word, Some(true)),
+            _ => (word, None),
+        }
+    };
+    let (hour, minute) = match digits.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (digits.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None if hour > 23 => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// "2 hours", "30 min", "3d", "a week".
+fn span(amount: &str, unit: Option<&str>) -> Option<(Duration, bool)> {
+    let split = amount
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(amount.len());
+    let (number, glued) = amount.split_at(split);
+    let (n, unit, used_next) = match (number, glued) {
+        ("", "a" | "an") => (1, unit?, true),
+        ("", _) => return None,
+        (n, "") => (n.parse::<i64>().ok()?, unit?, true),
+        (n, glued) => (n.parse::<i64>().ok()?, glued, false),
+    };
+    // The `try_` forms, since `n` is whatever was typed and the plain ones
+    // panic when it is out of range.
+    let duration = match unit.trim_end_matches('s') {
+        "m" | "min" | "minute" => Duration::try_minutes(n)?,
+        "h" | "hr" | "hour" => Duration::try_hours(n)?,
+        "d" | "day" => Duration::try_days(n)?,
+        "w" | "wk" | "week" => Duration::try_weeks(n)?,
+        _ => return None,
+    };
+    Some((duration, used_next))
+}
+
+/// Reads a due date out of phrases like "tomorrow 9am", "next fri",
+/// "in 2 hours", "mon 14:30", "2026-11-03" or "noon". Days are on the board
+/// zone's calendar; a day without a time gets 9:00 and a time without a day
+/// is the next time the clock shows it. `None` if any word isn't understood.
+pub fn parse_due(text: &str, now: DateTime<Local>, zone: BoardZone) -> Option<DateTime<Local>> {
+    let text = text.trim().to_lowercase();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    let wall = zone.wall_clock(now);
+    let today = wall.date();
+    if words[0] == "in" {
+        let (duration, used_next) = span(words.get(1)?, words.get(2).copied())?;
+        if words.len() != if used_next { 3 } else { 2 } {
+            return None;
+        }
+        return now.checked_add_signed(duration);
+    }
+
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let next = words.get(i + 1).copied();
+        match word {
+            "today" => date = Some(today),
+            "tonight" => {
+                date = Some(today);
+                time = time.or(NaiveTime::from_hms_opt(20, 0, 0));
+            }
+            "tomorrow" | "tmr" | "tmrw" => date = today.succ_opt(),
+            "noon" => time = NaiveTime::from_hms_opt(12, 0, 0),
+            "midnight" => time = NaiveTime::from_hms_opt(0, 0, 0),
+            "at" | "on" | "this" => {}
+            "next" if next == Some("week") => {
+                date = Some(today + Duration::weeks(1));
+                i += 1;
+            }
+            "next" if next.and_then(weekday).is_some() => {}
+            _ if weekday(word).is_some() => {
+                let target = weekday(word)?;
+                let ahead = (7 + target.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                // Today's own weekday means the one a week away.
+                let ahead = if ahead == 0 { 7 } else { ahead };
+                date = Some(today + Duration::days(ahead as i64));
+            }
+            _ => {
+                if let Ok(d) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                    date = Some(d);
+                } else {
+                    let suffix = next.filter(|n| *n == "am" || *n == "pm");
+                    time = Some(time_of_day(word, suffix)?);
+                    if suffix.is_some() {
+                        i += 1;
+                    }
+                }
+            }
+        }
+        i += 1;
+    }
+    if date.is_none() && time.is_none() {
+        return None;
+    }
+    let time = time.unwrap_or(NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0)?);
+    let date = match date {
+        Some(d) => d,
+        None if today.and_time(time) > wall => today,
+        None => today.succ_opt()?,
+    };
+    Some(zone.instant_at(date.and_time(time)))
+}
+
+/// A text field for typing a due date in words, with what it reads as shown
+/// underneath. Returns the date once Enter is pressed on a phrase that
+/// parses; the text is kept in egui memory under `id` until then.
+pub fn due_text_field(
+    ui: &mut Ui,
+    id: Id,
+    now: DateTime<Local>,
+    zone: BoardZone,
+) -> Option<DateTime<Local>> {
+    let mut text = ui
+        .data_mut(|d| d.get_temp::<String>(id))
+        .unwrap_or_default();
+    let field = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .id(id)
+            .hint_text("e.g. tomorrow 9am, next fri, in 2 hours"),
+    );
+    let parsed = parse_due(&text, now, zone);
+    let mut chosen = None;
+    if !text.trim().is_empty() {
+        match parsed {
+            Some(due) => {
+                let wall = zone.wall_clock(due);
+                ui.weak(format!("\u{2192} {}", wall.format("%a %d %b %Y %H:%M")));
+                if field.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    chosen = Some(due);
+                    text.clear();
+                }
+            }
+            None => {
+                ui.colored_label(ui.visuals().warn_fg_color, "Not understood");
+            }
+        }
+    }
+    ui.data_mut(|d| d.insert_temp(id, text));
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDateTime, TimeZone, Timelike};
+
+    use super::*;
+
+    fn zone() -> BoardZone {
+        BoardZone::Named(chrono_tz::Europe::Berlin)
+    }
+
+    /// Wednesday 2026-03-11, 15:00 in Berlin.
+    fn now() -> DateTime<Local> {
+        zone().instant_at(wall("2026-03-11 15:00"))
+    }
+
+    fn wall(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn parsed(text: &str) -> Option<NaiveDateTime> {
+        parse_due(text, now(), zone()).map(|t| zone().wall_clock(t))
+    }
+
+    #[test]
+    fn days_and_times() {
+        assert_eq!(parsed("tomorrow 9am"), Some(wall("2026-03-12 09:00")));
+        assert_eq!(parsed("next fri"), Some(wall("2026-03-13 09:00")));
+        assert_eq!(parsed("wed 14:30"), Some(wall("2026-03-18 14:30")));
+        assert_eq!(parsed("2026-11-03 noon"), Some(wall("2026-11-03 12:00")));
+        assert_eq!(parsed("4 pm"), Some(wall("2026-03-11 16:00")));
+        assert_eq!(parsed("9am"), Some(wall("2026-03-12 09:00")));
+    }
+
+    #[test]
+    fn spans_from_now() {
+        let now = now();
+        let after = |text: &str| parse_due(text, now, zone()).map(|t| t - now);
+        assert_eq!(after("in 2 hours"), Some(Duration::hours(2)));
+        assert_eq!(after("in 30min"), Some(Duration::minutes(30)));
+        assert_eq!(after("in a week"), Some(Duration::weeks(1)));
+        assert_eq!(after("in 3d"), Some(Duration::days(3)));
+    }
+
+    #[test]
+    fn not_understood() {
+        for text in ["", "soon", "in", "in 2", "in 2 fortnights", "tomorrow 25:00", "13pm"] {
+            assert_eq!(parsed(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn huge_spans_are_rejected() {
+        for text in [
+            "in 999999999 days",
+            "in 999999999999 weeks",
+            "in 9223372036854775807 minutes",
+            "in 99999999999999999999 hours",
+        ] {
+            assert_eq!(parsed(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn skipped_hour_moves_past_the_gap() {
+        // Berlin's clocks jump from 02:00 to 03:00 on 2026-03-29.
+        let now = Local.timestamp_opt(0, 0).unwrap();
+        let due = parse_due("2026-03-29 2:30", now, zone()).unwrap();
+        assert_eq!(zone().wall_clock(due).hour(), 3);
+    }
+}
@@ -0,0 +1,222 @@
+use std::process::Command;
+use std::thread;
+
+use notify_rust::{Notification, Timeout};
+use serde::{Deserialize, Serialize};
+
+use crate::priority::Priority;
+use crate::settings::Settings;
+
+/// Short audio confirmations for user actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    Created,
+    Completed,
+}
+impl Cue {
+    /// freedesktop sound theme name.
+    fn sound_id(self) -> &'static str {
+        match self {
+            Cue::Created => "message-new-instant",
+            Cue::Completed => "complete",
+        }
+    }
+
+    fn mac_sound(self) -> &'static str {
+        match self {
+            Cue::Created => "/System/Library/Sounds/Tink.aiff",
+            Cue::Completed => "/System/Library/Sounds/Glass.aiff",
+        }
+    }
+}
+
+/// How long a reminder stays on screen. Backends that ignore timeouts show
+/// it for their usual time whatever is picked here.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Persistence {
+    /// Whatever the notification server does by default.
+    #[default]
+    System,
+    /// Stays until dismissed.
+    Sticky,
+    Seconds(u32),
+}
+
+/// Lead times offered for early reminders, in minutes.
+pub const LEAD_PRESETS: [u32; 6] = [5, 15, 30, 60, 120, 24 * 60];
+
+/// "15 min", "2 h", "1 day".
+pub fn lead_label(minutes: u32) -> String {
+    match minutes {
+        m if m >= 24 * 60 && m % (24 * 60) == 0 => match m / (24 * 60) {
+            1 => "1 day".to_string(),
+            d => format!("{} days", d),
+        },
+        m if m >= 60 && m % 60 == 0 => format!("{} h", m / 60),
+        m => format!("{} min", m),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReminderKind {
+    /// Sent ahead of the due time, this many minutes before it.
+    Early(u32),
+    /// The due time has passed.
+    Overdue,
+    /// Back after being snoozed.
+    Snoozed,
+}
+
+/// A button picked on a reminder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReminderAction {
+    Done,
+    Snooze,
+}
+
+/// How far "Snooze" puts a reminder off.
+pub const SNOOZE_MINUTES: i64 = 10;
+
+/// Runs on the notification's own thread once a button is picked.
+pub type ActionHandler = Box<dyn FnOnce(ReminderAction) + Send>;
+
+/// A todo coming up or past due.
+#[derive(Clone, Debug)]
+pub struct Reminder {
+    pub id: usize,
+    pub text: String,
+    pub starred: bool,
+    pub priority: Priority,
+    pub kind: ReminderKind,
+}
+
+fn show(summary: &str, body: &str, persistence: Persistence, critical: bool) {
+    notification(summary, body, persistence, critical)
+        .show()
+        .ok();
+}
+
+fn notification(
+    summary: &str,
+    body: &str,
+    persistence: Persistence,
+    critical: bool,
+) -> Notification {
+    let mut notification = Notification::new();
+    notification.summary(summary).body(body);
+    match persistence {
+        Persistence::System => {}
+        Persistence::Sticky => {
+            notification.timeout(Timeout::Never);
+        }
+        Persistence::Seconds(s) => {
+            notification.timeout(Timeout::Milliseconds(s.saturating_mul(1000)));
+        }
+    }
+    // Some servers only keep critical notifications around, so sticky ones
+    // are sent as critical too.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if critical || persistence == Persistence::Sticky {
+        notification.urgency(notify_rust::Urgency::Critical);
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = critical;
+    notification
+}
+
+/// Shows `notification` with Done / Snooze buttons and waits on a thread of
+/// its own for one to be picked.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_with_actions(mut notification: Notification, on_action: ActionHandler) {
+    notification
+        .action("done", "Done")
+        .action("snooze", &format!("Snooze {} min", SNOOZE_MINUTES));
+    thread::spawn(move || {
+        if let Ok(handle) = notification.show() {
+            handle.wait_for_action(|action| match action {
+                "done" => on_action(ReminderAction::Done),
+                "snooze" => on_action(ReminderAction::Snooze),
+                _ => {}
+            });
+        }
+    });
+}
+
+/// Other platforms' notifications can't carry buttons through notify-rust.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show_with_actions(notification: Notification, _on_action: ActionHandler) {
+    notification.show().ok();
+}
+
+/// Everything the app says to the user outside its window: due reminders and,
+/// when enabled, feedback sounds.
+#[derive(Clone, Copy, Debug)]
+pub struct Notifier {
+    sounds: bool,
+    reminders: Persistence,
+    starred_reminders: Persistence,
+}
+impl Notifier {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            sounds: settings.feedback_sounds,
+            reminders: settings.reminder_persistence,
+            starred_reminders: settings.starred_reminder_persistence,
+        }
+    }
+
+    /// Starred todos get their own persistence, sticky unless changed.
+    /// Urgent ones are sent with critical urgency where the platform has it.
+    /// With `on_action`, the notification offers Done and Snooze buttons
+    /// where the platform supports them.
+    pub fn due(&self, reminder: &Reminder, on_action: Option<ActionHandler>) {
+        let persistence = if reminder.starred {
+            self.starred_reminders
+        } else {
+            self.reminders
+        };
+        let urgent = reminder.priority == Priority::Urgent;
+        let summary = match reminder.kind {
+            ReminderKind::Early(minutes) => format!("Todo due in {}", lead_label(minutes)),
+            ReminderKind::Overdue => "Todo overdue!".to_string(),
+            ReminderKind::Snoozed => "Todo reminder".to_string(),
+        };
+        let notification = notification(&summary, &reminder.text, persistence, urgent);
+        match on_action {
+            Some(on_action) => show_with_actions(notification, on_action),
+            None => {
+                notification.show().ok();
+            }
+        }
+    }
+
+    /// A todo's dependency was completed.
+    pub fn unblocked(&self, text: &str) {
+        show("Todo unblocked", text, self.reminders, false);
+    }
+
+    /// A focus session moved between work and break.
+    pub fn pomodoro(&self, summary: &str, text: &str) {
+        show(summary, text, self.reminders, false);
+    }
+
+    /// Plays `cue` on a background thread, falling back to the terminal bell
+    /// when no system sound player is available.
+    pub fn cue(&self, cue: Cue) {
+        if !self.sounds {
+            return;
+        }
+        thread::spawn(move || {
+            let status = if cfg!(target_os = "macos") {
+                Command::new("afplay").arg(cue.mac_sound()).status()
+            } else {
+                Command::new("canberra-gtk-play")
+                    .args(["-i", cue.sound_id()])
+                    .status()
+            };
+            if !status.is_ok_and(|s| s.success()) {
+                eprint!("\x07");
+            }
+        });
+    }
+}
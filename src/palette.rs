@@ -0,0 +1,82 @@
+use eframe::egui::{self, Color32, Sense, Stroke, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// How strongly an item's color shows through its window background.
+const FILL_MIX: f32 = 0.22;
+
+/// Colors items can be given to group them by eye.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Teal,
+    Blue,
+    Purple,
+    Pink,
+    Gray,
+}
+
+impl ItemColor {
+    pub const ALL: [Self; 9] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Teal,
+        Self::Blue,
+        Self::Purple,
+        Self::Pink,
+        Self::Gray,
+    ];
+
+    pub fn rgb(self) -> Color32 {
+        match self {
+            Self::Red => Color32::from_rgb(214, 69, 65),
+            Self::Orange => Color32::from_rgb(235, 137, 52),
+            Self::Yellow => Color32::from_rgb(230, 200, 60),
+            Self::Green => Color32::from_rgb(76, 175, 80),
+            Self::Teal => Color32::from_rgb(38, 166, 154),
+            Self::Blue => Color32::from_rgb(66, 133, 244),
+            Self::Purple => Color32::from_rgb(149, 97, 226),
+            Self::Pink => Color32::from_rgb(230, 105, 170),
+            Self::Gray => Color32::from_rgb(140, 140, 140),
+        }
+    }
+
+    /// `fill` with this color mixed in, for window backgrounds.
+    pub fn tint(self, fill: Color32) -> Color32 {
+        let [r, g, b, a] = fill.to_array();
+        let c = self.rgb();
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * FILL_MIX) as u8;
+        Color32::from_rgba_premultiplied(mix(r, c.r()), mix(g, c.g()), mix(b, c.b()), a)
+    }
+}
+
+/// A row of swatches plus "None". Returns whether the color changed.
+pub fn color_picker(ui: &mut Ui, color: &mut Option<ItemColor>) -> bool {
+    let before = *color;
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.0;
+        for option in ItemColor::ALL {
+            let (rect, response) = ui.allocate_exact_size(Vec2::splat(16.0), Sense::click());
+            let stroke = if *color == Some(option) {
+                Stroke::new(2.0, ui.visuals().strong_text_color())
+            } else {
+                Stroke::NONE
+            };
+            ui.painter().rect(rect, 3.0, option.rgb(), stroke);
+            if response.on_hover_text(format!("{:?}", option)).clicked() {
+                *color = Some(option);
+            }
+        }
+        if ui
+            .add_enabled(color.is_some(), egui::Button::new("None").small())
+            .clicked()
+        {
+            *color = None;
+        }
+    });
+    *color != before
+}
@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+
+use eframe::egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::bookmarks::ViewBookmark;
+use crate::connection::{deserialize_connections, Connection};
+use crate::lanes::Lane;
+use crate::lock::BoardLock;
+use crate::settings::BoardSettings;
+use crate::trash::Trashed;
+use crate::{rounded, AppState, Note, Todo};
+
+/// Exactly what goes into the board file. Anything on [`AppState`] that isn't
+/// copied here is session state and never written.
+#[derive(Serialize, Deserialize)]
+pub struct PersistedState {
+    pub notes: Vec<Note>,
+    pub todos: Vec<Todo>,
+    #[serde(deserialize_with = "deserialize_connections")]
+    pub connections: Vec<Connection>,
+    #[serde(serialize_with = "rounded::vec2")]
+    pub offset: Vec2,
+    pub zoom: f32,
+    pub next_id: usize,
+    pub background_image_path: Option<String>,
+    #[serde(default)]
+    pub archived: Vec<Todo>,
+    #[serde(default)]
+    pub trash: Vec<Trashed>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Boards saved before the app-wide settings moved to their own file
+    /// have them all under `settings`; only the board's own are read back.
+    #[serde(default, alias = "settings")]
+    pub board_settings: BoardSettings,
+    #[serde(default)]
+    pub lock: Option<BoardLock>,
+    #[serde(default)]
+    pub bookmarks: Vec<ViewBookmark>,
+    #[serde(default)]
+    pub lanes: Vec<Lane>,
+}
+
+impl PersistedState {
+    /// Every image file the board refers to, for bundling.
+    pub fn image_paths_mut(&mut self) -> Vec<&mut String> {
+        self.background_image_path
+            .iter_mut()
+            .chain(self.notes.iter_mut().filter_map(|n| n.image.as_mut()))
+            .collect()
+    }
+
+    /// Drops every item not in `ids`, the archive and trash, and links and references
+    /// leading out of the subset.
+    pub fn keep_only(&mut self, ids: &HashSet<usize>) {
+        self.notes.retain(|n| ids.contains(&n.id));
+        self.todos.retain(|t| ids.contains(&t.id));
+        self.archived.clear();
+        self.trash.clear();
+        self.connections
+            .retain(|c| ids.contains(&c.from) && ids.contains(&c.to));
+        for todo in self.todos.iter_mut() {
+            todo.details_note = todo.details_note.filter(|id| ids.contains(id));
+            todo.waiting_on = todo.waiting_on.filter(|id| ids.contains(id));
+        }
+        for bookmark in self.bookmarks.iter_mut() {
+            bookmark.anchor = bookmark.anchor.filter(|id| ids.contains(id));
+        }
+    }
+
+    /// Sorts items by id and connections by their ends, so saving the same
+    /// board always yields the same file whatever order things were created,
+    /// removed or raised in.
+    fn sort_for_diffing(&mut self) {
+        self.notes.sort_by_key(|n| n.id);
+        self.todos.sort_by_key(|t| t.id);
+        self.archived.sort_by_key(|t| t.id);
+        self.connections.sort_by_key(|c| (c.from, c.to));
+    }
+}
+
+impl AppState {
+    /// The saved copy of the board. The in-memory order is left alone either
+    /// way; only the copy is sorted when `stable_file_order` is on.
+    pub fn to_persisted(&self) -> PersistedState {
+        let mut persisted = PersistedState {
+            notes: self.notes.clone(),
+            todos: self.todos.clone(),
+            connections: self.connections.clone(),
+            offset: self.offset,
+            zoom: self.zoom,
+            next_id: self.next_id,
+            background_image_path: self.background_image_path.clone(),
+            archived: self.archived.clone(),
+            trash: self.trash.clone(),
+            timezone: self.timezone.clone(),
+            board_settings: self.board_settings.clone(),
+            lock: self.lock.clone(),
+            bookmarks: self.bookmarks.clone(),
+            lanes: self.lanes.clone(),
+        };
+        if self.board_settings.stable_file_order {
+            persisted.sort_for_diffing();
+        }
+        persisted
+    }
+
+    /// A fresh session for a saved board.
+    pub fn from_persisted(saved: PersistedState) -> Self {
+        let PersistedState {
+            notes,
+            todos,
+            connections,
+            offset,
+            zoom,
+            next_id,
+            background_image_path,
+            archived,
+            trash,
+            timezone,
+            board_settings,
+            lock,
+            bookmarks,
+            lanes,
+        } = saved;
+        Self {
+            notes,
+            todos,
+            connections,
+            offset,
+            zoom,
+            next_id,
+            background_image_path,
+            archived,
+            trash,
+            timezone,
+            board_settings,
+            lock,
+            bookmarks,
+            lanes,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use eframe::egui::Pos2;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn keys(value: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// A board in the middle of a session: items linked, selected, being
+    /// edited and searched for.
+    fn busy_board() -> AppState {
+        let mut state = AppState {
+            zoom: 1.0,
+            ..AppState::default()
+        };
+        let note = state.add_note(Pos2::new(10.0, 20.0));
+        let todo = state.add_todo(Pos2::new(300.0, 20.0), Local::now());
+        state.add_connection(note, todo);
+        state.connecting_from_id = Some(todo);
+        state.selected.insert(note);
+        state.settings_open = true;
+        state.focus = Some(note);
+        state.palette = Some("add".into());
+        state.flash = Some((todo, 1.0));
+        state.view_goal = Some((Vec2::new(5.0, 5.0), 2.0));
+        state.connection_menu = Some((note, todo));
+        state.unlocked = true;
+        state
+    }
+
+    #[test]
+    fn transient_fields_are_not_saved() {
+        let json = serde_json::to_value(busy_board().to_persisted()).unwrap();
+        assert_eq!(
+            keys(&json),
+            [
+                "archived",
+                "background_image_path",
+                "board_settings",
+                "bookmarks",
+                "connections",
+                "lanes",
+                "lock",
+                "next_id",
+                "notes",
+                "offset",
+                "timezone",
+                "todos",
+                "trash",
+                "zoom",
+            ]
+        );
+        let text = json.to_string();
+        for transient in [
+            "connecting_from_id",
+            "selected",
+            "settings_open",
+            "palette",
+            "flash",
+            "view_goal",
+            "connection_menu",
+            "unlocked",
+            "editing",
+            "checklist",
+            "search_cache",
+        ] {
+            assert!(!text.contains(&format!("\"{transient}\"")), "{transient}");
+        }
+    }
+
+    #[test]
+    fn saved_board_loads_back() {
+        let state = busy_board();
+        let json = serde_json::to_string(&state.to_persisted()).unwrap();
+        let loaded = AppState::from_persisted(serde_json::from_str(&json).unwrap());
+        assert_eq!(loaded.notes.len(), 1);
+        assert_eq!(loaded.todos.len(), 1);
+        assert_eq!(loaded.connections.len(), 1);
+        assert_eq!(loaded.next_id, state.next_id);
+        assert_eq!(loaded.connecting_from_id, None);
+        assert!(loaded.selected.is_empty());
+        assert!(!loaded.settings_open);
+        assert_eq!(loaded.palette, None);
+    }
+
+    #[test]
+    fn view_maps_the_same_after_save_load() {
+        use crate::transform::Transformer;
+
+        let state = AppState {
+            offset: Vec2::new(-1234.5678, 98.7654),
+            zoom: 0.37,
+            ..AppState::default()
+        };
+        let json = serde_json::to_string(&state.to_persisted()).unwrap();
+        let loaded = AppState::from_persisted(serde_json::from_str(&json).unwrap());
+        let (before, after) = (
+            Transformer::new(state.offset, state.zoom),
+            Transformer::new(loaded.offset, loaded.zoom),
+        );
+        let p = Pos2::new(500.0, -250.0);
+        // Offsets are saved to two decimals, so screen positions stay within
+        // a hundredth of a point.
+        assert!((before.to_screen(p) - after.to_screen(p)).length() < 0.01);
+        assert!((before.from_screen(p) - after.from_screen(p)).length() < 0.01 / state.zoom);
+    }
+
+    /// The same board built in a different order: created, raised and
+    /// linked differently.
+    fn shuffled_boards() -> (AppState, AppState) {
+        let now = Local::now();
+        let mut a = AppState::default();
+        let mut b = AppState::default();
+        for state in [&mut a, &mut b] {
+            state.board_settings.stable_file_order = true;
+            state.add_note(Pos2::ZERO);
+            state.add_todo(Pos2::new(10.0, 0.0), now);
+            state.add_note(Pos2::new(20.0, 0.0));
+            state.add_todo(Pos2::new(30.0, 0.0), now);
+            for todo in state.todos.iter_mut() {
+                todo.due = Some(now);
+            }
+        }
+        for (from, to) in [(0, 1), (2, 3), (0, 3)] {
+            a.add_connection(from, to);
+        }
+        for (from, to) in [(0, 3), (2, 3), (0, 1)] {
+            b.add_connection(from, to);
+        }
+        // Raising an item moves it to the end of its list.
+        b.notes.reverse();
+        b.todos.reverse();
+        (a, b)
+    }
+
+    #[test]
+    fn stable_order_saves_identically() {
+        let (a, b) = shuffled_boards();
+        let save = |s: &AppState| serde_json::to_string_pretty(&s.to_persisted()).unwrap();
+        assert_eq!(save(&a), save(&b));
+        // In memory the order is left alone.
+        assert_eq!(b.notes[0].id, 2);
+    }
+
+    #[test]
+    fn stable_order_sorts_by_id_and_ends() {
+        let (_, b) = shuffled_boards();
+        let saved = b.to_persisted();
+        let ids: Vec<usize> = saved.notes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, [0, 2]);
+        let ids: Vec<usize> = saved.todos.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1, 3]);
+        let ends: Vec<(usize, usize)> = saved.connections.iter().map(|c| (c.from, c.to)).collect();
+        assert_eq!(ends, [(0, 1), (0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn creation_order_is_kept_with_stable_order_off() {
+        let (_, mut b) = shuffled_boards();
+        b.board_settings.stable_file_order = false;
+        let ids: Vec<usize> = b.to_persisted().notes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, [2, 0]);
+    }
+}
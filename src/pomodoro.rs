@@ -0,0 +1,68 @@
+use chrono::{DateTime, Duration, Local};
+
+use crate::AppState;
+
+pub const WORK_MINUTES: i64 = 25;
+pub const BREAK_MINUTES: i64 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+/// A running focus session on one todo. It alternates work and break
+/// phases until stopped.
+#[derive(Clone, Copy, Debug)]
+pub struct Pomodoro {
+    pub todo: usize,
+    pub phase: Phase,
+    pub ends: DateTime<Local>,
+}
+
+impl Pomodoro {
+    pub fn start(todo: usize, now: DateTime<Local>) -> Self {
+        Self {
+            todo,
+            phase: Phase::Work,
+            ends: now + Duration::minutes(WORK_MINUTES),
+        }
+    }
+
+    /// "Focus 12:34" or "Break 03:10", rounded up to the second.
+    pub fn label(&self, now: DateTime<Local>) -> String {
+        let left = ((self.ends - now).num_milliseconds().max(0) + 999) / 1000;
+        let phase = match self.phase {
+            Phase::Work => "Focus",
+            Phase::Break => "Break",
+        };
+        format!("{} {:02}:{:02}", phase, left / 60, left % 60)
+    }
+}
+
+impl AppState {
+    /// Moves the session on once its phase is over. A finished work phase is
+    /// logged on the todo. Returns the summary and body to notify with.
+    /// Sessions on todos that went away just stop.
+    pub fn tick_pomodoro(&mut self, now: DateTime<Local>) -> Option<(&'static str, String)> {
+        let session = self.pomodoro.as_mut().filter(|p| now >= p.ends)?;
+        let Some(todo) = self.todos.iter_mut().find(|t| t.id == session.todo) else {
+            self.pomodoro = None;
+            return None;
+        };
+        let summary = match session.phase {
+            Phase::Work => {
+                todo.pomodoros.push(session.ends);
+                session.phase = Phase::Break;
+                session.ends = now + Duration::minutes(BREAK_MINUTES);
+                "Focus session done, take a break"
+            }
+            Phase::Break => {
+                session.phase = Phase::Work;
+                session.ends = now + Duration::minutes(WORK_MINUTES);
+                "Break over, back to focus"
+            }
+        };
+        Some((summary, todo.text.clone()))
+    }
+}
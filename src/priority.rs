@@ -0,0 +1,47 @@
+use eframe::egui::{self, Color32, Context};
+use serde::{Deserialize, Serialize};
+
+/// How much a todo matters. Ordered, so sorting puts Urgent last; list views
+/// sort by the reverse.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub const ALL: [Self; 4] = [Self::Low, Self::Normal, Self::High, Self::Urgent];
+
+    /// Tint for the title bar; Normal todos keep the plain look.
+    pub fn color(self) -> Option<Color32> {
+        match self {
+            Self::Low => Some(Color32::from_rgb(90, 140, 200)),
+            Self::Normal => None,
+            Self::High => Some(Color32::from_rgb(230, 150, 30)),
+            Self::Urgent => Some(Color32::from_rgb(220, 50, 50)),
+        }
+    }
+}
+
+/// Tints the title bar of the item window `window` in `color`.
+pub fn draw_title_tint(ctx: &Context, window: &egui::Response, color: Color32) {
+    let style = ctx.style();
+    let height = style.spacing.interact_size.y + style.spacing.window_margin.top * 2.0;
+    let rect = egui::Rect::from_min_size(
+        window.rect.min,
+        egui::vec2(window.rect.width(), height.min(window.rect.height())),
+    );
+    let rounding = style.visuals.window_rounding;
+    ctx.layer_painter(window.layer_id).rect_filled(
+        rect,
+        egui::Rounding {
+            sw: 0.0,
+            se: 0.0,
+            ..rounding
+        },
+        color.gamma_multiply(0.35),
+    );
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Context, Key, ViewportBuilder, ViewportCommand, ViewportId};
+
+use crate::natural_date::parse_due;
+use crate::timezone::BoardZone;
+
+/// The text typed so far in the quick capture window.
+#[derive(Default)]
+pub struct QuickCapture {
+    text: String,
+    due: String,
+    /// Set on the first frame only, so the cursor starts in the text field.
+    focused: bool,
+}
+
+pub enum CaptureResult {
+    /// Add a todo with this text, and this due date if one was typed.
+    Add(String, Option<DateTime<Local>>),
+    Cancel,
+}
+
+/// A small window of its own, on top of everything and shown even while
+/// the main window is hidden, for jotting down a todo. The due date is
+/// typed in words ("tomorrow 9am"). Enter adds, Escape cancels.
+pub fn quick_capture_window(
+    ctx: &Context,
+    capture: &mut QuickCapture,
+    now: DateTime<Local>,
+    zone: BoardZone,
+) -> Option<CaptureResult> {
+    let builder = ViewportBuilder::default()
+        .with_title("Quick capture")
+        .with_inner_size([380.0, 120.0])
+        .with_resizable(false)
+        .with_always_on_top();
+    ctx.show_viewport_immediate(
+        ViewportId::from_hash_of("quick_capture"),
+        builder,
+        |ctx, _| {
+            if ctx.input(|i| i.viewport().close_requested() || i.key_pressed(Key::Escape)) {
+                return Some(CaptureResult::Cancel);
+            }
+            let mut result = None;
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let text = ui.add(
+                    egui::TextEdit::singleline(&mut capture.text)
+                        .hint_text("Todo")
+                        .desired_width(f32::INFINITY),
+                );
+                if !capture.focused {
+                    text.request_focus();
+                    ctx.send_viewport_cmd(ViewportCommand::Focus);
+                    capture.focused = true;
+                }
+                let due = ui.add(
+                    egui::TextEdit::singleline(&mut capture.due)
+                        .hint_text("Due, e.g. tomorrow 9am (optional)")
+                        .desired_width(f32::INFINITY),
+                );
+                let parsed = parse_due(&capture.due, now, zone);
+                let understood = capture.due.trim().is_empty() || parsed.is_some();
+                ui.horizontal(|ui| {
+                    let ready = understood && !capture.text.trim().is_empty();
+                    let entered = (text.lost_focus() || due.lost_focus())
+                        && ui.input(|i| i.key_pressed(Key::Enter));
+                    if ui.add_enabled(ready, egui::Button::new("Add")).clicked() || ready && entered
+                    {
+                        result = Some(CaptureResult::Add(capture.text.trim().to_string(), parsed));
+                    }
+                    match parsed {
+                        Some(due) => {
+                            ui.weak(zone.wall_clock(due).format("%a %d %b %H:%M").to_string());
+                        }
+                        None if !understood => {
+                            ui.colored_label(ui.visuals().warn_fg_color, "Not understood");
+                        }
+                        None => {}
+                    }
+                });
+            });
+            result
+        },
+    )
+}
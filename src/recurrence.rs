@@ -0,0 +1,515 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use eframe::egui::{self, Id, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::timezone::BoardZone;
+use crate::{add_months, anchored_day, anchored_wall, days_in_month};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl Unit {
+    const ALL: [Self; 4] = [Self::Days, Self::Weeks, Self::Months, Self::Years];
+}
+
+/// Which day of the month monthly rules land on.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonthDay {
+    /// The due date's day of the month.
+    SameDay,
+    /// The nth `Weekday` of the month, 1-4, or -1 for the last one.
+    NthWeekday(i8, Weekday),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum End {
+    Never,
+    /// After this many occurrences in total.
+    After(u32),
+    /// Nothing falls after this day.
+    Until(NaiveDate),
+}
+
+/// A repeat `LoopFrequency` can't express: every `interval` units, on a set
+/// of weekdays or the nth weekday of the month, until an end condition.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Recurrence {
+    pub unit: Unit,
+    pub interval: u32,
+    /// For weekly rules; empty means the due date's weekday.
+    pub weekdays: Vec<Weekday>,
+    pub month_day: MonthDay,
+    pub end: End,
+    /// Occurrences already gone by, for [`End::After`].
+    pub passed: u32,
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Self {
+            unit: Unit::Weeks,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_day: MonthDay::SameDay,
+            end: End::Never,
+            passed: 0,
+        }
+    }
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// The nth `weekday` of the month (`nth` -1 for the last), if it exists.
+fn nth_weekday(year: i32, month: u32, nth: i8, weekday: Weekday) -> Option<NaiveDate> {
+    if nth < 0 {
+        let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))?;
+        let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return Some(last - Duration::days(back as i64));
+    }
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth as u8)
+}
+
+fn ordinal(nth: i8) -> &'static str {
+    match nth {
+        1 => "first",
+        2 => "second",
+        3 => "third",
+        4 => "fourth",
+        _ => "last",
+    }
+}
+
+impl Recurrence {
+    /// The occurrence after `wall`, ignoring the end condition. `day` is the
+    /// day of the month monthly and yearly steps aim for.
+    fn step(&self, wall: NaiveDateTime, day: u32) -> NaiveDateTime {
+        let n = self.interval.max(1);
+        match self.unit {
+            Unit::Days => wall + Duration::days(n as i64),
+            Unit::Weeks if self.weekdays.is_empty() => wall + Duration::weeks(n as i64),
+            Unit::Weeks => {
+                // Weeks count from the Monday of `wall`'s week; only every
+                // nth of them has occurrences.
+                let monday = wall.date().week(Weekday::Mon).first_day();
+                let mut next = wall + Duration::days(1);
+                loop {
+                    let week = (next.date() - monday).num_days() / 7;
+                    if week % n as i64 == 0 && self.weekdays.contains(&next.weekday()) {
+                        return next;
+                    }
+                    next += Duration::days(1);
+                }
+            }
+            Unit::Months => match self.month_day {
+                MonthDay::SameDay => add_months(wall, n, day),
+                MonthDay::NthWeekday(nth, weekday) => {
+                    let target = add_months(wall, n, 1).date();
+                    nth_weekday(target.year(), target.month(), nth, weekday)
+                        .map_or(target.and_time(wall.time()), |d| d.and_time(wall.time()))
+                }
+            },
+            Unit::Years => add_months(wall, 12 * n, day),
+        }
+    }
+
+    /// Moves `current` on to the first occurrence after `now`, counting the
+    /// occurrences stepped over. `None` once the end condition is reached.
+    pub fn advance(
+        &mut self,
+        current: DateTime<Local>,
+        zone: BoardZone,
+        now: DateTime<Local>,
+        anchor: Option<u32>,
+        time_anchor: Option<NaiveTime>,
+    ) -> Option<DateTime<Local>> {
+        let mut wall = anchored_wall(time_anchor, current, zone);
+        let day = anchored_day(anchor, wall.date());
+        let mut next = current;
+        while next <= now {
+            self.passed += 1;
+            if let End::After(count) = self.end {
+                if self.passed >= count {
+                    return None;
+                }
+            }
+            wall = self.step(wall, day);
+            if let End::Until(last) = self.end {
+                if wall.date() > last {
+                    return None;
+                }
+            }
+            next = zone.instant_at(wall);
+        }
+        Some(next)
+    }
+
+    /// The rule as an RFC 5545 RRULE value, starting from the current
+    /// occurrence.
+    pub fn to_rrule(&self) -> String {
+        let freq = match self.unit {
+            Unit::Days => "DAILY",
+            Unit::Weeks => "WEEKLY",
+            Unit::Months => "MONTHLY",
+            Unit::Years => "YEARLY",
+        };
+        let mut rule = format!("FREQ={}", freq);
+        if self.interval > 1 {
+            rule += &format!(";INTERVAL={}", self.interval);
+        }
+        match (self.unit, self.month_day) {
+            (Unit::Weeks, _) if !self.weekdays.is_empty() => {
+                let days: Vec<&str> = self.weekdays.iter().map(|&d| ical_day(d)).collect();
+                rule += &format!(";BYDAY={}", days.join(","));
+            }
+            (Unit::Months, MonthDay::NthWeekday(nth, weekday)) => {
+                rule += &format!(";BYDAY={}{}", nth, ical_day(weekday));
+            }
+            _ => {}
+        }
+        match self.end {
+            End::Never => {}
+            End::After(count) => rule += &format!(";COUNT={}", count.saturating_sub(self.passed)),
+            End::Until(last) => rule += &format!(";UNTIL={}T235959", last.format("%Y%m%d")),
+        }
+        rule
+    }
+
+    /// Reads the parts of an RRULE this struct can hold: FREQ, INTERVAL,
+    /// BYDAY, COUNT and UNTIL. Returns `None` if FREQ is missing or unknown.
+    pub fn from_rrule(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix("RRULE:").unwrap_or(text);
+        let mut rule = Self {
+            weekdays: Vec::new(),
+            ..Self::default()
+        };
+        let mut unit = None;
+        for part in text.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    unit = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Unit::Days,
+                        "WEEKLY" => Unit::Weeks,
+                        "MONTHLY" => Unit::Months,
+                        "YEARLY" => Unit::Years,
+                        _ => return None,
+                    })
+                }
+                // As far as the editor goes; much further and stepping
+                // runs off the end of the calendar.
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|&n| n <= 365)?,
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let day = day.trim().to_ascii_uppercase();
+                        // By characters, as pasted text needn't be ASCII.
+                        let split = day.char_indices().rev().nth(1)?.0;
+                        let weekday = parse_ical_day(&day[split..])?;
+                        match day[..split].parse::<i8>() {
+                            Ok(nth) => {
+                                let nth = if nth < 0 { -1 } else { nth.clamp(1, 4) };
+                                rule.month_day = MonthDay::NthWeekday(nth, weekday);
+                            }
+                            Err(_) => rule.weekdays.push(weekday),
+                        }
+                    }
+                }
+                "COUNT" => rule.end = End::After(value.parse().ok()?),
+                "UNTIL" => {
+                    let date = value.get(..8)?;
+                    rule.end = End::Until(NaiveDate::parse_from_str(date, "%Y%m%d").ok()?);
+                }
+                _ => {}
+            }
+        }
+        rule.unit = unit?;
+        rule.interval = rule.interval.max(1);
+        Some(rule)
+    }
+
+    /// "Every 2 weeks on Mon, Wed, Fri until 2026-12-31".
+    pub fn describe(&self) -> String {
+        let unit = match self.unit {
+            Unit::Days => "day",
+            Unit::Weeks => "week",
+            Unit::Months => "month",
+            Unit::Years => "year",
+        };
+        let mut text = if self.interval > 1 {
+            format!("Every {} {}s", self.interval, unit)
+        } else {
+            format!("Every {}", unit)
+        };
+        match (self.unit, self.month_day) {
+            (Unit::Weeks, _) if !self.weekdays.is_empty() => {
+                let days: Vec<String> = self.weekdays.iter().map(|d| d.to_string()).collect();
+                text += &format!(" on {}", days.join(", "));
+            }
+            (Unit::Months, MonthDay::NthWeekday(nth, weekday)) => {
+                text += &format!(" on the {} {}", ordinal(nth), weekday);
+            }
+            _ => {}
+        }
+        match self.end {
+            End::Never => {}
+            End::After(count) => text += &format!(", {} times", count),
+            End::Until(last) => text += &format!(" until {}", last),
+        }
+        text
+    }
+}
+
+fn ical_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_ical_day(code: &str) -> Option<Weekday> {
+    WEEKDAYS.into_iter().find(|&d| ical_day(d) == code)
+}
+
+/// Editor for a custom rule: interval and unit, weekdays or nth weekday,
+/// end condition, and an RRULE field to paste a rule from elsewhere.
+/// `due` seeds the nth-weekday choice.
+pub fn recurrence_editor(ui: &mut Ui, id: Id, rule: &mut Recurrence, due: Option<NaiveDate>) {
+    ui.horizontal(|ui| {
+        ui.label("Every");
+        ui.add(egui::DragValue::new(&mut rule.interval).clamp_range(1..=365));
+        egui::ComboBox::from_id_source(id.with("unit"))
+            .selected_text(format!("{:?}", rule.unit).to_lowercase())
+            .show_ui(ui, |ui| {
+                for unit in Unit::ALL {
+                    ui.selectable_value(&mut rule.unit, unit, format!("{:?}", unit).to_lowercase());
+                }
+            });
+    });
+    match rule.unit {
+        Unit::Weeks => {
+            ui.horizontal(|ui| {
+                for day in WEEKDAYS {
+                    let mut on = rule.weekdays.contains(&day);
+                    if ui.toggle_value(&mut on, day.to_string()).changed() {
+                        if on {
+                            rule.weekdays.push(day);
+                            rule.weekdays.sort_by_key(|d| d.num_days_from_monday());
+                        } else {
+                            rule.weekdays.retain(|&d| d != day);
+                        }
+                    }
+                }
+            });
+        }
+        Unit::Months => {
+            ui.horizontal(|ui| {
+                let mut by_weekday = matches!(rule.month_day, MonthDay::NthWeekday(..));
+                ui.radio_value(&mut by_weekday, false, "same day");
+                ui.radio_value(&mut by_weekday, true, "on the");
+                match (by_weekday, rule.month_day) {
+                    (false, _) => rule.month_day = MonthDay::SameDay,
+                    (true, MonthDay::SameDay) => {
+                        let date = due.unwrap_or_default();
+                        let nth = ((date.day() - 1) / 7 + 1) as i8;
+                        rule.month_day =
+                            MonthDay::NthWeekday(if nth > 4 { -1 } else { nth }, date.weekday());
+                    }
+                    (true, MonthDay::NthWeekday(..)) => {}
+                }
+                if let MonthDay::NthWeekday(nth, weekday) = &mut rule.month_day {
+                    egui::ComboBox::from_id_source(id.with("nth"))
+                        .selected_text(ordinal(*nth))
+                        .show_ui(ui, |ui| {
+                            for n in [1, 2, 3, 4, -1] {
+                                ui.selectable_value(nth, n, ordinal(n));
+                            }
+                        });
+                    egui::ComboBox::from_id_source(id.with("weekday"))
+                        .selected_text(weekday.to_string())
+                        .show_ui(ui, |ui| {
+                            for day in WEEKDAYS {
+                                ui.selectable_value(weekday, day, day.to_string());
+                            }
+                        });
+                }
+            });
+        }
+        Unit::Days | Unit::Years => {}
+    }
+    ui.horizontal(|ui| {
+        ui.label("Ends");
+        let today = due.unwrap_or_default();
+        if ui.radio(rule.end == End::Never, "never").clicked() {
+            rule.end = End::Never;
+        }
+        if ui
+            .radio(matches!(rule.end, End::After(_)), "after")
+            .clicked()
+        {
+            rule.end = End::After(10);
+            rule.passed = 0;
+        }
+        if let End::After(count) = &mut rule.end {
+            ui.add(
+                egui::DragValue::new(count)
+                    .clamp_range(1..=999)
+                    .suffix(" times"),
+            );
+        }
+        if ui.radio(matches!(rule.end, End::Until(_)), "on").clicked() {
+            rule.end = End::Until(today + Duration::days(90));
+        }
+        if let End::Until(last) = &mut rule.end {
+            ui.add(egui_extras::DatePickerButton::new(last).id_source(&format!("{:?}", id)));
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("RRULE");
+        let field_id = id.with("rrule");
+        let mut text = ui
+            .data_mut(|d| d.get_temp::<String>(field_id))
+            .unwrap_or_else(|| rule.to_rrule());
+        let field = ui.add(egui::TextEdit::singleline(&mut text).desired_width(f32::INFINITY));
+        if field.lost_focus() {
+            match Recurrence::from_rrule(&text) {
+                Some(parsed) => *rule = parsed,
+                None => {
+                    ui.ctx().data_mut(|d| d.remove::<String>(field_id));
+                }
+            }
+        }
+        if field.has_focus() {
+            ui.data_mut(|d| d.insert_temp(field_id, text));
+        } else {
+            ui.data_mut(|d| d.remove::<String>(field_id));
+        }
+    });
+    ui.weak(rule.describe());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(unit: Unit, interval: u32) -> Recurrence {
+        Recurrence {
+            unit,
+            interval,
+            ..Recurrence::default()
+        }
+    }
+
+    #[test]
+    fn rrule_round_trip() {
+        let rules = [
+            rule(Unit::Days, 3),
+            Recurrence {
+                weekdays: vec![Weekday::Mon, Weekday::Wed, Weekday::Fri],
+                end: End::Until(NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()),
+                ..rule(Unit::Weeks, 2)
+            },
+            Recurrence {
+                month_day: MonthDay::NthWeekday(1, Weekday::Mon),
+                end: End::After(5),
+                ..rule(Unit::Months, 1)
+            },
+            Recurrence {
+                month_day: MonthDay::NthWeekday(-1, Weekday::Fri),
+                ..rule(Unit::Months, 1)
+            },
+            rule(Unit::Years, 1),
+        ];
+        for rule in rules {
+            let text = rule.to_rrule();
+            assert_eq!(Recurrence::from_rrule(&text), Some(rule), "{text}");
+        }
+    }
+
+    #[test]
+    fn rrule_text() {
+        let weekly = Recurrence {
+            weekdays: vec![Weekday::Tue, Weekday::Thu],
+            ..rule(Unit::Weeks, 2)
+        };
+        assert_eq!(weekly.to_rrule(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH");
+        let counted = Recurrence {
+            end: End::After(10),
+            passed: 4,
+            ..rule(Unit::Days, 1)
+        };
+        assert_eq!(counted.to_rrule(), "FREQ=DAILY;COUNT=6");
+    }
+
+    #[test]
+    fn rrule_from_elsewhere() {
+        let parsed = Recurrence::from_rrule("RRULE:freq=monthly;byday=+2we;until=20270101T000000Z");
+        assert_eq!(
+            parsed,
+            Some(Recurrence {
+                month_day: MonthDay::NthWeekday(2, Weekday::Wed),
+                end: End::Until(NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()),
+                ..rule(Unit::Months, 1)
+            })
+        );
+        let parsed = Recurrence::from_rrule("FREQ=WEEKLY;INTERVAL=0;WKST=MO").unwrap();
+        assert_eq!(parsed.interval, 1);
+    }
+
+    #[test]
+    fn bad_rrules_are_rejected() {
+        for text in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=WEEKLY;BYDAY=€",
+            "FREQ=WEEKLY;BYDAY=1€€",
+            "FREQ=WEEKLY;BYDAY=M",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=DAILY;INTERVAL=4000000000",
+            "FREQ=DAILY;COUNT=many",
+            "FREQ=DAILY;UNTIL=2026",
+            "FREQ=DAILY;garbage",
+        ] {
+            assert_eq!(Recurrence::from_rrule(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn advance_ends_after_count() {
+        let zone = BoardZone::Named(chrono_tz::UTC);
+        let at = |day: u32| {
+            zone.instant_at(
+                NaiveDate::from_ymd_opt(2026, 3, day)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap(),
+            )
+        };
+        let mut every_other_day = Recurrence {
+            end: End::After(3),
+            ..rule(Unit::Days, 2)
+        };
+        assert_eq!(every_other_day.advance(at(1), zone, at(1), None, None), Some(at(3)));
+        assert_eq!(every_other_day.advance(at(3), zone, at(3), None, None), Some(at(5)));
+        assert_eq!(every_other_day.advance(at(5), zone, at(5), None, None), None);
+    }
+}
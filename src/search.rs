@@ -0,0 +1,206 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tags::normalize_tag;
+use crate::AppState;
+
+/// Which item fields a search looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchScopes {
+    pub text: bool,
+    /// A todo's linked details note.
+    pub details: bool,
+    pub tags: bool,
+}
+impl Default for SearchScopes {
+    fn default() -> Self {
+        Self {
+            text: true,
+            details: false,
+            tags: false,
+        }
+    }
+}
+
+/// The list panel's search box. View state only.
+#[derive(Clone, Debug, Default)]
+pub struct Search {
+    pub query: String,
+    pub scopes: SearchScopes,
+    /// Move keyboard focus into the search box on the next frame.
+    pub focus: bool,
+}
+
+/// Lowercased copy of an item's text, built on the first search after the
+/// item was made or [`SearchCache::invalidate`]d. Whatever edits the text of
+/// an existing item invalidates it.
+#[derive(Clone, Debug, Default)]
+pub struct SearchCache {
+    lower: Option<String>,
+}
+impl SearchCache {
+    pub fn invalidate(&mut self) {
+        self.lower = None;
+    }
+
+    fn refresh(&mut self, text: &str) {
+        if self.lower.is_none() {
+            self.lower = Some(text.to_lowercase());
+        }
+    }
+
+    fn lower(&self) -> &str {
+        self.lower.as_deref().unwrap_or_default()
+    }
+}
+
+/// The searchable, already lowercased fields of one item.
+pub struct Searchable<'a> {
+    pub text: &'a str,
+    pub details: Option<&'a str>,
+    /// Normalized, see [`normalize_tag`].
+    pub tags: &'a [String],
+}
+
+/// Whether `item` matches the lowercased `query` in any enabled scope. Tags
+/// are matched on the normalized query, so "#Work" finds the tag `work`.
+pub fn item_matches(item: &Searchable, query: &str, scopes: SearchScopes) -> bool {
+    let tag = normalize_tag(query);
+    (scopes.text && item.text.contains(query))
+        || (scopes.details && item.details.is_some_and(|d| d.contains(query)))
+        || (scopes.tags && !tag.is_empty() && item.tags.iter().any(|t| t.contains(&tag)))
+}
+
+/// Ids of the items matching the current search, or `None` while the search
+/// box is empty.
+pub fn matching_ids(state: &mut AppState) -> Option<HashSet<usize>> {
+    let query = state.search.query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    for note in state.notes.iter_mut() {
+        note.search_cache.refresh(&note.text);
+    }
+    for todo in state.todos.iter_mut() {
+        todo.search_cache.refresh(&todo.text);
+    }
+    let notes: HashMap<usize, &str> = state
+        .notes
+        .iter()
+        .map(|n| (n.id, n.search_cache.lower()))
+        .collect();
+    let scopes = state.search.scopes;
+    let mut ids: HashSet<usize> = state
+        .notes
+        .iter()
+        .filter(|n| {
+            let item = Searchable {
+                text: n.search_cache.lower(),
+                details: None,
+                tags: &n.meta.tags,
+            };
+            item_matches(&item, &query, scopes)
+        })
+        .map(|n| n.id)
+        .collect();
+    ids.extend(
+        state
+            .todos
+            .iter()
+            .filter(|t| {
+                let item = Searchable {
+                    text: t.search_cache.lower(),
+                    details: t.details_note.and_then(|id| notes.get(&id).copied()),
+                    tags: &t.meta.tags,
+                };
+                item_matches(&item, &query, scopes)
+            })
+            .map(|t| t.id),
+    );
+    Some(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use eframe::egui::Pos2;
+
+    use super::*;
+
+    /// A note "Groceries: Milk", a todo "Call the bank" detailed by a note
+    /// "Ask about the MORTGAGE", and a todo "buy milk".
+    fn board() -> AppState {
+        let mut state = AppState::default();
+        let now = Local::now();
+        let groceries = state.add_note(Pos2::ZERO);
+        let details = state.add_note(Pos2::ZERO);
+        let call = state.add_todo(Pos2::ZERO, now);
+        let buy = state.add_todo(Pos2::ZERO, now);
+        for (id, text) in [
+            (groceries, "Groceries: Milk"),
+            (details, "Ask about the MORTGAGE"),
+            (call, "Call the bank"),
+            (buy, "buy milk"),
+        ] {
+            let text = text.to_string();
+            match state.notes.iter_mut().find(|n| n.id == id) {
+                Some(note) => note.text = text,
+                None => state.todos.iter_mut().find(|t| t.id == id).unwrap().text = text,
+            }
+        }
+        state.todos[0].details_note = Some(details);
+        state
+    }
+
+    fn search(state: &mut AppState, query: &str, details: bool) -> Option<Vec<usize>> {
+        state.search.query = query.into();
+        state.search.scopes.details = details;
+        matching_ids(state).map(|ids| {
+            let mut ids: Vec<usize> = ids.into_iter().collect();
+            ids.sort();
+            ids
+        })
+    }
+
+    #[test]
+    fn matches_notes_and_todos_ignoring_case() {
+        let mut state = board();
+        assert_eq!(search(&mut state, "MILK", false), Some(vec![0, 3]));
+        assert_eq!(search(&mut state, "  bank ", false), Some(vec![2]));
+        assert_eq!(search(&mut state, "nothing", false), Some(vec![]));
+        assert_eq!(search(&mut state, "   ", false), None);
+    }
+
+    #[test]
+    fn details_scope_reaches_the_linked_note() {
+        let mut state = board();
+        // The details note matches on its own either way.
+        assert_eq!(search(&mut state, "mortgage", false), Some(vec![1]));
+        assert_eq!(search(&mut state, "mortgage", true), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn edited_text_is_searched_again() {
+        let mut state = board();
+        assert_eq!(search(&mut state, "milk", false), Some(vec![0, 3]));
+        state.todos[1].text = "buy bread".into();
+        // Text is lowercased again only once the edit invalidates it, not
+        // on every search.
+        assert_eq!(search(&mut state, "milk", false), Some(vec![0, 3]));
+        state.todos[1].search_cache.invalidate();
+        assert_eq!(search(&mut state, "milk", false), Some(vec![0]));
+    }
+
+    #[test]
+    fn tags_scope_matches_normalized_tags() {
+        let mut state = board();
+        state.todos[0].meta.tags = vec!["work".into(), "finance".into()];
+        state.notes[0].meta.tags = vec!["home".into()];
+        assert_eq!(search(&mut state, "#Work", false), Some(vec![]));
+        state.search.scopes.tags = true;
+        assert_eq!(search(&mut state, "#Work", false), Some(vec![2]));
+        assert_eq!(search(&mut state, "fin", false), Some(vec![2]));
+        assert_eq!(search(&mut state, "home", false), Some(vec![0]));
+        // A lone `#` is no tag, so it doesn't match every tagged item.
+        assert_eq!(search(&mut state, "#", false), Some(vec![]));
+    }
+}
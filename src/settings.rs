@@ -0,0 +1,770 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Color32, Context, Vec2};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::hotkeys::{keybindings_ui, sorted_bindings, Keybindings};
+use crate::kanban::ViewMode;
+use crate::lanes::lanes_ui;
+use crate::lock::lock_settings;
+use crate::menu::MenuAction;
+use crate::notifier::{lead_label, Persistence, LEAD_PRESETS};
+use crate::sim_time::simulated_time_settings;
+use crate::timezone::BoardZone;
+use crate::{AppState, LoopFrequency};
+
+/// What clicking the link button on a second item does.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LinkClickMode {
+    /// Always link the two items (duplicates are ignored).
+    #[default]
+    AddNew,
+    /// Unlink them if they are already connected, otherwise link them.
+    Toggle,
+}
+
+/// Due time given to newly created todos.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NewTodoDue {
+    #[default]
+    None,
+    /// This many minutes after creation.
+    InMinutes(u32),
+    /// The next time the board clock shows this hour: today if it hasn't
+    /// passed yet, otherwise tomorrow.
+    AtHour(u32),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Dark or light as the system is; dark where it can't be told.
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    const ALL: [Self; 3] = [Self::System, Self::Dark, Self::Light];
+}
+
+/// App-wide preferences, kept in their own file (see [`settings_file`]) so
+/// every board opens with them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub show_connection_badges: bool,
+    pub theme: Theme,
+    /// TOML file with colors laid over the theme; see [`crate::theme::CustomTheme`].
+    pub theme_file: Option<String>,
+    /// Seconds between automatic saves of the board.
+    pub autosave_seconds: u32,
+    /// Seconds between checks for due reminders.
+    pub reminder_poll_seconds: u32,
+    /// Size new notes and todos get, in canvas units.
+    pub note_size: Vec2,
+    pub todo_size: Vec2,
+    /// Todos due within this many minutes pulse to draw attention. 0 disables.
+    pub due_soon_minutes: u32,
+    pub show_hud: bool,
+    /// Decimal places for the zoom and coordinate readouts.
+    pub hud_precision: usize,
+    pub show_minimap: bool,
+    pub show_list_panel: bool,
+    /// Order list views by todo priority, most urgent first.
+    pub sort_by_priority: bool,
+    pub view_mode: ViewMode,
+    pub show_agenda: bool,
+    /// Bottom panel summing up tracked time.
+    pub show_time_report: bool,
+    /// Focus mode leaves the focused item's linked neighbours undimmed.
+    pub focus_neighbors: bool,
+    pub curved_connections: bool,
+    /// How far bezier control points reach out, relative to the link length.
+    pub curve_tension: f32,
+    /// Context menu entries the user switched off. Anything not listed is
+    /// shown, so new actions appear without touching old settings.
+    pub disabled_menu_actions: Vec<MenuAction>,
+    pub link_click_mode: LinkClickMode,
+    /// Play a short sound when creating or completing items.
+    pub feedback_sounds: bool,
+    /// Notes show a character counter as they approach this length, turning
+    /// red past it. 0 disables the counter.
+    pub note_soft_limit: usize,
+    /// Notes can't grow past this many characters; longer pastes are cut.
+    pub note_hard_limit: usize,
+    /// Run "Tidy overlaps" every time the board is opened.
+    pub auto_arrange_on_load: bool,
+    /// Small crosshair at world (0, 0).
+    pub show_origin: bool,
+    /// Bring a window to the front when it is clicked or dragged.
+    pub raise_on_interaction: bool,
+    /// Multiplier on canvas drag distance.
+    pub pan_sensitivity: f32,
+    pub invert_pan: bool,
+    pub invert_zoom: bool,
+    /// Give a todo a due time of "now" once the todo it waits on is done.
+    pub unblocked_due_now: bool,
+    /// Completing a repeating todo moves it straight to its next occurrence,
+    /// instead of it staying ticked until its due time passes.
+    pub repeat_on_completion: bool,
+    #[serde(serialize_with = "sorted_bindings")]
+    pub keybindings: Keybindings,
+    /// Below this zoom items are drawn as plain boxes instead of windows.
+    pub lod_zoom: f32,
+    /// Items let go after a drag land on the grid.
+    pub snap_to_grid: bool,
+    /// Grid spacing in canvas units.
+    pub grid_size: f32,
+    /// Show lines where a dragged item's edges or center meet another's,
+    /// and line it up when let go.
+    pub alignment_guides: bool,
+    /// Flag linked files (like the background image) that change on disk.
+    pub watch_linked_files: bool,
+    pub new_todo_freq: LoopFrequency,
+    pub new_todo_due: NewTodoDue,
+    pub reminder_persistence: Persistence,
+    pub starred_reminder_persistence: Persistence,
+    /// Minutes before the due time todos send an early reminder, unless they
+    /// set their own. 0 disables.
+    pub remind_before_minutes: u32,
+    /// Closing the window hides it to the tray, so reminders from every
+    /// board keep firing.
+    pub run_in_background: bool,
+    /// The quick capture shortcut works from anywhere on the desktop.
+    pub global_quick_capture: bool,
+    /// Pick connection and label colors from the background's brightness.
+    pub auto_contrast: bool,
+    /// Multiplier on the background image's colors; 1 leaves it unchanged.
+    pub background_brightness: f32,
+    /// Translucent color laid over the background image.
+    pub background_overlay: Option<Color32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_connection_badges: true,
+            theme: Theme::System,
+            theme_file: None,
+            autosave_seconds: 30,
+            reminder_poll_seconds: 15,
+            note_size: Vec2::new(200.0, 100.0),
+            todo_size: Vec2::new(200.0, 150.0),
+            due_soon_minutes: 60,
+            show_hud: true,
+            hud_precision: 2,
+            show_minimap: true,
+            show_list_panel: false,
+            sort_by_priority: false,
+            view_mode: ViewMode::Canvas,
+            show_agenda: false,
+            show_time_report: false,
+            focus_neighbors: true,
+            curved_connections: false,
+            curve_tension: 0.5,
+            disabled_menu_actions: Vec::new(),
+            link_click_mode: LinkClickMode::AddNew,
+            feedback_sounds: false,
+            note_soft_limit: 10_000,
+            note_hard_limit: 100_000,
+            auto_arrange_on_load: false,
+            show_origin: true,
+            raise_on_interaction: true,
+            pan_sensitivity: 1.0,
+            invert_pan: false,
+            invert_zoom: false,
+            unblocked_due_now: false,
+            repeat_on_completion: false,
+            keybindings: Keybindings::new(),
+            lod_zoom: 0.3,
+            snap_to_grid: false,
+            grid_size: 20.0,
+            alignment_guides: true,
+            watch_linked_files: false,
+            new_todo_freq: LoopFrequency::Once,
+            new_todo_due: NewTodoDue::None,
+            reminder_persistence: Persistence::System,
+            starred_reminder_persistence: Persistence::Sticky,
+            remind_before_minutes: 0,
+            run_in_background: false,
+            global_quick_capture: true,
+            auto_contrast: true,
+            background_brightness: 1.0,
+            background_overlay: None,
+        }
+    }
+}
+
+/// Preferences that belong to one board, saved in its file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BoardSettings {
+    /// Done todos are archived after this many days. 0 disables.
+    pub archive_after_days: u32,
+    /// Deleted items stay in the trash this many days. 0 keeps them forever.
+    pub trash_retention_days: u32,
+    /// Save items and connections sorted, for boards kept under version
+    /// control.
+    pub stable_file_order: bool,
+}
+
+impl Default for BoardSettings {
+    fn default() -> Self {
+        Self {
+            archive_after_days: 0,
+            trash_retention_days: 30,
+            stable_file_order: false,
+        }
+    }
+}
+
+/// The app-wide settings file, next to the main board `main`.
+pub fn settings_file(main: &Path) -> PathBuf {
+    let stem = main.file_stem().unwrap_or_default().to_string_lossy();
+    main.with_file_name(format!("{}-settings.json", stem))
+}
+
+impl Settings {
+    pub fn menu_enabled(&self, action: MenuAction) -> bool {
+        !self.disabled_menu_actions.contains(&action)
+    }
+
+    /// The settings saved next to the main board `main`. Until there is a
+    /// settings file, the copy older versions kept inside the main board is
+    /// used; defaults if neither can be read.
+    pub fn load(main: &Path) -> Self {
+        if let Ok(json) = std::fs::read_to_string(settings_file(main)) {
+            return serde_json::from_str(&json).unwrap_or_default();
+        }
+        std::fs::read_to_string(main)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .and_then(|mut board| serde_json::from_value(board.get_mut("settings")?.take()).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, main: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(settings_file(main), json)
+    }
+}
+
+fn menu_actions_ui(ui: &mut egui::Ui, settings: &mut Settings, item_actions: bool) {
+    for action in MenuAction::ALL
+        .into_iter()
+        .filter(|a| a.is_item_action() == item_actions)
+    {
+        let mut enabled = settings.menu_enabled(action);
+        if ui.checkbox(&mut enabled, action.label()).changed() {
+            settings.disabled_menu_actions.retain(|&a| a != action);
+            if !enabled {
+                settings.disabled_menu_actions.push(action);
+            }
+        }
+    }
+}
+
+fn general_settings(ui: &mut egui::Ui, settings: &mut Settings, reload_theme: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Theme");
+        for theme in Theme::ALL {
+            ui.radio_value(&mut settings.theme, theme, format!("{:?}", theme));
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Colors from");
+        let name = settings.theme_file.as_deref().map(Path::new);
+        match name.and_then(Path::file_name) {
+            Some(name) => ui.monospace(name.to_string_lossy()),
+            None => ui.weak("no theme file"),
+        };
+        if ui.button("Choose...").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Theme", &["toml"])
+                .pick_file()
+            {
+                settings.theme_file = Some(path.display().to_string());
+            }
+        }
+        if settings.theme_file.is_some() {
+            if ui
+                .button("Reload")
+                .on_hover_text("Read the file again after editing it")
+                .clicked()
+            {
+                *reload_theme = true;
+            }
+            if ui.button("Clear").clicked() {
+                settings.theme_file = None;
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Save every");
+        ui.add(
+            egui::DragValue::new(&mut settings.autosave_seconds)
+                .clamp_range(5..=3600)
+                .suffix(" s"),
+        );
+        ui.label("and check reminders every");
+        ui.add(
+            egui::DragValue::new(&mut settings.reminder_poll_seconds)
+                .clamp_range(1..=600)
+                .suffix(" s"),
+        );
+    });
+    for (label, size) in [
+        ("New notes open at", &mut settings.note_size),
+        ("New todos open at", &mut settings.todo_size),
+    ] {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.add(egui::DragValue::new(&mut size.x).clamp_range(80.0..=2000.0));
+            ui.label("\u{00D7}");
+            ui.add(egui::DragValue::new(&mut size.y).clamp_range(40.0..=2000.0));
+        });
+    }
+}
+
+fn new_todo_defaults(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.horizontal(|ui| {
+        ui.label("New todos repeat");
+        egui::ComboBox::from_id_source("new_todo_freq")
+            .selected_text(format!("{:?}", settings.new_todo_freq))
+            .show_ui(ui, |ui| {
+                for f in LoopFrequency::ALL {
+                    ui.selectable_value(&mut settings.new_todo_freq, f, format!("{:?}", f));
+                }
+            });
+    });
+    ui.horizontal(|ui| {
+        ui.label("and are due");
+        let due = &mut settings.new_todo_due;
+        ui.radio_value(due, NewTodoDue::None, "never");
+        if ui
+            .radio(matches!(due, NewTodoDue::InMinutes(_)), "in")
+            .clicked()
+        {
+            *due = NewTodoDue::InMinutes(60);
+        }
+        if let NewTodoDue::InMinutes(m) = due {
+            ui.add(
+                egui::DragValue::new(m)
+                    .clamp_range(1..=7 * 24 * 60)
+                    .suffix(" min"),
+            );
+        }
+        if ui
+            .radio(matches!(due, NewTodoDue::AtHour(_)), "at")
+            .clicked()
+        {
+            *due = NewTodoDue::AtHour(17);
+        }
+        if let NewTodoDue::AtHour(h) = due {
+            ui.add(egui::DragValue::new(h).clamp_range(0..=23).suffix(":00"));
+        }
+    });
+}
+
+fn lead_text(minutes: u32) -> String {
+    if minutes == 0 {
+        "never".to_string()
+    } else {
+        lead_label(minutes)
+    }
+}
+
+/// The todo's early reminder: the board default, off, or one of the presets.
+pub fn remind_before_picker(ui: &mut egui::Ui, id: usize, value: &mut Option<u32>, default: u32) {
+    let default_text = format!("Default ({})", lead_text(default));
+    let text = |v: Option<u32>| v.map_or(default_text.clone(), lead_text);
+    egui::ComboBox::from_id_source(egui::Id::new("remind_before").with(id))
+        .selected_text(text(*value))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, text(None));
+            for minutes in std::iter::once(0).chain(LEAD_PRESETS) {
+                ui.selectable_value(value, Some(minutes), text(Some(minutes)));
+            }
+        });
+}
+
+fn persistence_picker(ui: &mut egui::Ui, label: &str, persistence: &mut Persistence) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.radio_value(persistence, Persistence::System, "system default");
+        ui.radio_value(persistence, Persistence::Sticky, "until dismissed");
+        if ui
+            .radio(matches!(persistence, Persistence::Seconds(_)), "for")
+            .clicked()
+        {
+            *persistence = Persistence::Seconds(10);
+        }
+        if let Persistence::Seconds(s) = persistence {
+            ui.add(egui::DragValue::new(s).clamp_range(1..=600).suffix(" s"));
+        }
+    });
+}
+
+fn background_controls(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.horizontal(|ui| {
+        ui.label("Background brightness");
+        ui.add(egui::Slider::new(
+            &mut settings.background_brightness,
+            0.0..=1.0,
+        ));
+    });
+    ui.horizontal(|ui| {
+        let mut tinted = settings.background_overlay.is_some();
+        if ui.checkbox(&mut tinted, "Tint background").changed() {
+            settings.background_overlay =
+                tinted.then(|| Color32::from_rgba_unmultiplied(0, 0, 0, 96));
+        }
+        if let Some(color) = &mut settings.background_overlay {
+            ui.color_edit_button_srgba(color);
+        }
+    });
+}
+
+/// The settings saved with the open board rather than for every board.
+fn board_settings_ui(ui: &mut egui::Ui, board: &mut BoardSettings, timezone: &mut Option<String>) {
+    timezone_picker(ui, timezone);
+    ui.horizontal(|ui| {
+        ui.label("Archive done todos after");
+        ui.add(
+            egui::DragValue::new(&mut board.archive_after_days)
+                .clamp_range(0..=365)
+                .suffix(" days"),
+        );
+        if board.archive_after_days == 0 {
+            ui.weak("(off)");
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Empty the trash after");
+        ui.add(
+            egui::DragValue::new(&mut board.trash_retention_days)
+                .clamp_range(0..=365)
+                .suffix(" days"),
+        );
+        if board.trash_retention_days == 0 {
+            ui.weak("(never)");
+        }
+    });
+    ui.checkbox(
+        &mut board.stable_file_order,
+        "Save items in a stable order (for version control)",
+    );
+}
+
+fn timezone_picker(ui: &mut egui::Ui, timezone: &mut Option<String>) {
+    ui.horizontal(|ui| {
+        ui.label("Board time zone");
+        egui::ComboBox::from_id_source("board_timezone")
+            .selected_text(timezone.as_deref().unwrap_or("System"))
+            .height(300.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(timezone, None, "System");
+                for tz in chrono_tz::TZ_VARIANTS {
+                    ui.selectable_value(timezone, Some(tz.name().to_string()), tz.name());
+                }
+            });
+    });
+}
+
+/// Where the boards are saved. Returns the folder picked to move them to.
+fn storage_settings(ui: &mut egui::Ui, data_dir: Option<&Path>) -> Option<PathBuf> {
+    ui.horizontal(|ui| {
+        ui.label("Data folder");
+        match data_dir {
+            Some(dir) => ui.monospace(dir.display().to_string()),
+            None => ui.weak("(none, nothing is saved)"),
+        };
+        if ui.button("Move data to...").clicked() {
+            FileDialog::new().pick_folder()
+        } else {
+            None
+        }
+    })
+    .inner
+}
+
+/// The settings window. `data_dir` is the folder the boards are saved in;
+/// returns the folder picked to move them to.
+pub fn settings_window(
+    ctx: &Context,
+    state: &mut AppState,
+    data_dir: Option<&Path>,
+) -> Option<PathBuf> {
+    let AppState {
+        settings,
+        board_settings,
+        settings_open,
+        timezone,
+        lock,
+        unlocked,
+        lock_input,
+        rebinding,
+        lanes,
+        simulated_now,
+        reload_theme,
+        ..
+    } = state;
+    let zone = BoardZone::parse(timezone.as_deref());
+    let mut move_data_to = None;
+    egui::Window::new("Settings")
+        .open(settings_open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            lock_settings(ui, lock, unlocked, lock_input);
+            ui.separator();
+            egui::CollapsingHeader::new("This board")
+                .default_open(true)
+                .show(ui, |ui| {
+                    // A locked board keeps its own settings; the app-wide
+                    // ones below stay editable.
+                    ui.set_enabled(lock.is_none() || *unlocked);
+                    board_settings_ui(ui, board_settings, timezone);
+                    egui::CollapsingHeader::new("Lanes").show(ui, |ui| {
+                        lanes_ui(ui, lanes);
+                    });
+                });
+            ui.separator();
+            ui.weak("The rest apply to every board.");
+            move_data_to = storage_settings(ui, data_dir);
+            general_settings(ui, settings, reload_theme);
+            simulated_time_settings(ui, simulated_now, zone);
+            ui.checkbox(
+                &mut settings.show_connection_badges,
+                "Show connection count badges",
+            );
+            ui.horizontal(|ui| {
+                ui.label("Highlight todos due within");
+                ui.add(
+                    egui::DragValue::new(&mut settings.due_soon_minutes)
+                        .clamp_range(0..=24 * 60)
+                        .suffix(" min"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.show_hud, "Show zoom/coordinate HUD");
+                ui.add_enabled(
+                    settings.show_hud,
+                    egui::DragValue::new(&mut settings.hud_precision)
+                        .clamp_range(0..=4)
+                        .suffix(" decimals"),
+                );
+            });
+            ui.checkbox(&mut settings.show_minimap, "Show the minimap");
+            ui.horizontal(|ui| {
+                ui.label("Pan speed");
+                ui.add(egui::Slider::new(&mut settings.pan_sensitivity, 0.25..=4.0));
+                ui.checkbox(&mut settings.invert_pan, "Invert pan");
+                ui.checkbox(&mut settings.invert_zoom, "Invert zoom");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Draw items as boxes below zoom");
+                ui.add(
+                    egui::DragValue::new(&mut settings.lod_zoom)
+                        .clamp_range(0.0..=1.0)
+                        .speed(0.01),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.snap_to_grid, "Snap items to a grid of");
+                ui.add_enabled(
+                    settings.snap_to_grid,
+                    egui::DragValue::new(&mut settings.grid_size).clamp_range(5.0..=200.0),
+                );
+            });
+            ui.checkbox(
+                &mut settings.alignment_guides,
+                "Show alignment guides while dragging",
+            );
+            background_controls(ui, settings);
+            ui.checkbox(&mut settings.show_origin, "Mark the canvas origin");
+            ui.checkbox(
+                &mut settings.auto_contrast,
+                "Match line colors to the background",
+            );
+            ui.checkbox(
+                &mut settings.raise_on_interaction,
+                "Bring clicked windows to the front",
+            );
+            new_todo_defaults(ui, settings);
+            ui.horizontal(|ui| {
+                ui.label("Remind");
+                egui::ComboBox::from_id_source("remind_before_default")
+                    .selected_text(lead_text(settings.remind_before_minutes))
+                    .show_ui(ui, |ui| {
+                        for minutes in std::iter::once(0).chain(LEAD_PRESETS) {
+                            ui.selectable_value(
+                                &mut settings.remind_before_minutes,
+                                minutes,
+                                lead_text(minutes),
+                            );
+                        }
+                    });
+                ui.label("before todos are due");
+            });
+            persistence_picker(ui, "Reminders stay", &mut settings.reminder_persistence);
+            persistence_picker(
+                ui,
+                "Starred reminders stay",
+                &mut settings.starred_reminder_persistence,
+            );
+            ui.checkbox(
+                &mut settings.run_in_background,
+                "Keep running in the tray when the window is closed",
+            )
+            .on_hover_text(
+                "Reminders from every board keep coming while it runs. Needs a system tray; \
+                 without one, closing the window quits.",
+            );
+            ui.checkbox(
+                &mut settings.global_quick_capture,
+                "Quick capture shortcut works in other programs too",
+            )
+            .on_hover_text("Needs X11 (or XWayland). Change the key under Keyboard shortcuts.");
+            ui.checkbox(
+                &mut settings.unblocked_due_now,
+                "Make unblocked todos due right away",
+            );
+            ui.checkbox(
+                &mut settings.repeat_on_completion,
+                "Move repeating todos to their next occurrence when completed",
+            )
+            .on_hover_text("Otherwise they stay done until their due time passes.");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.curved_connections, "Curved connections");
+                ui.add_enabled(
+                    settings.curved_connections,
+                    egui::Slider::new(&mut settings.curve_tension, 0.0..=1.5).text("tension"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Link button on connected items:");
+                ui.radio_value(&mut settings.link_click_mode, LinkClickMode::AddNew, "Keep");
+                ui.radio_value(
+                    &mut settings.link_click_mode,
+                    LinkClickMode::Toggle,
+                    "Disconnect",
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Note length: warn at");
+                ui.add(
+                    egui::DragValue::new(&mut settings.note_soft_limit)
+                        .clamp_range(0..=settings.note_hard_limit)
+                        .speed(100),
+                );
+                ui.label("max");
+                ui.add(
+                    egui::DragValue::new(&mut settings.note_hard_limit)
+                        .clamp_range(1_000..=10_000_000)
+                        .speed(1000),
+                );
+                ui.label("chars");
+            });
+            ui.checkbox(
+                &mut settings.watch_linked_files,
+                "Watch linked files for changes",
+            );
+            ui.checkbox(
+                &mut settings.auto_arrange_on_load,
+                "Tidy overlapping items when the board opens",
+            );
+            ui.checkbox(
+                &mut settings.feedback_sounds,
+                "Sound when creating or completing items",
+            );
+            egui::CollapsingHeader::new("Keyboard shortcuts").show(ui, |ui| {
+                keybindings_ui(ui, &mut settings.keybindings, rebinding);
+            });
+            egui::CollapsingHeader::new("Context menus").show(ui, |ui| {
+                ui.label("Canvas");
+                menu_actions_ui(ui, settings, false);
+                ui.separator();
+                ui.label("Items");
+                menu_actions_ui(ui, settings, true);
+            });
+        });
+    move_data_to
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::scratch_dir;
+
+    #[test]
+    fn settings_file_is_not_a_board() {
+        let dir = scratch_dir("not-a-board");
+        let main = dir.join("app_state.json");
+        Settings::default().save(&main).unwrap();
+        assert_eq!(settings_file(&main), dir.join("app_state-settings.json"));
+        assert!(crate::boards::list_boards(&main).is_empty());
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let dir = scratch_dir("round-trip");
+        let main = dir.join("app_state.json");
+        assert_eq!(Settings::load(&main), Settings::default());
+        let settings = Settings {
+            remind_before_minutes: 25,
+            auto_arrange_on_load: true,
+            ..Default::default()
+        };
+        settings.save(&main).unwrap();
+        assert_eq!(Settings::load(&main), settings);
+    }
+
+    #[test]
+    fn keybindings_save_in_a_stable_order() {
+        use crate::hotkeys::HotkeyAction;
+        use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+
+        let bindings = [
+            (HotkeyAction::AddNote, KeyboardShortcut::new(Modifiers::ALT, Key::A)),
+            (HotkeyAction::Undo, KeyboardShortcut::new(Modifiers::ALT, Key::U)),
+            (HotkeyAction::Search, KeyboardShortcut::new(Modifiers::ALT, Key::S)),
+        ];
+        let mut a = Settings::default();
+        let mut b = Settings::default();
+        a.keybindings.extend(bindings);
+        b.keybindings.extend(bindings.into_iter().rev());
+        let save = |s: &Settings| serde_json::to_string(s).unwrap();
+        assert_eq!(save(&a), save(&b));
+    }
+
+    /// Boards from before the split kept everything under `settings`: the
+    /// app-wide part is read from the main board until the file exists, and
+    /// each board keeps its own part.
+    #[test]
+    fn old_boards_migrate() {
+        let dir = scratch_dir("migrate");
+        let main = dir.join("app_state.json");
+        let old = r#"{"notes": [], "todos": [], "connections": [], "offset": [0.0, 0.0],
+            "zoom": 1.0, "next_id": 0, "background_image_path": null,
+            "settings": {"remind_before_minutes": 25, "archive_after_days": 7,
+            "trash_retention_days": 0}}"#;
+        std::fs::write(&main, old).unwrap();
+        assert_eq!(Settings::load(&main).remind_before_minutes, 25);
+        let board: crate::persist::PersistedState = serde_json::from_str(old).unwrap();
+        assert_eq!(
+            board.board_settings,
+            BoardSettings {
+                archive_after_days: 7,
+                trash_retention_days: 0,
+                stable_file_order: false,
+            }
+        );
+
+        // Once saved, the settings file wins over what the board still holds.
+        Settings::default().save(&main).unwrap();
+        assert_eq!(Settings::load(&main), Settings::default());
+    }
+}
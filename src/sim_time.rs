@@ -0,0 +1,68 @@
+use chrono::{DateTime, Local, Timelike};
+use eframe::egui::{self, Align2, Context, DragValue};
+use egui_extras::DatePickerButton;
+
+use crate::timezone::BoardZone;
+use crate::AppState;
+
+/// Settings controls for the simulated "now". Only the canvas styling
+/// follows it; reminders and anything that writes a time stay on the real
+/// clock.
+pub fn simulated_time_settings(
+    ui: &mut egui::Ui,
+    simulated: &mut Option<DateTime<Local>>,
+    zone: BoardZone,
+) {
+    ui.horizontal(|ui| {
+        let mut on = simulated.is_some();
+        if ui
+            .checkbox(&mut on, "Preview the board at")
+            .on_hover_text("Due highlights and streaks act as if it were this time")
+            .changed()
+        {
+            *simulated = on.then(Local::now);
+        }
+        let Some(at) = simulated else {
+            return;
+        };
+        let mut wall = zone.wall_clock(*at);
+        let mut date = wall.date();
+        let mut hour = wall.hour();
+        let mut changed = ui
+            .add(DatePickerButton::new(&mut date).id_source("simulated_date"))
+            .changed();
+        changed |= ui
+            .add(DragValue::new(&mut hour).clamp_range(0..=23).suffix(":00"))
+            .changed();
+        if changed {
+            wall = date.and_time(wall.time());
+            if let Some(w) = wall.with_hour(hour) {
+                *at = zone.instant_at(w);
+            }
+        }
+    });
+}
+
+/// Reminds the user that the board is being shown at a simulated time, with
+/// a way back to real time.
+pub fn simulated_time_banner(ctx: &Context, state: &mut AppState) {
+    let Some(at) = state.simulated_now else {
+        return;
+    };
+    let wall = state.zone().wall_clock(at);
+    egui::Area::new(egui::Id::new("simulated_time_banner"))
+        .anchor(Align2::CENTER_BOTTOM, [0.0, -8.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "\u{23F1} Simulated time: {}",
+                        wall.format("%Y-%m-%d %H:%M")
+                    ));
+                    if ui.button("Back to real time").clicked() {
+                        state.simulated_now = None;
+                    }
+                });
+            });
+        });
+}
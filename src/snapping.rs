@@ -0,0 +1,166 @@
+use eframe::egui::{self, Color32, Painter, Pos2, Rect, Response, Stroke, Vec2};
+
+use crate::transform::Transformer;
+use crate::AppState;
+
+/// How close, in screen pixels, an edge or center has to come to another
+/// item's to line up with it.
+const GUIDE_TOLERANCE: f32 = 6.0;
+/// Grid dots are left out once they would sit closer together than this.
+const MIN_DOT_SPACING: f32 = 8.0;
+
+/// Where a dragged item lines up with others, in canvas units: the shift
+/// that would make it line up exactly, and the line it lines up on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Guides {
+    /// Horizontal shift, and the x of the vertical guide line.
+    pub x: Option<(f32, f32)>,
+    /// Vertical shift, and the y of the horizontal guide line.
+    pub y: Option<(f32, f32)>,
+}
+
+impl Guides {
+    pub fn shift(&self) -> Vec2 {
+        Vec2::new(
+            self.x.map_or(0.0, |(dx, _)| dx),
+            self.y.map_or(0.0, |(dy, _)| dy),
+        )
+    }
+}
+
+/// The closest line-up of `moving`'s left, center or right with those of
+/// `others`, and the same for top, middle and bottom. `tolerance` is in
+/// canvas units.
+pub fn find_guides(moving: Rect, others: &[Rect], tolerance: f32) -> Guides {
+    let xs = |r: &Rect| [r.left(), r.center().x, r.right()];
+    let ys = |r: &Rect| [r.top(), r.center().y, r.bottom()];
+    let closest = |own: [f32; 3], theirs: &mut dyn Iterator<Item = [f32; 3]>| {
+        let mut best: Option<(f32, f32)> = None;
+        for lines in theirs {
+            for line in lines {
+                for edge in own {
+                    let shift = line - edge;
+                    if shift.abs() <= tolerance && best.is_none_or(|(b, _)| shift.abs() < b.abs()) {
+                        best = Some((shift, line));
+                    }
+                }
+            }
+        }
+        best
+    };
+    Guides {
+        x: closest(xs(&moving), &mut others.iter().map(xs)),
+        y: closest(ys(&moving), &mut others.iter().map(ys)),
+    }
+}
+
+pub fn snap_to_grid(pos: Pos2, grid: f32) -> Pos2 {
+    if grid <= 0.0 {
+        return pos;
+    }
+    (pos.to_vec2() / grid).round().to_pos2() * grid
+}
+
+/// Where an item dragged to `moving` lands when let go: lined up with a
+/// guide where there is one, otherwise (with `grid` set) on the grid.
+pub fn snapped_position(moving: Rect, guides: Guides, grid: Option<f32>) -> Pos2 {
+    let aligned = moving.min + guides.shift();
+    let Some(grid) = grid else {
+        return aligned;
+    };
+    let on_grid = snap_to_grid(moving.min, grid);
+    Pos2::new(
+        if guides.x.is_some() {
+            aligned.x
+        } else {
+            on_grid.x
+        },
+        if guides.y.is_some() {
+            aligned.y
+        } else {
+            on_grid.y
+        },
+    )
+}
+
+/// What item drags snap to this frame: the grid, if on, and (with guides
+/// on) every item's rect as it was when the frame began.
+pub struct Snapping {
+    grid: Option<f32>,
+    /// Empty with alignment guides off.
+    rects: Vec<(usize, Rect)>,
+    tolerance: f32,
+}
+
+impl Snapping {
+    pub fn new(state: &AppState, transformer: Transformer) -> Self {
+        let settings = &state.settings;
+        let rects = if settings.alignment_guides {
+            let notes = state.notes.iter().map(|n| (n.id, n.position, n.size));
+            let todos = state.todos.iter().map(|t| (t.id, t.position, t.size));
+            notes
+                .chain(todos)
+                .map(|(id, pos, size)| (id, Rect::from_min_size(pos, size)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            grid: settings.snap_to_grid.then_some(settings.grid_size),
+            rects,
+            tolerance: GUIDE_TOLERANCE / transformer.zoom(),
+        }
+    }
+
+    /// For item `id`'s window, now at `rect` (canvas units): the guides to
+    /// show while it is dragged, and where it lands on the frame it is let go.
+    pub fn drag(&self, id: usize, response: &Response, rect: Rect) -> (Guides, Option<Pos2>) {
+        if !response.dragged() && !response.drag_stopped() {
+            return (Guides::default(), None);
+        }
+        let others: Vec<Rect> = self
+            .rects
+            .iter()
+            .filter(|(other, _)| *other != id)
+            .map(|(_, r)| *r)
+            .collect();
+        let guides = find_guides(rect, &others, self.tolerance);
+        let landed = response
+            .drag_stopped()
+            .then(|| snapped_position(rect, guides, self.grid));
+        (guides, landed)
+    }
+}
+
+pub fn draw_guides(painter: &Painter, guides: Guides, transformer: Transformer, color: Color32) {
+    let clip = painter.clip_rect();
+    let stroke = Stroke::new(1.0, color);
+    if let Some((_, x)) = guides.x {
+        let x = transformer.to_screen(Pos2::new(x, 0.0)).x;
+        painter.vline(x, clip.y_range(), stroke);
+    }
+    if let Some((_, y)) = guides.y {
+        let y = transformer.to_screen(Pos2::new(0.0, y)).y;
+        painter.hline(clip.x_range(), y, stroke);
+    }
+}
+
+/// Faint dots on the grid points inside `canvas`, while they aren't too
+/// dense to make out.
+pub fn draw_grid(painter: &Painter, canvas: Rect, transformer: Transformer, grid: f32) {
+    let spacing = grid * transformer.zoom();
+    if grid <= 0.0 || spacing < MIN_DOT_SPACING {
+        return;
+    }
+    let first = snap_to_grid(transformer.from_screen(canvas.min), grid);
+    let color = Color32::from_gray(128).gamma_multiply(0.35);
+    let mut y = transformer.to_screen(first).y;
+    while y <= canvas.bottom() {
+        let mut x = transformer.to_screen(first).x;
+        while x <= canvas.right() {
+            painter.circle_filled(egui::pos2(x, y), 1.0, color);
+            x += spacing;
+        }
+        y += spacing;
+    }
+}
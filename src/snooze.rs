@@ -0,0 +1,57 @@
+use chrono::{DateTime, Duration, Local};
+use eframe::egui::Ui;
+
+use crate::timezone::BoardZone;
+use crate::{tomorrow_at, Todo};
+
+/// Hour "tomorrow morning" means.
+const MORNING_HOUR: u32 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Snooze {
+    TenMinutes,
+    OneHour,
+    TomorrowMorning,
+}
+
+impl Snooze {
+    const ALL: [Self; 3] = [Self::TenMinutes, Self::OneHour, Self::TomorrowMorning];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::TenMinutes => "10 minutes",
+            Self::OneHour => "1 hour",
+            Self::TomorrowMorning => "Tomorrow morning",
+        }
+    }
+
+    /// The new due time, counted from `now` rather than the old due time so
+    /// an overdue todo doesn't come straight back.
+    pub fn until(self, now: DateTime<Local>, zone: BoardZone) -> DateTime<Local> {
+        match self {
+            Self::TenMinutes => now + Duration::minutes(10),
+            Self::OneHour => now + Duration::hours(1),
+            Self::TomorrowMorning => tomorrow_at(now, zone, MORNING_HOUR),
+        }
+    }
+}
+
+/// The presets as buttons, for a "Snooze" submenu. Closes the menu on a pick.
+pub fn snooze_menu(ui: &mut Ui) -> Option<Snooze> {
+    let mut picked = None;
+    for preset in Snooze::ALL {
+        if ui.button(preset.label()).clicked() {
+            picked = Some(preset);
+            ui.close_menu();
+        }
+    }
+    picked
+}
+
+impl Todo {
+    /// Pushes the due time to `until` and re-arms its reminders.
+    pub fn snooze(&mut self, until: DateTime<Local>) {
+        self.due = Some(until);
+        self.rearm_reminders();
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use eframe::egui::{self, Color32, Rounding, Sense, Vec2};
+
+use crate::recurrence::{Recurrence, Unit};
+use crate::timezone::BoardZone;
+use crate::LoopFrequency;
+
+/// Periods shown in the strip, oldest first, ending with the current one.
+const STRIP_LEN: usize = 7;
+
+/// Length in days of one period of `freq`, or `None` for one-off todos.
+/// Custom repeats count one period per step of `rule`.
+fn period_days(freq: LoopFrequency, rule: &Recurrence) -> Option<i64> {
+    match freq {
+        LoopFrequency::Custom => {
+            let unit = match rule.unit {
+                Unit::Days => 1,
+                Unit::Weeks => 7,
+                Unit::Months => 30,
+                Unit::Years => 365,
+            };
+            Some(unit * rule.interval.max(1) as i64)
+        }
+        LoopFrequency::Once => None,
+        LoopFrequency::Daily => Some(1),
+        LoopFrequency::Monthly => Some(30),
+        LoopFrequency::Yearly => Some(365),
+        LoopFrequency::Weekly
+        | LoopFrequency::Sunday
+        | LoopFrequency::Monday
+        | LoopFrequency::Tuesday
+        | LoopFrequency::Wednesday
+        | LoopFrequency::Thursday
+        | LoopFrequency::Friday
+        | LoopFrequency::Saturday => Some(7),
+    }
+}
+
+/// How many periods back each completion falls, 0 being the current one.
+/// Extra completions within a period count once.
+fn completed_periods(
+    history: &[DateTime<Local>],
+    period: i64,
+    today: NaiveDate,
+    zone: BoardZone,
+) -> BTreeSet<i64> {
+    history
+        .iter()
+        .map(|&at| (today - zone.wall_clock(at).date()).num_days())
+        .filter(|&days_ago| days_ago >= 0)
+        .map(|days_ago| days_ago / period)
+        .collect()
+}
+
+/// Consecutive completed periods up to now, however far back they go. The
+/// current period still being open doesn't break the streak.
+fn current_streak(done: &BTreeSet<i64>) -> usize {
+    let from = if done.contains(&0) { 0 } else { 1 };
+    (from..).take_while(|ago| done.contains(ago)).count()
+}
+
+/// The longest run of consecutive completed periods ever.
+fn best_streak(done: &BTreeSet<i64>) -> usize {
+    let mut best = 0;
+    let mut run = 0;
+    let mut prev = None;
+    for &ago in done {
+        run = if prev == Some(ago - 1) { run + 1 } else { 1 };
+        best = best.max(run);
+        prev = Some(ago);
+    }
+    best
+}
+
+/// A row of squares, one per recent period of a recurring todo, filled for
+/// the periods it was completed in, followed by the current and best streak
+/// and the number of completions. Hovering a square shows its dates.
+/// Nothing is shown for one-off todos.
+pub fn streak_strip(
+    ui: &mut egui::Ui,
+    history: &[DateTime<Local>],
+    freq: LoopFrequency,
+    rule: &Recurrence,
+    now: DateTime<Local>,
+    zone: BoardZone,
+) {
+    let Some(period) = period_days(freq, rule) else {
+        return;
+    };
+    let today = zone.wall_clock(now).date();
+    let done = completed_periods(history, period, today, zone);
+    let streak = current_streak(&done);
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 3.0;
+        let on = ui.visuals().selection.bg_fill;
+        let off = ui.visuals().faint_bg_color;
+        for ago in (0..STRIP_LEN as i64).rev() {
+            let filled = done.contains(&ago);
+            let (rect, response) = ui.allocate_exact_size(Vec2::splat(10.0), Sense::hover());
+            let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+            ui.painter().rect(
+                rect,
+                Rounding::same(2.0),
+                if filled { on } else { off },
+                stroke,
+            );
+            let last = today - Duration::days(ago * period);
+            let first = last - Duration::days(period - 1);
+            let dates = if period == 1 {
+                last.format("%a %d %b").to_string()
+            } else {
+                format!(
+                    "{} \u{2013} {}",
+                    first.format("%d %b"),
+                    last.format("%d %b")
+                )
+            };
+            response.on_hover_text(format!(
+                "{}: {}",
+                dates,
+                if filled { "done" } else { "missed" }
+            ));
+        }
+        ui.add_space(4.0);
+        let color = if streak > 0 {
+            ui.visuals().text_color()
+        } else {
+            Color32::GRAY
+        };
+        ui.colored_label(color, format!("{} streak", streak));
+        ui.weak(format!(
+            "\u{00B7} best {} \u{00B7} {} done",
+            best_streak(&done),
+            history.len()
+        ));
+    });
+}
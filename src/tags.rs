@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+
+use eframe::egui::{self, Context, Id, Ui};
+
+use crate::AppState;
+
+/// Tags are compared as typed minus surrounding space, a leading `#` and case,
+/// so `#Work` and `work` are the same tag.
+pub fn normalize_tag(raw: &str) -> String {
+    raw.trim().trim_start_matches('#').trim().to_lowercase()
+}
+
+/// Every tag used on the board, sorted.
+pub fn all_tags(state: &AppState) -> BTreeSet<String> {
+    state
+        .notes
+        .iter()
+        .map(|n| &n.meta)
+        .chain(state.todos.iter().map(|t| &t.meta))
+        .flat_map(|m| m.tags.iter().cloned())
+        .collect()
+}
+
+/// The item's tags as removable chips plus a field to add one. The half-typed
+/// tag lives in egui memory under `id`, so it survives between frames without
+/// a field on the item.
+pub fn tag_editor(ui: &mut Ui, id: Id, tags: &mut Vec<String>) {
+    ui.horizontal_wrapped(|ui| {
+        let mut remove = None;
+        for (i, tag) in tags.iter().enumerate() {
+            if ui
+                .small_button(format!("#{} \u{00D7}", tag))
+                .on_hover_text("Remove tag")
+                .clicked()
+            {
+                remove = Some(i);
+            }
+        }
+        if let Some(i) = remove {
+            tags.remove(i);
+        }
+        let mut draft = ui.data_mut(|d| d.get_temp::<String>(id).unwrap_or_default());
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut draft)
+                .hint_text("+ tag")
+                .desired_width(60.0),
+        );
+        if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let tag = normalize_tag(&draft);
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+            draft.clear();
+        }
+        ui.data_mut(|d| d.insert_temp(id, draft));
+    });
+}
+
+/// Strip of every tag on the board; picking some hides items carrying none of
+/// them. Not shown on boards without tags.
+pub fn tag_filter_bar(ctx: &Context, state: &mut AppState) {
+    let tags = all_tags(state);
+    // Forget picks whose last item went away, or the board would stay
+    // filtered with nothing left to untick.
+    state.filter.tags.retain(|t| tags.contains(t));
+    if tags.is_empty() {
+        return;
+    }
+    egui::TopBottomPanel::top("tag_filter").show(ctx, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Tags:");
+            for tag in &tags {
+                let picked = state.filter.tags.contains(tag);
+                if ui.selectable_label(picked, format!("#{}", tag)).clicked() {
+                    if picked {
+                        state.filter.tags.remove(tag);
+                    } else {
+                        state.filter.tags.insert(tag.clone());
+                    }
+                }
+            }
+            if !state.filter.tags.is_empty() && ui.button("Clear").clicked() {
+                state.filter.tags.clear();
+            }
+        });
+    });
+}
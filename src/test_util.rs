@@ -0,0 +1,11 @@
+//! Helpers shared by the unit tests.
+
+use std::path::PathBuf;
+
+/// An empty directory of its own under the system temp dir.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("canvas-test-{}-{}", std::process::id(), name));
+    _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
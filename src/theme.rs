@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use eframe::egui::{Color32, Visuals};
+use serde::Deserialize;
+
+/// A `"#rrggbb"` or `"#rrggbbaa"` color in a theme file.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Hex(pub Color32);
+
+impl TryFrom<String> for Hex {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Color32::from_hex(&text)
+            .map(Self)
+            .map_err(|_| format!("\"{}\" is not a #rrggbb color", text))
+    }
+}
+
+/// Colors from a user's theme file, laid over the dark or light look. Every
+/// key may be left out:
+///
+/// ```toml
+/// dark = true             # base look; the Theme setting decides otherwise
+/// accent = "#4a90d9"      # selection and links
+/// canvas = "#1b1d21"      # canvas background
+/// note = "#2a2d33"        # note (and other window) fill
+/// todo = "#26302a"        # todo fill
+/// text = "#e0e0e0"
+/// connection = "#7aa2f7"
+/// connection_width = 2.0
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomTheme {
+    pub dark: Option<bool>,
+    pub accent: Option<Hex>,
+    pub canvas: Option<Hex>,
+    pub note: Option<Hex>,
+    pub todo: Option<Hex>,
+    pub text: Option<Hex>,
+    pub connection: Option<Hex>,
+    pub connection_width: Option<f32>,
+}
+
+impl CustomTheme {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&text).map_err(|e| e.message().to_string())
+    }
+
+    /// The dark or light visuals (`dark` unless the file names its own base)
+    /// with the file's colors set.
+    pub fn visuals(&self, dark: bool) -> Visuals {
+        let mut visuals = if self.dark.unwrap_or(dark) {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        };
+        if let Some(Hex(accent)) = self.accent {
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+        }
+        if let Some(Hex(canvas)) = self.canvas {
+            visuals.panel_fill = canvas;
+        }
+        if let Some(Hex(note)) = self.note {
+            visuals.window_fill = note;
+        }
+        if let Some(Hex(text)) = self.text {
+            visuals.override_text_color = Some(text);
+        }
+        visuals
+    }
+}
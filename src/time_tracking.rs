@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use eframe::egui::{self, Context, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::timezone::BoardZone;
+use crate::{generate_title, AppState, Todo};
+
+/// Days listed in the report, today included.
+const REPORT_DAYS: i64 = 7;
+
+/// One stretch of tracked work. `end` is `None` while the timer runs.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct TimeSpan {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
+/// "1h 05m", "12m", "0m".
+pub fn format_duration(d: Duration) -> String {
+    let minutes = d.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+impl Todo {
+    pub fn tracking(&self) -> bool {
+        self.time_log.last().is_some_and(|s| s.end.is_none())
+    }
+
+    /// Everything tracked, the running span up to `now` included.
+    pub fn tracked(&self, now: DateTime<Local>) -> Duration {
+        self.time_log
+            .iter()
+            .map(|s| s.end.unwrap_or(now) - s.start)
+            .fold(Duration::zero(), |a, b| a + b)
+    }
+
+    pub fn stop_tracking(&mut self, now: DateTime<Local>) {
+        if let Some(span) = self.time_log.last_mut().filter(|s| s.end.is_none()) {
+            span.end = Some(now.max(span.start));
+        }
+    }
+}
+
+impl AppState {
+    /// Starts the timer on todo `id`, stopping whichever one was running, so
+    /// time is only ever booked to one todo.
+    pub fn start_tracking(&mut self, id: usize, now: DateTime<Local>) {
+        for todo in self.todos.iter_mut() {
+            todo.stop_tracking(now);
+        }
+        if let Some(todo) = self.todos.iter_mut().find(|t| t.id == id) {
+            todo.time_log.push(TimeSpan {
+                start: now,
+                end: None,
+            });
+        }
+    }
+}
+
+/// Timer button and running total, for the todo window. Returns `Some(true)`
+/// when the timer should start and `Some(false)` when it should stop.
+pub fn tracking_row(ui: &mut Ui, todo: &Todo, now: DateTime<Local>) -> Option<bool> {
+    let mut toggled = None;
+    ui.horizontal(|ui| {
+        if todo.tracking() {
+            if ui.button("\u{23F9} Stop timer").clicked() {
+                toggled = Some(false);
+            }
+        } else if ui.button("\u{25B6} Start timer").clicked() {
+            toggled = Some(true);
+        }
+        if !todo.time_log.is_empty() {
+            ui.label(format!("Tracked {}", format_duration(todo.tracked(now))));
+        }
+    });
+    toggled
+}
+
+/// Tracked time per board-zone day, spans crossing midnight split between
+/// the days they touch.
+fn per_day(todo: &Todo, zone: BoardZone, now: DateTime<Local>) -> BTreeMap<NaiveDate, Duration> {
+    let mut days = BTreeMap::new();
+    for span in &todo.time_log {
+        let end = span.end.unwrap_or(now);
+        let mut start = span.start;
+        while start < end {
+            let date = zone.wall_clock(start).date();
+            let midnight = date
+                .succ_opt()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map_or(end, |m| zone.instant_at(m));
+            let until = end.min(midnight.max(start + Duration::seconds(1)));
+            *days.entry(date).or_insert_with(Duration::zero) += until - start;
+            start = until;
+        }
+    }
+    days
+}
+
+/// Bottom panel totalling tracked time for the last week, day by day, and
+/// per todo for this week and last. Archived todos count too.
+pub fn time_report_panel(ctx: &Context, state: &AppState, now: DateTime<Local>) {
+    let zone = state.zone();
+    let today = zone.wall_clock(now).date();
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let last_week = this_week - Duration::weeks(1);
+    let mut by_day: BTreeMap<NaiveDate, Duration> = BTreeMap::new();
+    let mut by_todo: Vec<(String, Duration, Duration)> = Vec::new();
+    for todo in state.todos.iter().chain(state.archived.iter()) {
+        let days = per_day(todo, zone, now);
+        let (mut this, mut last) = (Duration::zero(), Duration::zero());
+        for (&date, &d) in &days {
+            *by_day.entry(date).or_insert_with(Duration::zero) += d;
+            if date >= this_week {
+                this += d;
+            } else if date >= last_week {
+                last += d;
+            }
+        }
+        if this > Duration::zero() || last > Duration::zero() {
+            by_todo.push((generate_title(&todo.text), this, last));
+        }
+    }
+    by_todo.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+    let total = |from: NaiveDate, to: NaiveDate| {
+        by_day
+            .range(from..to)
+            .fold(Duration::zero(), |a, (_, &d)| a + d)
+    };
+
+    egui::TopBottomPanel::bottom("time_report")
+        .resizable(true)
+        .default_height(160.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Time tracked");
+                ui.label(format!(
+                    "This week {} \u{00B7} Last week {}",
+                    format_duration(total(this_week, this_week + Duration::weeks(1))),
+                    format_duration(total(last_week, this_week)),
+                ));
+            });
+            ui.columns(2, |columns| {
+                egui::Grid::new("time_by_day")
+                    .striped(true)
+                    .show(&mut columns[0], |ui| {
+                        for back in (0..REPORT_DAYS).rev() {
+                            let date = today - Duration::days(back);
+                            ui.label(date.format("%a %d %b").to_string());
+                            let d = by_day.get(&date).copied().unwrap_or_else(Duration::zero);
+                            ui.label(format_duration(d));
+                            ui.end_row();
+                        }
+                    });
+                egui::ScrollArea::vertical().show(&mut columns[1], |ui| {
+                    if by_todo.is_empty() {
+                        ui.weak("Nothing tracked in the last two weeks.");
+                        return;
+                    }
+                    egui::Grid::new("time_by_todo")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Todo");
+                            ui.strong("This week");
+                            ui.strong("Last week");
+                            ui.end_row();
+                            for (title, this, last) in &by_todo {
+                                ui.label(title);
+                                ui.label(format_duration(*this));
+                                ui.label(format_duration(*last));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        });
+}
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Local};
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+
+use crate::connection::Connection;
+use crate::{generate_title, AppState, Todo};
+
+const ROW_HEIGHT: f32 = 24.0;
+const HEADER_HEIGHT: f32 = 20.0;
+/// Narrowest a day gets before the timeline scrolls sideways instead.
+const MIN_DAY_WIDTH: f32 = 24.0;
+/// Length of bars for todos with nothing before them.
+const DEFAULT_SPAN_HOURS: i64 = 24;
+
+/// For each todo, the todos it depends on: the one it is waiting on and the
+/// start of every dependency link ending at it. Unlike [`crate::blockers`],
+/// done todos count too, since the timeline shows finished work as well.
+fn predecessors(todos: &[Todo], connections: &[Connection]) -> HashMap<usize, Vec<usize>> {
+    let ids: HashSet<usize> = todos.iter().map(|t| t.id).collect();
+    let mut before: HashMap<usize, Vec<usize>> = HashMap::new();
+    let links = todos
+        .iter()
+        .filter_map(|t| t.waiting_on.map(|w| (w, t.id)))
+        .chain(
+            connections
+                .iter()
+                .filter(|c| c.dependency)
+                .map(|c| (c.from, c.to)),
+        );
+    for (from, to) in links {
+        if from != to && ids.contains(&from) && ids.contains(&to) {
+            let list = before.entry(to).or_default();
+            if !list.contains(&from) {
+                list.push(from);
+            }
+        }
+    }
+    before
+}
+
+struct Bar<'a> {
+    todo: &'a Todo,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    /// The dated predecessor finishing last, which is what holds this one up.
+    critical_before: Option<usize>,
+}
+
+/// Bars for every dated todo: each starts when its last predecessor is due
+/// and ends at its own due date.
+fn bars<'a>(todos: &[&'a Todo], before: &HashMap<usize, Vec<usize>>) -> Vec<Bar<'a>> {
+    let due: HashMap<usize, DateTime<Local>> =
+        todos.iter().filter_map(|t| Some((t.id, t.due?))).collect();
+    todos
+        .iter()
+        .filter_map(|todo| {
+            let end = todo.due?;
+            let last = before
+                .get(&todo.id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| Some((due.get(id).copied()?, *id)))
+                .max();
+            let start = match last {
+                Some((at, _)) if at < end => at,
+                _ => end - Duration::hours(DEFAULT_SPAN_HOURS),
+            };
+            Some(Bar {
+                todo,
+                start,
+                end,
+                critical_before: last.map(|(_, id)| id),
+            })
+        })
+        .collect()
+}
+
+/// The links on each chain's critical path: walking back from every todo
+/// nothing depends on, through whichever predecessor finishes last.
+fn critical_links(bars: &[Bar], before: &HashMap<usize, Vec<usize>>) -> HashSet<(usize, usize)> {
+    let has_successor: HashSet<usize> = before.values().flatten().copied().collect();
+    let critical: HashMap<usize, usize> = bars
+        .iter()
+        .filter_map(|b| Some((b.todo.id, b.critical_before?)))
+        .collect();
+    let mut links = HashSet::new();
+    for bar in bars.iter().filter(|b| !has_successor.contains(&b.todo.id)) {
+        let mut id = bar.todo.id;
+        // `insert` failing means a cycle; stop there.
+        while let Some(&prev) = critical.get(&id) {
+            if !links.insert((prev, id)) {
+                break;
+            }
+            id = prev;
+        }
+    }
+    links
+}
+
+/// Dated todos as bars on a time axis, with dependency links between them.
+/// The critical path of each linked chain is highlighted. Returns the id of
+/// a clicked bar. Items the filter hides are left out.
+pub fn timeline_view(ui: &mut Ui, state: &AppState, now: DateTime<Local>) -> Option<usize> {
+    let zone = state.zone();
+    let todos: Vec<&Todo> = state
+        .todos
+        .iter()
+        .filter(|t| t.due.is_some() && state.filter.matches_todo(t))
+        .collect();
+    if todos.is_empty() {
+        ui.centered_and_justified(|ui| ui.weak("No todos with a due date."));
+        return None;
+    }
+    let before = predecessors(&state.todos, &state.connections);
+    let mut bars = bars(&todos, &before);
+    bars.sort_by_key(|b| (b.start, b.end, b.todo.id));
+    let critical = critical_links(&bars, &before);
+
+    // Whole days from midnight before the first start to after the last end.
+    let first_day = zone.wall_clock(bars.iter().map(|b| b.start).min()?).date();
+    let last_day = zone.wall_clock(bars.iter().map(|b| b.end).max()?).date();
+    let origin = zone.instant_at(first_day.and_hms_opt(0, 0, 0).unwrap_or_default());
+    let days = (last_day - first_day).num_days() + 1;
+    let day_width = (ui.available_width() / days as f32).max(MIN_DAY_WIDTH);
+    let x_of = |rect: Rect, t: DateTime<Local>| {
+        rect.left() + (t - origin).num_minutes() as f32 / (24.0 * 60.0) * day_width
+    };
+
+    let mut clicked = None;
+    egui::ScrollArea::both().show(ui, |ui| {
+        let size = Vec2::new(
+            days as f32 * day_width,
+            HEADER_HEIGHT + bars.len() as f32 * ROW_HEIGHT,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        let font = FontId::proportional(11.0);
+
+        // Day grid with dates; only every seventh is labelled when crowded.
+        let label_every = if day_width < 48.0 { 7 } else { 1 };
+        for day in 0..days {
+            let date = first_day + Duration::days(day);
+            let x = rect.left() + day as f32 * day_width;
+            painter.vline(x, rect.y_range(), Stroke::new(1.0, visuals.faint_bg_color));
+            if day % label_every == 0 {
+                painter.text(
+                    Pos2::new(x + 2.0, rect.top()),
+                    Align2::LEFT_TOP,
+                    date.format("%d %b").to_string(),
+                    font.clone(),
+                    visuals.weak_text_color(),
+                );
+            }
+        }
+        if (origin..=origin + Duration::days(days)).contains(&now) {
+            painter.vline(
+                x_of(rect, now),
+                rect.y_range(),
+                Stroke::new(1.5, Color32::LIGHT_RED),
+            );
+        }
+
+        let mut rects: HashMap<usize, Rect> = HashMap::new();
+        for (row, bar) in bars.iter().enumerate() {
+            let top = rect.top() + HEADER_HEIGHT + row as f32 * ROW_HEIGHT;
+            let bar_rect = Rect::from_x_y_ranges(
+                x_of(rect, bar.start)..=x_of(rect, bar.end).max(x_of(rect, bar.start) + 4.0),
+                top + 4.0..=top + ROW_HEIGHT - 4.0,
+            );
+            rects.insert(bar.todo.id, bar_rect);
+        }
+
+        for (&to, froms) in &before {
+            let Some(&end) = rects.get(&to) else {
+                continue;
+            };
+            for from in froms {
+                let Some(&start) = rects.get(from) else {
+                    continue;
+                };
+                let on_path = critical.contains(&(*from, to));
+                let stroke = if on_path {
+                    Stroke::new(2.0, visuals.warn_fg_color)
+                } else {
+                    Stroke::new(1.0, visuals.weak_text_color())
+                };
+                let a = start.right_center();
+                let b = end.left_center();
+                let reach = ((b.x - a.x).abs() / 2.0).max(12.0);
+                painter.add(Shape::CubicBezier(
+                    egui::epaint::CubicBezierShape::from_points_stroke(
+                        [a, a + Vec2::X * reach, b - Vec2::X * reach, b],
+                        false,
+                        Color32::TRANSPARENT,
+                        stroke,
+                    ),
+                ));
+                painter.add(Shape::convex_polygon(
+                    vec![b, b + Vec2::new(-6.0, -3.5), b + Vec2::new(-6.0, 3.5)],
+                    stroke.color,
+                    Stroke::NONE,
+                ));
+            }
+        }
+
+        let pointer = response.hover_pos();
+        for bar in &bars {
+            let bar_rect = rects[&bar.todo.id];
+            let on_path = critical
+                .iter()
+                .any(|&(a, b)| a == bar.todo.id || b == bar.todo.id);
+            let mut fill = if bar.todo.is_done {
+                visuals.widgets.inactive.bg_fill
+            } else if bar.end < now {
+                Color32::from_rgb(160, 60, 60)
+            } else {
+                visuals.selection.bg_fill
+            };
+            if pointer.is_some_and(|p| bar_rect.contains(p)) {
+                fill = fill.gamma_multiply(1.3);
+                if response.clicked() {
+                    clicked = Some(bar.todo.id);
+                }
+            }
+            painter.rect_filled(bar_rect, 3.0, fill);
+            if on_path {
+                painter.rect_stroke(bar_rect, 3.0, Stroke::new(1.5, visuals.warn_fg_color));
+            }
+            painter.text(
+                Pos2::new(bar_rect.right() + 4.0, bar_rect.center().y),
+                Align2::LEFT_CENTER,
+                generate_title(&bar.todo.text),
+                font.clone(),
+                if bar.todo.is_done {
+                    visuals.weak_text_color()
+                } else {
+                    visuals.text_color()
+                },
+            );
+        }
+        let hovered = pointer.and_then(|p| bars.iter().find(|b| rects[&b.todo.id].contains(p)));
+        if let Some(bar) = hovered {
+            let due = zone.wall_clock(bar.end).format("%Y-%m-%d %H:%M");
+            response.on_hover_text(format!("{}\nDue: {}", generate_title(&bar.todo.text), due));
+        }
+    });
+    clicked
+}
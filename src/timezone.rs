@@ -0,0 +1,108 @@
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// The zone a board's due dates are shown, edited and repeated in. Instants
+/// are still stored as `DateTime<Local>`; only wall-clock math goes through
+/// the board zone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardZone {
+    Local,
+    Named(Tz),
+}
+
+impl BoardZone {
+    /// Unknown names fall back to the system zone.
+    pub fn parse(name: Option<&str>) -> Self {
+        name.and_then(|n| n.parse::<Tz>().ok())
+            .map_or(BoardZone::Local, BoardZone::Named)
+    }
+
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            BoardZone::Local => None,
+            BoardZone::Named(tz) => Some(tz.name()),
+        }
+    }
+
+    pub fn wall_clock(self, t: DateTime<Local>) -> NaiveDateTime {
+        match self {
+            BoardZone::Local => t.naive_local(),
+            BoardZone::Named(tz) => t.with_timezone(&tz).naive_local(),
+        }
+    }
+
+    pub fn instant_at(self, wall: NaiveDateTime) -> DateTime<Local> {
+        match self {
+            BoardZone::Local => resolve(&Local, wall),
+            BoardZone::Named(tz) => resolve(&tz, wall).with_timezone(&Local),
+        }
+    }
+}
+
+/// Maps a wall-clock time to an instant. A time repeated by a fall-back
+/// transition resolves to its first occurrence; a time skipped by a
+/// spring-forward transition moves forward past the gap, like clocks do.
+fn resolve<Z: TimeZone>(zone: &Z, wall: NaiveDateTime) -> DateTime<Z> {
+    match zone.from_local_datetime(&wall) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t,
+        LocalResult::None => {
+            // Step back to a time before the gap, then add the same span of
+            // real time back on: 02:30 in a 02:00-03:00 gap becomes 03:30.
+            let mut back = chrono::Duration::zero();
+            loop {
+                back += chrono::Duration::minutes(30);
+                if let Some(t) = zone.from_local_datetime(&(wall - back)).earliest() {
+                    return t + back;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, Timelike};
+    use chrono_tz::America::New_York;
+
+    use super::*;
+
+    fn wall(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn spring_forward_gap_moves_past_it() {
+        // New York skips 02:00-03:00 on 2026-03-08.
+        let t = resolve(&New_York, wall(3, 8, 2, 30));
+        assert_eq!((t.hour(), t.minute()), (3, 30));
+        assert_eq!(t, resolve(&New_York, wall(3, 8, 1, 30)) + Duration::hours(1));
+    }
+
+    #[test]
+    fn fall_back_overlap_takes_the_first() {
+        // New York repeats 01:00-02:00 on 2026-11-01.
+        let first = resolve(&New_York, wall(11, 1, 1, 30));
+        let before = resolve(&New_York, wall(11, 1, 0, 30));
+        assert_eq!(first - before, Duration::hours(1));
+        assert_eq!(first.naive_local(), wall(11, 1, 1, 30));
+    }
+
+    #[test]
+    fn wall_clock_round_trips_through_named_zones() {
+        let zone = BoardZone::Named(New_York);
+        for w in [wall(1, 15, 9, 0), wall(3, 8, 3, 0), wall(7, 4, 23, 59), wall(11, 1, 2, 0)] {
+            assert_eq!(zone.wall_clock(zone.instant_at(w)), w);
+        }
+    }
+
+    #[test]
+    fn parse_falls_back_to_local() {
+        assert_eq!(BoardZone::parse(Some("America/New_York")), BoardZone::Named(New_York));
+        assert_eq!(BoardZone::parse(Some("Nowhere/Special")), BoardZone::Local);
+        assert_eq!(BoardZone::parse(None), BoardZone::Local);
+        assert_eq!(BoardZone::Named(New_York).name(), Some("America/New_York"));
+    }
+}
@@ -0,0 +1,44 @@
+use eframe::egui::{self, Align2, Context, Vec2};
+
+const TOAST_SECONDS: f64 = 4.0;
+
+struct Toast {
+    text: String,
+    shown_at: Option<f64>,
+}
+
+/// Short-lived messages stacked in the bottom-right corner of the window.
+#[derive(Default)]
+pub struct Toasts {
+    items: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.items.push(Toast {
+            text: text.into(),
+            shown_at: None,
+        });
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let now = ctx.input(|i| i.time);
+        self.items
+            .retain(|t| t.shown_at.is_none_or(|at| now - at < TOAST_SECONDS));
+        if self.items.is_empty() {
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(TOAST_SECONDS));
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-12.0, -12.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                for toast in self.items.iter_mut() {
+                    toast.shown_at.get_or_insert(now);
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(&toast.text);
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,105 @@
+use eframe::egui::{Pos2, Vec2};
+
+use crate::connection::Anchor;
+
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 10.0;
+
+/// Maps between world (canvas) and screen coordinates for one frame:
+/// `screen = world * zoom + offset`.
+///
+/// The zoom is clamped to `MIN_ZOOM..=MAX_ZOOM` on construction, and a zero,
+/// negative or non-finite zoom falls back to 1, so `from_screen` always
+/// undoes `to_screen`.
+#[derive(Clone, Copy, Debug)]
+pub struct Transformer {
+    offset: Vec2,
+    zoom: f32,
+}
+impl Transformer {
+    pub fn new(offset: Vec2, zoom: f32) -> Self {
+        let zoom = if zoom.is_finite() && zoom > 0.0 {
+            zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+        } else {
+            1.0
+        };
+        let offset = if offset.is_finite() {
+            offset
+        } else {
+            Vec2::ZERO
+        };
+        Self { offset, zoom }
+    }
+
+    pub fn zoom(self) -> f32 {
+        self.zoom
+    }
+
+    pub fn offset(self) -> Vec2 {
+        self.offset
+    }
+
+    pub fn to_screen(self, pos: Pos2) -> Pos2 {
+        pos * self.zoom + self.offset
+    }
+
+    pub fn anchor_to_screen(self, anchor: Anchor) -> Anchor {
+        Anchor {
+            pos: self.to_screen(anchor.pos),
+            ..anchor
+        }
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_screen(self, pos: Pos2) -> Pos2 {
+        (pos - self.offset) / self.zoom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn from_screen_undoes_to_screen(
+            zoom in MIN_ZOOM..=MAX_ZOOM,
+            offset in (-1.0e5f32..1.0e5, -1.0e5f32..1.0e5),
+            point in (-1.0e5f32..1.0e5, -1.0e5f32..1.0e5),
+        ) {
+            let t = Transformer::new(Vec2::new(offset.0, offset.1), zoom);
+            let p = Pos2::new(point.0, point.1);
+            let back = t.from_screen(t.to_screen(p));
+            // Relative to the largest value involved, as f32 only keeps ~7
+            // significant digits.
+            let scale = p.x.abs()
+                .max(p.y.abs())
+                .max(offset.0.abs() / zoom)
+                .max(offset.1.abs() / zoom);
+            let tolerance = scale.max(1.0) * 1e-5;
+            prop_assert!((back - p).length() <= tolerance, "{:?} came back as {:?}", p, back);
+        }
+    }
+
+    #[test]
+    fn bad_zoom_falls_back() {
+        for zoom in [0.0, -2.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(Transformer::new(Vec2::ZERO, zoom).zoom(), 1.0, "{zoom}");
+        }
+        assert_eq!(Transformer::new(Vec2::ZERO, 0.001).zoom(), MIN_ZOOM);
+        assert_eq!(Transformer::new(Vec2::ZERO, 1e6).zoom(), MAX_ZOOM);
+        let t = Transformer::new(Vec2::new(f32::NAN, 3.0), 2.0);
+        assert_eq!(t.offset(), Vec2::ZERO);
+        let p = t.from_screen(t.to_screen(Pos2::new(4.0, -4.0)));
+        assert_eq!(p, Pos2::new(4.0, -4.0));
+    }
+
+    #[test]
+    fn maps_world_to_screen() {
+        let t = Transformer::new(Vec2::new(100.0, 50.0), 2.0);
+        assert_eq!(t.to_screen(Pos2::new(10.0, -5.0)), Pos2::new(120.0, 40.0));
+        assert_eq!(t.from_screen(Pos2::new(100.0, 50.0)), Pos2::ZERO);
+    }
+}
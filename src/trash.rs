@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Local};
+use eframe::egui::{self, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::connection::Connection;
+use crate::{generate_title, AppState, Note, Todo};
+
+/// One deletion, kept so it can be undone from the trash window.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Trashed {
+    pub deleted_at: DateTime<Local>,
+    pub notes: Vec<Note>,
+    pub todos: Vec<Todo>,
+    /// Every link that touched the deleted items, including ones to items
+    /// that stayed on the board.
+    pub connections: Vec<Connection>,
+}
+
+impl Trashed {
+    /// "Shopping list", or "Shopping list and 2 more".
+    fn title(&self) -> String {
+        let mut titles = self
+            .notes
+            .iter()
+            .map(|n| generate_title(&n.text))
+            .chain(self.todos.iter().map(|t| generate_title(&t.text)));
+        let first = titles.next().unwrap_or_default();
+        match titles.count() {
+            0 => first,
+            more => format!("{} and {} more", first, more),
+        }
+    }
+}
+
+impl AppState {
+    /// Puts trash entry `index` back on the board. Links come back where the
+    /// item at their other end still exists. Returns an id to move the view
+    /// to.
+    pub fn restore_trashed(&mut self, index: usize) -> Option<usize> {
+        if index >= self.trash.len() {
+            return None;
+        }
+        let entry = self.trash.remove(index);
+        let first = entry
+            .notes
+            .first()
+            .map(|n| n.id)
+            .or(entry.todos.first().map(|t| t.id));
+        self.notes.extend(entry.notes);
+        self.todos.extend(entry.todos);
+        let ids = self.item_ids();
+        let archived: HashSet<usize> = self.archived.iter().map(|t| t.id).collect();
+        let exists = |id: &usize| ids.contains(id) || archived.contains(id);
+        for conn in entry.connections {
+            if exists(&conn.from) && exists(&conn.to) && !self.connections.contains(&conn) {
+                self.connections.push(conn);
+            }
+        }
+        first
+    }
+
+    /// Drops trash entries older than `days`. Returns how many went; `days
+    /// == 0` keeps everything.
+    pub fn purge_trash(&mut self, now: DateTime<Local>, days: u32) -> usize {
+        if days == 0 {
+            return 0;
+        }
+        let cutoff = now - Duration::days(days as i64);
+        let before = self.trash.len();
+        self.trash.retain(|t| t.deleted_at >= cutoff);
+        before - self.trash.len()
+    }
+}
+
+/// Window listing deleted items, newest first, each with Restore and
+/// Delete-for-good buttons. Returns an id of restored items, so the canvas
+/// can move to them.
+pub fn trash_window(ctx: &Context, state: &mut AppState, read_only: bool) -> Option<usize> {
+    let mut open = state.trash_open;
+    let mut restore = None;
+    let mut purge = HashSet::new();
+    let zone = state.zone();
+    let retention = state.board_settings.trash_retention_days;
+    egui::Window::new(format!("Trash ({})", state.trash.len()))
+        .id(egui::Id::new("trash_window"))
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            if retention > 0 {
+                ui.weak(format!(
+                    "Items are deleted for good after {} days.",
+                    retention
+                ));
+            }
+            if state.trash.is_empty() {
+                ui.weak("The trash is empty.");
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("trash_grid").striped(true).show(ui, |ui| {
+                        for (i, entry) in state.trash.iter().enumerate().rev() {
+                            ui.label(entry.title());
+                            let at = zone.wall_clock(entry.deleted_at);
+                            ui.weak(at.format("%Y-%m-%d %H:%M").to_string());
+                            ui.add_enabled_ui(!read_only, |ui| {
+                                if ui.button("Restore").clicked() {
+                                    restore = Some(i);
+                                }
+                                if ui
+                                    .button("Delete")
+                                    .on_hover_text("Delete for good")
+                                    .clicked()
+                                {
+                                    purge.insert(i);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+            if !read_only && ui.button("Empty trash").clicked() {
+                purge = (0..state.trash.len()).collect();
+            }
+        });
+    state.trash_open = open;
+    if !purge.is_empty() {
+        let mut i = 0;
+        state.trash.retain(|_| {
+            i += 1;
+            !purge.contains(&(i - 1))
+        });
+        return None;
+    }
+    restore.and_then(|i| state.restore_trashed(i))
+}
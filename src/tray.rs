@@ -0,0 +1,99 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use eframe::egui::Context;
+
+/// What the tray menu asks the app to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayCommand {
+    /// Show the window, or hide it if it is showing.
+    Toggle,
+    QuickAdd,
+    Quit,
+}
+
+/// The tray icon. Commands arrive on a channel and wake the UI, which
+/// handles them in its next frame.
+pub struct Tray {
+    commands: Receiver<TrayCommand>,
+    #[cfg(target_os = "linux")]
+    _handle: ksni::blocking::Handle<linux::Icon>,
+}
+
+impl Tray {
+    /// Puts the icon in the system tray. `None` where there is no tray to
+    /// put it in, so callers fall back to quitting when the window closes.
+    pub fn spawn(ctx: &Context) -> Option<Self> {
+        let (send, commands) = mpsc::channel();
+        #[cfg(target_os = "linux")]
+        {
+            use ksni::blocking::TrayMethods;
+            let icon = linux::Icon {
+                send,
+                ctx: ctx.clone(),
+            };
+            let handle = icon.spawn().ok()?;
+            Some(Self {
+                commands,
+                _handle: handle,
+            })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (send, commands, ctx);
+            None
+        }
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = TrayCommand> + '_ {
+        self.commands.try_iter()
+    }
+}
+
+fn send(to: &Sender<TrayCommand>, ctx: &Context, command: TrayCommand) {
+    _ = to.send(command);
+    ctx.request_repaint();
+}
+
+/// StatusNotifierItem over D-Bus, which KDE, GNOME (with the AppIndicator
+/// extension) and most other Linux panels show.
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    pub struct Icon {
+        pub send: Sender<TrayCommand>,
+        pub ctx: Context,
+    }
+
+    impl ksni::Tray for Icon {
+        fn id(&self) -> String {
+            env!("CARGO_PKG_NAME").into()
+        }
+        fn title(&self) -> String {
+            "Endless Canvas".into()
+        }
+        fn icon_name(&self) -> String {
+            "appointment-soon".into()
+        }
+        fn activate(&mut self, _x: i32, _y: i32) {
+            send(&self.send, &self.ctx, TrayCommand::Toggle);
+        }
+        fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+            use ksni::menu::StandardItem;
+            let item = |label: &str, command: TrayCommand| {
+                StandardItem {
+                    label: label.into(),
+                    activate: Box::new(move |icon: &mut Self| send(&icon.send, &icon.ctx, command)),
+                    ..Default::default()
+                }
+                .into()
+            };
+            vec![
+                item("Show / Hide", TrayCommand::Toggle),
+                item("Quick add todo", TrayCommand::QuickAdd),
+                ksni::MenuItem::Separator,
+                item("Quit", TrayCommand::Quit),
+            ]
+        }
+    }
+}
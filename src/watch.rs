@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use eframe::egui::Context;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches the files a board links to and remembers which of them changed
+/// or disappeared since the user last acknowledged them. One watcher covers
+/// all files; it watches their parent directories, so files that editors
+/// replace on save are still tracked.
+#[derive(Default)]
+pub struct FileWatch {
+    watcher: Option<RecommendedWatcher>,
+    events: Option<Receiver<notify::Result<notify::Event>>>,
+    files: HashSet<PathBuf>,
+    dirs: HashSet<PathBuf>,
+    changed: HashSet<PathBuf>,
+}
+
+impl FileWatch {
+    /// Watches exactly `files` from now on. Cheap when nothing changed, so it
+    /// can run every frame.
+    pub fn sync(&mut self, ctx: &Context, files: HashSet<PathBuf>) {
+        if files == self.files {
+            return;
+        }
+        if files.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        if self.watcher.is_none() {
+            let (tx, rx) = channel();
+            let repaint = ctx.clone();
+            let watcher = notify::recommended_watcher(move |event| {
+                _ = tx.send(event);
+                repaint.request_repaint();
+            });
+            let Ok(watcher) = watcher else {
+                return;
+            };
+            self.watcher = Some(watcher);
+            self.events = Some(rx);
+        }
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        let dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|f| f.parent().map(Path::to_path_buf))
+            .collect();
+        for dir in self.dirs.difference(&dirs) {
+            _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.dirs) {
+            _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        }
+        self.changed.retain(|f| files.contains(f));
+        self.dirs = dirs;
+        self.files = files;
+    }
+
+    /// Picks up pending file events and returns the files currently marked
+    /// as changed.
+    pub fn changed(&mut self) -> &HashSet<PathBuf> {
+        if let Some(events) = &self.events {
+            for event in events.try_iter().flatten() {
+                if event.kind.is_access() {
+                    continue;
+                }
+                self.changed
+                    .extend(event.paths.into_iter().filter(|p| self.files.contains(p)));
+            }
+        }
+        &self.changed
+    }
+
+    pub fn acknowledge(&mut self, file: &Path) {
+        self.changed.remove(file);
+    }
+}